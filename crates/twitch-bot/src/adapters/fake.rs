@@ -0,0 +1,103 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{app::ports::EventSource, model::Event, runtime::Shutdowner};
+
+/// A deterministic [`EventSource`] for handler tests.
+///
+/// The first `fetch` emits the given events in order and then closes the
+/// channel, so a consumer reading it returns once they are all handled. Later
+/// fetches yield a channel that is already closed.
+#[non_exhaustive]
+pub struct FakeSource {
+    events: Mutex<Vec<Event>>,
+}
+
+impl FakeSource {
+    pub fn new(events: Vec<Event>) -> Self {
+        Self {
+            events: Mutex::new(events),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSource for FakeSource {
+    async fn fetch(&self) -> Result<mpsc::Receiver<Event>> {
+        let events = std::mem::take(&mut *self.events.lock().unwrap_or_else(|e| e.into_inner()));
+        let (tx, rx) = mpsc::channel(events.len().max(1));
+        for event in events {
+            // the buffer holds every event, so this never waits
+            let _ = tx.try_send(event);
+        }
+
+        Ok(rx)
+    }
+}
+
+#[async_trait]
+impl Shutdowner for FakeSource {
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::{
+        app::{
+            dispatch::{EventRouter, Handler, request::SystemRequest},
+            test_support::{Counter, system_event},
+        },
+        runtime::{Consumer, EventConsumer},
+    };
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Handler<SystemRequest> for Recorder {
+        async fn handle(&self, request: SystemRequest) -> Result<()> {
+            self.0.lock().unwrap().push(request.event.message);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consumer_handles_every_fake_event() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let fallback = Arc::new(AtomicUsize::new(0));
+        let router = EventRouter::builder()
+            .system(Arc::new(Recorder(messages.clone())))
+            .fallback(Arc::new(Counter(fallback.clone())))
+            .build()
+            .unwrap();
+
+        let source = FakeSource::new(vec![system_event("one"), system_event("two")]);
+        let consumer = Consumer::new(router);
+        consumer.consume(source.fetch().await.unwrap()).await;
+
+        let mut messages = messages.lock().unwrap().clone();
+        messages.sort();
+        assert_eq!(messages, ["one", "two"]);
+        assert_eq!(fallback.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_second_fetch_is_closed() {
+        let source = FakeSource::new(vec![system_event("once")]);
+
+        let mut first = source.fetch().await.unwrap();
+        assert!(first.recv().await.is_some());
+        assert!(first.recv().await.is_none());
+        assert!(source.fetch().await.unwrap().recv().await.is_none());
+    }
+}
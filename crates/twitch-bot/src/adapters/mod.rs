@@ -1,2 +1,6 @@
+#[cfg(test)]
+pub mod fake;
+pub mod storage;
 pub mod system;
 pub mod twitch;
+pub mod webhook;
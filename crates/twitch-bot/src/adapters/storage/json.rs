@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::memory::with_prefix;
+use crate::app::ports::KvStore;
+
+/// Keeps entries in a flat JSON object on disk, e.g. `{"counter:deaths": "12"}`.
+///
+/// Everything is held in memory and the whole file is rewritten on every
+/// change, through a temporary file and a rename so a crash never leaves it
+/// half written. Fine for chat-driven state, not for anything hot.
+pub struct JsonKvStore {
+    path: PathBuf,
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl JsonKvStore {
+    /// Loads `path`, or starts empty when it does not exist yet.
+    pub async fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let entries = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("invalid storage file {}", path.display()))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+}
+
+#[async_trait]
+impl KvStore for JsonKvStore {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.entries.lock().await.get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().await;
+        entries.insert(key.to_string(), value);
+
+        // still holding the lock, so writes land in order
+        write_atomically(&self.path, &serde_json::to_vec_pretty(&*entries)?).await
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let mut entries = self.entries.lock().await;
+        if entries.remove(key).is_none() {
+            return Ok(());
+        }
+
+        write_atomically(&self.path, &serde_json::to_vec_pretty(&*entries)?).await
+    }
+
+    async fn list_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(with_prefix(&*self.entries.lock().await, prefix))
+    }
+}
+
+async fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, contents)
+        .await
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .with_context(|| format!("failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_entries_persist_across_reopen() {
+        let path =
+            std::env::temp_dir().join(format!("twitch-bot-storage-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = JsonKvStore::open(&path).await.unwrap();
+        assert_eq!(store.get("counter:deaths").await.unwrap(), None);
+        store.set("counter:deaths", "2".to_string()).await.unwrap();
+        store.set("quote:1", "hello".to_string()).await.unwrap();
+        store.delete("quote:1").await.unwrap();
+        drop(store);
+
+        let reopened = JsonKvStore::open(&path).await.unwrap();
+        assert_eq!(
+            reopened.list_prefix("").await.unwrap(),
+            [("counter:deaths".to_string(), "2".to_string())]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
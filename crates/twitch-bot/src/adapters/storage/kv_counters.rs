@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::app::ports::{CounterStore, KvStore};
+
+const KEY_PREFIX: &str = "counter:";
+
+/// Counters kept in a [`KvStore`] under `counter:<name>`.
+pub struct KvCounterStore {
+    store: Arc<dyn KvStore>,
+    /// `KvStore` has no compare-and-set, so increments are serialized here.
+    increments: Mutex<()>,
+}
+
+impl KvCounterStore {
+    pub fn new(store: Arc<dyn KvStore>) -> Self {
+        Self {
+            store,
+            increments: Mutex::new(()),
+        }
+    }
+
+    async fn read(&self, key: &str) -> anyhow::Result<u64> {
+        self.store
+            .get(key)
+            .await?
+            .map(|value| {
+                value
+                    .parse()
+                    .with_context(|| format!("{key} is not a number: {value:?}"))
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+}
+
+#[async_trait]
+impl CounterStore for KvCounterStore {
+    async fn get(&self, name: &str) -> anyhow::Result<u64> {
+        self.read(&format!("{KEY_PREFIX}{name}")).await
+    }
+
+    async fn increment(&self, name: &str) -> anyhow::Result<u64> {
+        let key = format!("{KEY_PREFIX}{name}");
+        let _guard = self.increments.lock().await;
+
+        let value = self.read(&key).await?.saturating_add(1);
+        self.store.set(&key, value.to_string()).await?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::storage::MemoryKvStore;
+
+    #[tokio::test]
+    async fn test_concurrent_increments_are_not_lost() {
+        let kv = Arc::new(MemoryKvStore::new());
+        let counters = Arc::new(KvCounterStore::new(kv.clone()));
+
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let counters = counters.clone();
+                tokio::spawn(async move { counters.increment("deaths").await.unwrap() })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(counters.get("deaths").await.unwrap(), 20);
+        assert_eq!(counters.get("wins").await.unwrap(), 0);
+        assert_eq!(
+            kv.get("counter:deaths").await.unwrap().as_deref(),
+            Some("20")
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use async_trait::async_trait;
+
+use crate::app::ports::KvStore;
+
+/// Keeps everything in memory, so state is lost on restart.
+#[derive(Default)]
+pub struct MemoryKvStore {
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemoryKvStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl KvStore for MemoryKvStore {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.entries().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> anyhow::Result<()> {
+        self.entries().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.entries().remove(key);
+        Ok(())
+    }
+
+    async fn list_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(with_prefix(&self.entries(), prefix))
+    }
+}
+
+pub(super) fn with_prefix(
+    entries: &BTreeMap<String, String>,
+    prefix: &str,
+) -> Vec<(String, String)> {
+    entries
+        .range(prefix.to_string()..)
+        .take_while(|(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_get_delete_and_list_prefix() {
+        let store = MemoryKvStore::new();
+        store.set("counter:wins", "3".to_string()).await.unwrap();
+        store.set("counter:deaths", "7".to_string()).await.unwrap();
+        store.set("quote:1", "hello".to_string()).await.unwrap();
+
+        assert_eq!(
+            store.get("quote:1").await.unwrap().as_deref(),
+            Some("hello")
+        );
+        assert_eq!(
+            store.list_prefix("counter:").await.unwrap(),
+            [
+                ("counter:deaths".to_string(), "7".to_string()),
+                ("counter:wins".to_string(), "3".to_string()),
+            ]
+        );
+
+        store.delete("quote:1").await.unwrap();
+        store.delete("quote:1").await.unwrap();
+        assert_eq!(store.get("quote:1").await.unwrap(), None);
+    }
+}
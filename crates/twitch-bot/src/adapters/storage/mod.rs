@@ -0,0 +1,7 @@
+mod json;
+mod kv_counters;
+mod memory;
+
+pub use json::JsonKvStore;
+pub use kv_counters::KvCounterStore;
+pub use memory::MemoryKvStore;
@@ -4,11 +4,18 @@ use std::sync::Arc;
 use twitch_sdk::TokenManager;
 use twitch_sdk::chat::sender::HelixSender;
 
-use crate::{app::ports::MessageSink, config::model::TwitchAuth, model::ChatTarget};
+use crate::{
+    app::ports::{MessageSink, WhisperSink},
+    config::model::TwitchAuth,
+    model::ChatTarget,
+};
+
+use super::ChatRoomTracker;
 
 #[non_exhaustive]
 pub struct TwitchChatSink {
     sender: HelixSender,
+    room_tracker: Option<Arc<ChatRoomTracker>>,
 }
 
 impl TwitchChatSink {
@@ -17,16 +24,48 @@ impl TwitchChatSink {
         let client_id = config.client_id.as_str();
 
         let sender = HelixSender::new(writer_id, client_id, token_manager)?;
-        Ok(Self { sender })
+        Ok(Self {
+            sender,
+            room_tracker: None,
+        })
+    }
+
+    /// Refuses messages the tracked room's modes would make Twitch drop,
+    /// instead of sending them into the void. The bot account is assumed to
+    /// follow the channel.
+    #[must_use]
+    pub fn with_room_tracker(mut self, tracker: Arc<ChatRoomTracker>) -> Self {
+        self.room_tracker = Some(tracker);
+        self
     }
 }
 
 #[async_trait]
 impl MessageSink for TwitchChatSink {
     async fn send(&self, target: &ChatTarget, message: &str) -> anyhow::Result<()> {
+        let room = self
+            .room_tracker
+            .as_ref()
+            .and_then(|tracker| tracker.snapshot(&target.broadcaster_id));
+        let sent = match room {
+            Some((restrictions, role)) => {
+                self.sender
+                    .send_checked(&target.broadcaster_id, message, &restrictions, role, true)
+                    .await
+            }
+            None => self.sender.send(&target.broadcaster_id, message).await,
+        };
+
+        sent.context("failed to send message")
+    }
+}
+
+#[async_trait]
+impl WhisperSink for TwitchChatSink {
+    async fn whisper(&self, user_id: &str, message: &str) -> anyhow::Result<()> {
         self.sender
-            .send(&target.broadcaster_id, message)
+            .send_whisper(user_id, message)
             .await
-            .context("failed to send message")
+            .context("failed to send whisper")
     }
 }
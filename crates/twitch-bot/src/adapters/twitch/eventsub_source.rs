@@ -1,57 +1,78 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use tracing::info;
-use twitch_sdk::{EventSubClient, TokenManager};
+use twitch_sdk::{EventSubClient, EventSubTopic, TokenManager};
 
 use crate::{
     app::ports::EventSource, config::model::TwitchAuth, model::Event, runtime::Shutdowner,
 };
 
-use super::mapper::map_event;
+use super::{ChatRoomTracker, mapper::map_event};
 
 const BUFFER_SIZE: usize = 100;
 
 #[non_exhaustive]
 pub struct TwitchEventSubSource {
-    client: Mutex<EventSubClient>,
+    client: EventSubClient,
     cancel_token: CancellationToken,
+    room_tracker: Option<Arc<ChatRoomTracker>>,
 }
 
 impl TwitchEventSubSource {
-    pub fn new(config: &TwitchAuth, token_manager: Arc<TokenManager>) -> Result<Self> {
-        Self::with_cancel_token(config, token_manager, CancellationToken::new())
+    /// `topics` are subscribed to on top of the ones every bot needs.
+    ///
+    /// Shares `token_manager`'s cancel token, so shutting the source down also
+    /// stops its background token refresh.
+    pub fn new(
+        config: &TwitchAuth,
+        token_manager: Arc<TokenManager>,
+        topics: &[EventSubTopic],
+    ) -> Result<Self> {
+        let cancel_token = token_manager.cancel_token();
+        Self::with_cancel_token(config, token_manager, topics, cancel_token)
     }
 
     pub fn with_cancel_token(
         config: &TwitchAuth,
         token_manager: Arc<TokenManager>,
+        topics: &[EventSubTopic],
         cancel_token: CancellationToken,
     ) -> Result<Self> {
         let client_id = config.client_id.as_str().to_string();
         let broadcaster_id = config.broadcaster_id.as_str().to_string();
         let bot_user_id = config.writer_id.as_str().to_string();
 
-        let client = Mutex::new(
+        let client = topics.iter().fold(
             EventSubClient::new(token_manager, client_id, broadcaster_id, bot_user_id)
                 .with_cancel_token(cancel_token.clone()),
+            |client, topic| client.with_topic(*topic),
         );
 
         Ok(Self {
             client,
             cancel_token,
+            room_tracker: None,
         })
     }
+
+    /// Feeds every event to `tracker` before it is mapped, so the chat sink
+    /// sharing it sees the room's current modes.
+    #[must_use]
+    pub fn with_room_tracker(mut self, tracker: Arc<ChatRoomTracker>) -> Self {
+        self.room_tracker = Some(tracker);
+        self
+    }
 }
 
 #[async_trait]
 impl Shutdowner for TwitchEventSubSource {
     async fn shutdown(&self) -> anyhow::Result<()> {
         self.cancel_token.cancel();
-        self.client.lock().await.shutdown().await?;
+        self.client.shutdown().await?;
         Ok(())
     }
 }
@@ -64,14 +85,16 @@ impl Drop for TwitchEventSubSource {
 
 #[async_trait]
 impl EventSource for TwitchEventSubSource {
-    async fn fetch(&self) -> mpsc::Receiver<Event> {
-        let mut sdk_rx = {
-            let mut guard = self.client.lock().await;
-            guard.connect().await.expect("SDK connect failed")
-        };
+    async fn fetch(&self) -> Result<mpsc::Receiver<Event>> {
+        let mut sdk_rx = self
+            .client
+            .connect()
+            .await
+            .context("failed to connect to EventSub")?;
         let (tx, rx) = mpsc::channel(BUFFER_SIZE);
 
         let cancellation_token = self.cancel_token.clone();
+        let room_tracker = self.room_tracker.clone();
 
         tokio::spawn(async move {
             loop {
@@ -86,6 +109,9 @@ impl EventSource for TwitchEventSubSource {
                     maybe_event = sdk_rx.recv() => {
                         match maybe_event {
                             Some(event) => {
+                                if let Some(tracker) = &room_tracker {
+                                    tracker.observe(&event);
+                                }
                                 let event = map_event(event);
                                 if tx.send(event).await.is_err() {
                                     info!("receiver dropped");
@@ -102,6 +128,6 @@ impl EventSource for TwitchEventSubSource {
             }
         });
 
-        rx
+        Ok(rx)
     }
 }
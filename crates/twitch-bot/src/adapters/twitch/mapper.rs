@@ -3,12 +3,19 @@ use std::time::SystemTime;
 use twitch_sdk::{TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser};
 
 use crate::model::{
-    ChatMessage, ChatTarget, Event, Platform, RewardRedemption, Role, SystemEvent, User,
+    ChatMessage, ChatTarget, Event, EventContext, Platform, RewardRedemption, Role, StreamStatus,
+    SystemEvent, User, Whisper,
 };
 
 pub(crate) fn map_event(event: TwitchEvent) -> Event {
     match event {
-        TwitchEvent::ChatMessage { user, target, text } => map_chat_message(user, target, text),
+        TwitchEvent::ChatMessage {
+            user,
+            target,
+            text,
+            message_id,
+            ..
+        } => map_chat_message(user, target, text, message_id),
         TwitchEvent::RewardRedemption {
             user,
             reward_id,
@@ -22,20 +29,42 @@ pub(crate) fn map_event(event: TwitchEvent) -> Event {
             cost,
             user_input,
             received_at: SystemTime::now(),
+            context: EventContext::default(),
+        }),
+        TwitchEvent::Whisper { user, text } => Event::Whisper(Whisper::new(map_user(user), text)),
+        TwitchEvent::StreamOnline { .. } => Event::StreamStatus(StreamStatus::new(true)),
+        TwitchEvent::StreamOffline => Event::StreamStatus(StreamStatus::new(false)),
+        TwitchEvent::Connected { .. } => Event::System(SystemEvent {
+            message: SystemEvent::CONNECTED.to_string(),
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        }),
+        // already applied to the chat sink's room tracker by the source
+        TwitchEvent::ChatSettingsUpdate { .. } => Event::System(SystemEvent {
+            message: "Chat settings updated".to_string(),
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
         }),
         _ => Event::System(SystemEvent {
             message: "Unknown event type".to_string(),
             received_at: SystemTime::now(),
+            context: EventContext::default(),
         }),
     }
 }
 
-fn map_chat_message(user: TwitchUser, target: TwitchChatTarget, text: String) -> Event {
+fn map_chat_message(
+    user: TwitchUser,
+    target: TwitchChatTarget,
+    text: String,
+    message_id: Option<String>,
+) -> Event {
     let user = map_user(user);
     let Some(target) = map_chat_target(target) else {
         return Event::System(SystemEvent {
             message: "Chat message arrived without a complete chat target".to_string(),
             received_at: SystemTime::now(),
+            context: EventContext::default(),
         });
     };
 
@@ -43,7 +72,9 @@ fn map_chat_message(user: TwitchUser, target: TwitchChatTarget, text: String) ->
         author: user,
         target,
         text,
+        message_id,
         received_at: SystemTime::now(),
+        context: EventContext::default(),
     })
 }
 
@@ -62,6 +93,7 @@ fn map_user(user: TwitchUser) -> User {
     }
 }
 
+/// Keeps only the highest badge; each bot role already includes the roles below it.
 fn map_role(role: TwitchRole) -> Role {
     match role.highest() {
         TwitchRole::BROADCASTER => Role::BROADCASTER,
@@ -71,3 +103,36 @@ fn map_role(role: TwitchRole) -> Role {
         _ => Role::PLEB,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_badge_implies_lower_roles() {
+        let role = map_role(TwitchRole::MODERATOR);
+        assert_eq!(role, Role::MODERATOR);
+        assert!(role.is_vip());
+        assert!(role.is_subscriber());
+        assert!(!role.is_broadcaster());
+
+        let mut badges = TwitchRole::empty();
+        badges.add(TwitchRole::BROADCASTER);
+        badges.add(TwitchRole::SUBSCRIBER);
+        assert!(map_role(badges).is_moderator());
+
+        assert_eq!(map_role(TwitchRole::empty()), Role::PLEB);
+    }
+
+    #[test]
+    fn test_connected_maps_to_system_marker() {
+        let event = map_event(TwitchEvent::Connected {
+            session_id: Some("session-1".to_string()),
+        });
+
+        match event {
+            Event::System(event) => assert_eq!(event.message, SystemEvent::CONNECTED),
+            other => panic!("expected a system event, got {other:?}"),
+        }
+    }
+}
@@ -1,6 +1,12 @@
 mod chat_sink;
 mod eventsub_source;
 mod mapper;
+mod moderator;
+mod replay_source;
+mod room_tracker;
 
 pub use chat_sink::TwitchChatSink;
 pub use eventsub_source::TwitchEventSubSource;
+pub use moderator::TwitchModerator;
+pub use replay_source::ReplaySource;
+pub use room_tracker::ChatRoomTracker;
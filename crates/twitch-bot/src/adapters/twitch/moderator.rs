@@ -0,0 +1,54 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use twitch_sdk::{TokenManager, helix::HelixClient};
+
+use crate::{app::ports::ChatModerator, config::model::TwitchAuth, model::ChatTarget};
+
+/// Moderates through Helix as the bot account, which must be a moderator in the channel.
+#[non_exhaustive]
+pub struct TwitchModerator {
+    client: HelixClient,
+    moderator_id: String,
+}
+
+impl TwitchModerator {
+    pub fn new(config: &TwitchAuth, token_manager: Arc<TokenManager>) -> anyhow::Result<Self> {
+        let client = HelixClient::new(config.client_id.as_str(), token_manager)?;
+        Ok(Self {
+            client,
+            moderator_id: config.writer_id.as_str().to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ChatModerator for TwitchModerator {
+    async fn delete_message(&self, target: &ChatTarget, message_id: &str) -> anyhow::Result<()> {
+        self.client
+            .delete_chat_message(&target.broadcaster_id, &self.moderator_id, message_id)
+            .await
+            .context("failed to delete message")
+    }
+
+    async fn timeout(
+        &self,
+        target: &ChatTarget,
+        user_id: &str,
+        duration: Duration,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let duration_secs = u32::try_from(duration.as_secs()).unwrap_or(u32::MAX);
+        self.client
+            .timeout_user(
+                &target.broadcaster_id,
+                &self.moderator_id,
+                user_id,
+                duration_secs,
+                reason,
+            )
+            .await
+            .context("failed to time out user")
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use serde::Deserialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use twitch_sdk::{TwitchEvent, eventsub::parse_eventsub_message, irc::parse_irc_messages};
+
+use crate::{
+    app::ports::EventSource,
+    config::model::{ReplayConfig, ReplaySpeed},
+    model::Event,
+    runtime::Shutdowner,
+};
+
+use super::mapper::map_event;
+
+const BUFFER_SIZE: usize = 100;
+
+/// Feeds a recorded session back through the SDK parsers.
+///
+/// The file holds one raw message per line: IRC lines as received, or EventSub
+/// WebSocket messages as single-line JSON. The channel closes at the end of the file.
+#[non_exhaustive]
+pub struct ReplaySource {
+    path: PathBuf,
+    speed: ReplaySpeed,
+    cancel_token: CancellationToken,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordedMessage {
+    metadata: RecordedMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordedMetadata {
+    message_timestamp: String,
+}
+
+impl ReplaySource {
+    pub fn new(config: &ReplayConfig) -> Result<Self> {
+        let path = PathBuf::from(config.path.as_str());
+        if !path.is_file() {
+            bail!("replay file not found: {}", path.display());
+        }
+
+        Ok(Self {
+            path,
+            speed: config.speed,
+            cancel_token: CancellationToken::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Shutdowner for ReplaySource {
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.cancel_token.cancel();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSource for ReplaySource {
+    async fn fetch(&self) -> Result<mpsc::Receiver<Event>> {
+        let (tx, rx) = mpsc::channel(BUFFER_SIZE);
+        let path = self.path.clone();
+        let speed = self.speed;
+        let cancel_token = self.cancel_token.clone();
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel_token.cancelled() => info!("replay cancelled, stopping..."),
+                result = replay(path, speed, tx) => match result {
+                    Ok(count) => info!("replay finished after {} events", count),
+                    Err(error) => warn!("replay stopped: {:?}", error),
+                },
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+async fn replay(path: PathBuf, speed: ReplaySpeed, tx: mpsc::Sender<Event>) -> Result<usize> {
+    let file = File::open(&path)
+        .await
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+    let mut previous_sent_at = None;
+    let mut count = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (sent_at, events) = match parse_line(line) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                warn!("skipping unreadable replay line: {:?}", error);
+                continue;
+            }
+        };
+
+        if speed == ReplaySpeed::RealTime
+            && let (Some(previous), Some(current)) = (previous_sent_at, sent_at)
+            && let Ok(gap) = current.duration_since(previous)
+        {
+            tokio::time::sleep(gap).await;
+        }
+        previous_sent_at = sent_at.or(previous_sent_at);
+
+        for event in events {
+            if tx.send(map_event(event)).await.is_err() {
+                info!("receiver dropped");
+                return Ok(count);
+            }
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Parses one recorded line into its events and, when present, the time Twitch sent it.
+fn parse_line(line: &str) -> Result<(Option<SystemTime>, Vec<TwitchEvent>)> {
+    if line.starts_with('{') {
+        let recorded: RecordedMessage =
+            serde_json::from_str(line).context("invalid EventSub message")?;
+        let sent_at = OffsetDateTime::parse(&recorded.metadata.message_timestamp, &Rfc3339)
+            .ok()
+            .map(SystemTime::from);
+        let events = parse_eventsub_message(line)?.into_iter().collect();
+        return Ok((sent_at, events));
+    }
+
+    Ok((irc_sent_at(line), parse_irc_messages(line)))
+}
+
+fn irc_sent_at(line: &str) -> Option<SystemTime> {
+    let tags = line.strip_prefix('@')?.split_once(' ')?.0;
+    let millis = tags
+        .split(';')
+        .find_map(|tag| tag.strip_prefix("tmi-sent-ts="))?
+        .parse()
+        .ok()?;
+
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::config::model::ReplayPath;
+
+    const IRC_LINE: &str = "@display-name=Viewer;room-id=1337;tmi-sent-ts=1700000000000;user-id=1 \
+                            :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :!ping";
+
+    fn eventsub_line(timestamp: &str) -> String {
+        serde_json::json!({
+            "metadata": {
+                "message_id": "1",
+                "message_type": "notification",
+                "message_timestamp": timestamp,
+                "subscription_type": "channel.channel_points_custom_reward_redemption.add",
+                "subscription_version": "1"
+            },
+            "payload": {
+                "event": {
+                    "user_id": "2",
+                    "user_name": "Redeemer",
+                    "user_input": "",
+                    "reward": { "id": "r1", "title": "Hydrate", "cost": 100 }
+                }
+            }
+        })
+        .to_string()
+    }
+
+    /// A file in the temp dir, removed on drop.
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn write_replay(name: &str, lines: &[&str]) -> (TempFile, ReplaySource) {
+        let path = std::env::temp_dir().join(format!(
+            "twitch-bot-replay-{}-{name}.log",
+            std::process::id()
+        ));
+        let mut file = std::fs::File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+
+        let config = ReplayConfig {
+            path: ReplayPath::try_from(path.display().to_string()).unwrap(),
+            speed: ReplaySpeed::AsFastAsPossible,
+        };
+        let source = ReplaySource::new(&config).unwrap();
+        (TempFile(path), source)
+    }
+
+    #[tokio::test]
+    async fn test_replays_irc_and_eventsub_lines_in_order() {
+        let eventsub = eventsub_line("2023-11-14T22:13:21Z");
+        let (_path, source) =
+            write_replay("mixed", &[IRC_LINE, "", "PING :tmi.twitch.tv", &eventsub]);
+
+        let mut rx = source.fetch().await.unwrap();
+
+        match rx.recv().await {
+            Some(Event::ChatMessage(message)) => assert_eq!(message.text, "!ping"),
+            other => panic!("expected a chat message, got {other:?}"),
+        }
+        match rx.recv().await {
+            Some(Event::RewardRedemption(redemption)) => {
+                assert_eq!(redemption.reward_title, "Hydrate")
+            }
+            other => panic!("expected a reward redemption, got {other:?}"),
+        }
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_recorded_timestamps() {
+        let (sent_at, _) = parse_line(IRC_LINE).unwrap();
+        assert_eq!(
+            sent_at,
+            Some(UNIX_EPOCH + Duration::from_millis(1_700_000_000_000))
+        );
+
+        let (sent_at, events) = parse_line(&eventsub_line("2023-11-14T22:13:21Z")).unwrap();
+        assert_eq!(
+            sent_at,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_001))
+        );
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_missing_file_is_rejected() {
+        let config = ReplayConfig {
+            path: ReplayPath::try_from("/nonexistent/replay.log".to_string()).unwrap(),
+            speed: ReplaySpeed::RealTime,
+        };
+        assert!(ReplaySource::new(&config).is_err());
+    }
+}
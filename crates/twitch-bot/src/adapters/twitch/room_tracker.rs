@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+
+use twitch_sdk::{TwitchEvent, TwitchRole, chat::restrictions::ChatRestrictions};
+
+/// Follows the broadcaster's chat modes and the bot account's role in that
+/// chat, so [`TwitchChatSink`](super::TwitchChatSink) can refuse messages
+/// Twitch would silently drop.
+///
+/// The modes come from `channel.chat_settings.update`. The role is known up
+/// front when the bot posts as the broadcaster, and is otherwise learned from
+/// the badges on the bot's own chat messages; until then nothing is checked.
+pub struct ChatRoomTracker {
+    broadcaster_id: String,
+    writer_id: String,
+    state: Mutex<TrackedRoom>,
+}
+
+#[derive(Default)]
+struct TrackedRoom {
+    restrictions: ChatRestrictions,
+    role: Option<TwitchRole>,
+}
+
+impl ChatRoomTracker {
+    pub fn new(broadcaster_id: impl Into<String>, writer_id: impl Into<String>) -> Self {
+        let broadcaster_id = broadcaster_id.into();
+        let writer_id = writer_id.into();
+        let role = (writer_id == broadcaster_id).then_some(TwitchRole::BROADCASTER);
+
+        Self {
+            broadcaster_id,
+            writer_id,
+            state: Mutex::new(TrackedRoom {
+                role,
+                ..TrackedRoom::default()
+            }),
+        }
+    }
+
+    pub fn observe(&self, event: &TwitchEvent) {
+        let mut state = self.state.lock().expect("room tracker lock poisoned");
+        state.restrictions.apply(event);
+        if let TwitchEvent::ChatMessage { user, .. } = event
+            && user.id == self.writer_id
+        {
+            state.role = Some(user.role);
+        }
+    }
+
+    /// The modes of `broadcaster_id`'s chat and the bot's role there, or
+    /// `None` when that isn't the tracked chat or the role isn't known yet.
+    pub fn snapshot(&self, broadcaster_id: &str) -> Option<(ChatRestrictions, TwitchRole)> {
+        if broadcaster_id != self.broadcaster_id {
+            return None;
+        }
+
+        let state = self.state.lock().expect("room tracker lock poisoned");
+        state.role.map(|role| (state.restrictions, role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subs_only() -> TwitchEvent {
+        TwitchEvent::ChatSettingsUpdate {
+            emote_mode: false,
+            follower_mode: false,
+            follower_mode_duration: None,
+            slow_mode: false,
+            slow_mode_wait_time: None,
+            subscriber_mode: true,
+            unique_chat_mode: false,
+        }
+    }
+
+    #[test]
+    fn test_broadcaster_account_is_tracked_from_the_start() {
+        let tracker = ChatRoomTracker::new("1337", "1337");
+        tracker.observe(&subs_only());
+
+        let (restrictions, role) = tracker.snapshot("1337").unwrap();
+        assert!(restrictions.subs_only);
+        assert_eq!(role, TwitchRole::BROADCASTER);
+        assert!(tracker.snapshot("other").is_none());
+    }
+
+    #[test]
+    fn test_other_accounts_wait_for_their_role() {
+        let tracker = ChatRoomTracker::new("1337", "42");
+        tracker.observe(&subs_only());
+
+        assert!(tracker.snapshot("1337").is_none());
+    }
+}
@@ -0,0 +1,3 @@
+mod sink;
+
+pub use sink::WebhookSink;
@@ -0,0 +1,248 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Context;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Url, header::CONTENT_TYPE};
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::{app::ports::EventPublisher, config::model::WebhookConfig, model::Event};
+
+const QUEUE_SIZE: usize = 256;
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Hex-encoded HMAC-SHA256 of the request body, keyed with the webhook secret.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// POSTs every published event as JSON to an external URL.
+///
+/// Deliveries run on a background task behind a bounded queue. Events are
+/// dropped and counted when the queue is full or every retry failed, so a slow
+/// or unreachable receiver never holds up dispatch.
+#[non_exhaustive]
+pub struct WebhookSink {
+    queue: mpsc::Sender<Vec<u8>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl WebhookSink {
+    pub fn new(config: &WebhookConfig) -> anyhow::Result<Self> {
+        let url = Url::parse(config.url.as_str()).context("invalid webhook url")?;
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let delivery = Delivery {
+            client,
+            url,
+            secret: config.secret.as_str().as_bytes().to_vec(),
+        };
+
+        let (queue, rx) = mpsc::channel(QUEUE_SIZE);
+        let dropped = Arc::new(AtomicU64::new(0));
+        tokio::spawn(delivery.run(rx, dropped.clone()));
+
+        Ok(Self { queue, dropped })
+    }
+
+    /// Number of events that never reached the receiver.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// The sink goes away with its consumer at shutdown, so this is where the
+/// run's losses are summed up. Deliveries still queued or retrying at that
+/// point aren't counted.
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        match self.dropped() {
+            0 => info!("webhook sink closed, no events dropped"),
+            dropped => warn!(
+                dropped,
+                "webhook sink closed, some events were never delivered"
+            ),
+        }
+    }
+}
+
+impl EventPublisher for WebhookSink {
+    fn publish(&self, event: &Event) {
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(err) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("failed to serialize event for webhook: {}", err);
+                return;
+            }
+        };
+
+        if self.queue.try_send(body).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("webhook queue is full, dropping event");
+        }
+    }
+}
+
+struct Delivery {
+    client: Client,
+    url: Url,
+    secret: Vec<u8>,
+}
+
+impl Delivery {
+    async fn run(self, mut queue: mpsc::Receiver<Vec<u8>>, dropped: Arc<AtomicU64>) {
+        while let Some(body) = queue.recv().await {
+            if let Err(err) = self.send_with_retries(body).await {
+                dropped.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "dropping event after {} webhook attempts: {:#}",
+                    MAX_ATTEMPTS, err
+                );
+            }
+        }
+    }
+
+    async fn send_with_retries(&self, body: Vec<u8>) -> anyhow::Result<()> {
+        let signature = sign(&self.secret, &body);
+        let mut attempt = 1;
+
+        loop {
+            match self.post(body.clone(), &signature).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt >= MAX_ATTEMPTS => return Err(err),
+                Err(err) => {
+                    debug!("webhook attempt {} failed: {:#}", attempt, err);
+                    tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn post(&self, body: Vec<u8>, signature: &str) -> anyhow::Result<()> {
+        self.client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::app::test_support::system_event;
+    use crate::config::model::{WebhookSecret, WebhookUrl};
+
+    const SECRET: &str = "s3cret";
+
+    fn config(url: String) -> WebhookConfig {
+        WebhookConfig {
+            url: WebhookUrl::try_from(url).unwrap(),
+            secret: WebhookSecret::try_from(SECRET.to_string()).unwrap(),
+        }
+    }
+
+    /// Accepts a single HTTP request, answers 200 and returns (signature header, body).
+    async fn receive_one(listener: TcpListener) -> (String, Vec<u8>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+
+        let header_end = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    key.eq_ignore_ascii_case(name)
+                        .then(|| value.trim().to_string())
+                })
+                .unwrap_or_default()
+        };
+        let content_length: usize = header("content-length").parse().unwrap();
+
+        while request.len() < header_end + content_length {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .await
+            .unwrap();
+
+        (header(SIGNATURE_HEADER), request[header_end..].to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_events_arrive_as_signed_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let sink = WebhookSink::new(&config(url)).unwrap();
+
+        sink.publish(&system_event("hello"));
+        let (signature, body) = tokio::time::timeout(Duration::from_secs(5), receive_one(listener))
+            .await
+            .expect("webhook was not delivered");
+
+        assert_eq!(signature, sign(SECRET.as_bytes(), &body));
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["type"], "system");
+        assert_eq!(json["message"], "hello");
+        assert_eq!(sink.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_events_are_dropped_and_counted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        let sink = WebhookSink::new(&config(url)).unwrap();
+
+        sink.publish(&system_event("lost"));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sink.dropped() == 0 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("event was never counted as dropped");
+    }
+}
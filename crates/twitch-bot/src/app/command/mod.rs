@@ -4,7 +4,11 @@ mod parser;
 
 pub(crate) use invocation::CommandInvocation;
 pub(crate) use name::CommandName;
-pub(crate) use parser::CommandParser;
+pub(crate) use parser::{CommandParser, DEFAULT_COMMAND_PREFIX};
 
+pub(crate) const HELP_COMMAND_NAME: &str = "help";
+pub(crate) const LAST_SEEN_COMMAND_NAME: &str = "lastseen";
 pub(crate) const MUSIC_COMMAND_NAME: &str = "music";
+pub(crate) const PING_COMMAND_NAME: &str = "ping";
+pub(crate) const QUOTE_COMMAND_NAME: &str = "quote";
 pub(crate) const SKIP_COMMAND_NAME: &str = "skip";
@@ -1,17 +1,246 @@
+use std::collections::HashMap;
+
 use super::{CommandInvocation, CommandName};
 
-pub(crate) struct CommandParser;
+pub(crate) const DEFAULT_COMMAND_PREFIX: &str = "!";
+
+/// Detects commands in chat text by their leading prefix, or optionally by a
+/// leading `@botnick` mention.
+///
+/// Channels can override the default prefix; lookups ignore ASCII case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CommandParser {
+    prefix: String,
+    channel_prefixes: HashMap<String, String>,
+    mention: Option<String>,
+}
+
+impl Default for CommandParser {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMMAND_PREFIX)
+    }
+}
 
 impl CommandParser {
-    pub fn parse_chat_text(text: &str) -> Option<CommandInvocation> {
-        text.strip_prefix('!')
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            channel_prefixes: HashMap::new(),
+            mention: None,
+        }
+    }
+
+    /// Also treats `@nick <command> [args]` as a command; the prefix after the
+    /// mention is optional.
+    pub fn with_mention(mut self, nick: impl Into<String>) -> Self {
+        self.mention = Some(nick.into());
+        self
+    }
+
+    /// Uses `prefix` instead of the default one for messages in `channel`.
+    pub fn with_channel_prefix(
+        mut self,
+        channel: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Self {
+        let channel = channel.into().to_ascii_lowercase();
+        self.channel_prefixes.insert(channel, prefix.into());
+        self
+    }
+
+    pub fn prefix_for(&self, channel: &str) -> &str {
+        self.channel_prefixes
+            .get(&channel.to_ascii_lowercase())
+            .unwrap_or(&self.prefix)
+    }
+
+    /// Parses with the default prefix, ignoring per-channel overrides.
+    #[cfg(test)]
+    pub fn parse_chat_text(&self, text: &str) -> Option<CommandInvocation> {
+        self.parse_with_prefix(&self.prefix, text)
+    }
+
+    pub fn parse_in_channel(&self, channel: &str, text: &str) -> Option<CommandInvocation> {
+        self.parse_with_prefix(self.prefix_for(channel), text)
+    }
+
+    fn parse_with_prefix(&self, prefix: &str, text: &str) -> Option<CommandInvocation> {
+        self.strip_mention(prefix, text)
+            .or_else(|| text.strip_prefix(prefix))
             .filter(|s| !s.is_empty())
             .and_then(|rest| {
-                let mut parts = rest.split_whitespace();
-                parts.next().map(|name| CommandInvocation {
+                let rest = rest.trim_start();
+                let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+                (!name.is_empty()).then(|| CommandInvocation {
                     name: CommandName::from(name),
-                    args: parts.map(String::from).collect(),
+                    args: split_args(args),
                 })
             })
     }
+
+    fn strip_mention<'a>(&self, prefix: &str, text: &'a str) -> Option<&'a str> {
+        let nick = self.mention.as_deref()?;
+        let rest = text.strip_prefix('@')?;
+
+        let (head, rest) = (rest.get(..nick.len())?, &rest[nick.len()..]);
+        if !head.eq_ignore_ascii_case(nick) {
+            return None;
+        }
+
+        // `@botnick, help` and `@botnick: help` are common ways to address someone
+        let rest = rest.strip_prefix([',', ':']).unwrap_or(rest);
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        let rest = rest.trim_start();
+        Some(rest.strip_prefix(prefix).unwrap_or(rest))
+    }
+}
+
+/// Splits on whitespace, keeping double-quoted groups together as one argument.
+///
+/// An unbalanced quote swallows the rest of the line into a single argument.
+fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (arg, tail) = quoted.split_once('"').unwrap_or((quoted, ""));
+            args.push(arg.to_string());
+            rest = tail.trim_start();
+        } else {
+            let (arg, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            args.push(arg.to_string());
+            rest = tail.trim_start();
+        }
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> CommandInvocation {
+        CommandParser::default()
+            .parse_chat_text(text)
+            .expect("expected a command")
+    }
+
+    #[test]
+    fn test_unquoted_args() {
+        let command = parse("!so cool streamer");
+        assert_eq!(command.name.as_str(), "so");
+        assert_eq!(command.args, vec!["cool", "streamer"]);
+    }
+
+    #[test]
+    fn test_quoted_arg() {
+        let command = parse(r#"!title "Hello World""#);
+        assert_eq!(command.name.as_str(), "title");
+        assert_eq!(command.args, vec!["Hello World"]);
+    }
+
+    #[test]
+    fn test_mixed_args() {
+        let command = parse(r#"!so "cool streamer" now   please"#);
+        assert_eq!(command.args, vec!["cool streamer", "now", "please"]);
+    }
+
+    #[test]
+    fn test_unbalanced_quote_takes_rest_of_line() {
+        let command = parse(r#"!title "Hello World and more"#);
+        assert_eq!(command.args, vec!["Hello World and more"]);
+    }
+
+    #[test]
+    fn test_empty_quotes_yield_empty_arg() {
+        let command = parse(r#"!title """#);
+        assert_eq!(command.args, vec![""]);
+    }
+
+    #[test]
+    fn test_no_args() {
+        let command = parse("!music");
+        assert_eq!(command.name.as_str(), "music");
+        assert!(command.args.is_empty());
+    }
+
+    #[test]
+    fn test_not_a_command() {
+        let parser = CommandParser::default();
+        assert!(parser.parse_chat_text("hello").is_none());
+        assert!(parser.parse_chat_text("!").is_none());
+        assert!(parser.parse_chat_text("! ").is_none());
+    }
+
+    #[test]
+    fn test_custom_prefix() {
+        let parser = CommandParser::new("?");
+        let command = parser
+            .parse_chat_text("?so streamer")
+            .expect("expected a command");
+        assert_eq!(command.name.as_str(), "so");
+        assert_eq!(command.args, vec!["streamer"]);
+        assert!(parser.parse_chat_text("!so streamer").is_none());
+    }
+
+    #[test]
+    fn test_multi_char_prefix() {
+        let parser = CommandParser::new("!!");
+        let command = parser
+            .parse_chat_text("!!music")
+            .expect("expected a command");
+        assert_eq!(command.name.as_str(), "music");
+        assert!(parser.parse_chat_text("!music").is_none());
+    }
+
+    #[test]
+    fn test_mention() {
+        let parser = CommandParser::default().with_mention("mybot");
+
+        let command = parser
+            .parse_chat_text("@mybot help me")
+            .expect("expected a command");
+        assert_eq!(command.name.as_str(), "help");
+        assert_eq!(command.args, vec!["me"]);
+
+        let command = parser
+            .parse_chat_text("@MyBot, !help")
+            .expect("expected a command");
+        assert_eq!(command.name.as_str(), "help");
+
+        assert!(parser.parse_chat_text("!help").is_some());
+    }
+
+    #[test]
+    fn test_mention_requires_exact_nick() {
+        let parser = CommandParser::default().with_mention("mybot");
+        assert!(parser.parse_chat_text("@mybotfan help").is_none());
+        assert!(parser.parse_chat_text("@otherbot help").is_none());
+        assert!(parser.parse_chat_text("@mybot").is_none());
+        assert!(
+            CommandParser::default()
+                .parse_chat_text("@mybot help")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_per_channel_prefix() {
+        let parser = CommandParser::default().with_channel_prefix("Channel_B", "?");
+
+        assert!(parser.parse_in_channel("channel_a", "!ping").is_some());
+        assert!(parser.parse_in_channel("channel_a", "?ping").is_none());
+
+        assert!(parser.parse_in_channel("channel_b", "!ping").is_none());
+        let command = parser
+            .parse_in_channel("channel_b", "?ping")
+            .expect("expected a command");
+        assert_eq!(command.name.as_str(), "ping");
+    }
 }
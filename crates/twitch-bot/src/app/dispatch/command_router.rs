@@ -28,6 +28,10 @@ impl CommandRouter {
 }
 
 impl CommandRouterBuilder {
+    /// Wraps the whole router, so it runs for every route and the fallback.
+    ///
+    /// Router interceptors run in the order added, before routing and so
+    /// outside any `HandlerStack` built around a single route's handler.
     #[allow(dead_code)]
     pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor<CommandRequest>>) -> Self {
         self.interceptors.push(interceptor);
@@ -1,29 +1,49 @@
 use std::sync::Arc;
 
-use anyhow::{Context, Result};
+use anyhow::{Result, bail, ensure};
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 
 use crate::{
-    app::dispatch::{
-        projector::{project_chat, project_reward, project_system},
-        request::{ChatRequest, RewardRequest, SystemRequest},
-        route::Route,
-        traits::{Handler, Interceptor, apply_interceptors},
+    app::{
+        command::CommandParser,
+        dispatch::{
+            projector::{
+                project_chat, project_donation, project_reward, project_stream_status,
+                project_system, project_whisper,
+            },
+            request::{
+                ChatRequest, DonationRequest, RewardRequest, StreamStatusRequest, SystemRequest,
+                WhisperRequest,
+            },
+            route::Route,
+            traits::{Handler, Interceptor, apply_interceptors},
+        },
     },
     model::Event,
 };
 
 pub(crate) struct EventRouter {
-    chat_handler: Arc<dyn Handler<ChatRequest>>,
-    reward_handler: Arc<dyn Handler<RewardRequest>>,
-    system_handler: Arc<dyn Handler<SystemRequest>>,
+    command_parser: Arc<ArcSwap<CommandParser>>,
+    chat_handler: Option<Arc<dyn Handler<ChatRequest>>>,
+    reward_handler: Option<Arc<dyn Handler<RewardRequest>>>,
+    donation_handler: Option<Arc<dyn Handler<DonationRequest>>>,
+    whisper_handler: Option<Arc<dyn Handler<WhisperRequest>>>,
+    stream_status_handler: Option<Arc<dyn Handler<StreamStatusRequest>>>,
+    system_handler: Option<Arc<dyn Handler<SystemRequest>>>,
+    fallback_handler: Option<Arc<dyn Handler<Event>>>,
 }
 
 #[derive(Default)]
 pub(crate) struct EventRouterBuilder {
+    command_parser: Option<Arc<ArcSwap<CommandParser>>>,
     chat_handler: Option<Arc<dyn Handler<ChatRequest>>>,
     reward_handler: Option<Arc<dyn Handler<RewardRequest>>>,
+    donation_handler: Option<Arc<dyn Handler<DonationRequest>>>,
+    whisper_handler: Option<Arc<dyn Handler<WhisperRequest>>>,
+    stream_status_handler: Option<Arc<dyn Handler<StreamStatusRequest>>>,
     system_handler: Option<Arc<dyn Handler<SystemRequest>>>,
+    fallback_handler: Option<Arc<dyn Handler<Event>>>,
     interceptors: Vec<Arc<dyn Interceptor<Event>>>,
 }
 
@@ -34,12 +54,19 @@ impl EventRouter {
 }
 
 impl EventRouterBuilder {
-    #[allow(dead_code)]
     pub fn interceptor(mut self, interceptor: Arc<dyn Interceptor<Event>>) -> Self {
         self.interceptors.push(interceptor);
         self
     }
 
+    /// Overrides how chat text is split into commands; defaults to the `!` prefix.
+    /// The parser is read from the shared slot on every message, so it can be
+    /// replaced at runtime.
+    pub fn shared_command_parser(mut self, parser: Arc<ArcSwap<CommandParser>>) -> Self {
+        self.command_parser = Some(parser);
+        self
+    }
+
     pub fn chat(mut self, handler: Arc<dyn Handler<ChatRequest>>) -> Self {
         self.chat_handler = Some(handler);
         self
@@ -50,27 +77,82 @@ impl EventRouterBuilder {
         self
     }
 
+    pub fn donation(mut self, handler: Arc<dyn Handler<DonationRequest>>) -> Self {
+        self.donation_handler = Some(handler);
+        self
+    }
+
+    pub fn whisper(mut self, handler: Arc<dyn Handler<WhisperRequest>>) -> Self {
+        self.whisper_handler = Some(handler);
+        self
+    }
+
+    pub fn stream_status(mut self, handler: Arc<dyn Handler<StreamStatusRequest>>) -> Self {
+        self.stream_status_handler = Some(handler);
+        self
+    }
+
     pub fn system(mut self, handler: Arc<dyn Handler<SystemRequest>>) -> Self {
         self.system_handler = Some(handler);
         self
     }
 
+    /// Handles events whose stage has no registered handler.
+    #[allow(dead_code)]
+    pub fn fallback(mut self, handler: Arc<dyn Handler<Event>>) -> Self {
+        self.fallback_handler = Some(handler);
+        self
+    }
+
     pub fn build(self) -> Result<Arc<dyn Handler<Event>>> {
         let Self {
+            command_parser,
             chat_handler,
             reward_handler,
+            donation_handler,
+            whisper_handler,
+            stream_status_handler,
             system_handler,
+            fallback_handler,
             interceptors,
         } = self;
 
-        let chat_handler = chat_handler.context("event router requires a chat handler")?;
-        let reward_handler = reward_handler.context("event router requires a reward handler")?;
-        let system_handler = system_handler.context("event router requires a system handler")?;
+        if fallback_handler.is_none() {
+            ensure!(
+                chat_handler.is_some(),
+                "event router requires a chat handler or a fallback handler"
+            );
+            ensure!(
+                reward_handler.is_some(),
+                "event router requires a reward handler or a fallback handler"
+            );
+            ensure!(
+                donation_handler.is_some(),
+                "event router requires a donation handler or a fallback handler"
+            );
+            ensure!(
+                whisper_handler.is_some(),
+                "event router requires a whisper handler or a fallback handler"
+            );
+            ensure!(
+                stream_status_handler.is_some(),
+                "event router requires a stream status handler or a fallback handler"
+            );
+            ensure!(
+                system_handler.is_some(),
+                "event router requires a system handler or a fallback handler"
+            );
+        }
 
         let router: Arc<dyn Handler<Event>> = Arc::new(EventRouter {
+            command_parser: command_parser.unwrap_or_default(),
             chat_handler,
             reward_handler,
+            donation_handler,
+            whisper_handler,
+            stream_status_handler,
             system_handler,
+            fallback_handler,
         });
 
         Ok(apply_interceptors(router, interceptors))
@@ -83,9 +165,159 @@ impl Handler<Event> for EventRouter {
         let route = Route::from(&event);
 
         match route {
-            Route::Chat => self.chat_handler.handle(project_chat(event)?).await,
-            Route::Reward => self.reward_handler.handle(project_reward(event)?).await,
-            Route::System => self.system_handler.handle(project_system(event)?).await,
+            Route::Chat => match &self.chat_handler {
+                Some(handler) => {
+                    handler
+                        .handle(project_chat(event, &self.command_parser.load())?)
+                        .await
+                }
+                None => self.fallback(route, event).await,
+            },
+            Route::Reward => match &self.reward_handler {
+                Some(handler) => handler.handle(project_reward(event)?).await,
+                None => self.fallback(route, event).await,
+            },
+            Route::Donation => match &self.donation_handler {
+                Some(handler) => handler.handle(project_donation(event)?).await,
+                None => self.fallback(route, event).await,
+            },
+            Route::Whisper => match &self.whisper_handler {
+                Some(handler) => handler.handle(project_whisper(event)?).await,
+                None => self.fallback(route, event).await,
+            },
+            Route::StreamStatus => match &self.stream_status_handler {
+                Some(handler) => handler.handle(project_stream_status(event)?).await,
+                None => self.fallback(route, event).await,
+            },
+            Route::System => match &self.system_handler {
+                Some(handler) => handler.handle(project_system(event)?).await,
+                None => self.fallback(route, event).await,
+            },
         }
     }
 }
+
+impl EventRouter {
+    async fn fallback(&self, route: Route, event: Event) -> Result<()> {
+        match &self.fallback_handler {
+            Some(handler) => handler.handle(event).await,
+            None => bail!("no handler for route {route:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::{
+        app::test_support::{Counter, system_event},
+        model::{Currency, Donation, EventContext, RewardRedemption, StreamStatus, User, Whisper},
+    };
+
+    fn reward_event() -> Event {
+        Event::RewardRedemption(RewardRedemption {
+            user: User::system(),
+            reward_id: "reward".to_string(),
+            reward_title: "Reward".to_string(),
+            cost: 100,
+            user_input: None,
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        })
+    }
+
+    #[test]
+    fn test_build_requires_handlers_without_fallback() {
+        let chat = Arc::new(AtomicUsize::new(0));
+        let result = EventRouter::builder().chat(Arc::new(Counter(chat))).build();
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_routes_go_to_fallback() {
+        let system = Arc::new(AtomicUsize::new(0));
+        let fallback = Arc::new(AtomicUsize::new(0));
+
+        let router = EventRouter::builder()
+            .system(Arc::new(Counter(system.clone())))
+            .fallback(Arc::new(Counter(fallback.clone())))
+            .build()
+            .unwrap();
+
+        router.handle(system_event("system")).await.unwrap();
+        router.handle(reward_event()).await.unwrap();
+
+        assert_eq!(system.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_donations_reach_donation_handler() {
+        let donation = Arc::new(AtomicUsize::new(0));
+        let fallback = Arc::new(AtomicUsize::new(0));
+
+        let router = EventRouter::builder()
+            .donation(Arc::new(Counter(donation.clone())))
+            .fallback(Arc::new(Counter(fallback.clone())))
+            .build()
+            .unwrap();
+
+        let event = Event::Donation(Donation::new(
+            User::system(),
+            5.0,
+            Currency::Usd,
+            Some("hi".to_string()),
+        ));
+        router.handle(event).await.unwrap();
+
+        assert_eq!(donation.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_whispers_reach_whisper_handler() {
+        let whisper = Arc::new(AtomicUsize::new(0));
+        let fallback = Arc::new(AtomicUsize::new(0));
+
+        let router = EventRouter::builder()
+            .whisper(Arc::new(Counter(whisper.clone())))
+            .fallback(Arc::new(Counter(fallback.clone())))
+            .build()
+            .unwrap();
+
+        router
+            .handle(Event::Whisper(Whisper::new(User::system(), "psst")))
+            .await
+            .unwrap();
+
+        assert_eq!(whisper.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_status_reaches_stream_status_handler() {
+        let stream_status = Arc::new(AtomicUsize::new(0));
+        let fallback = Arc::new(AtomicUsize::new(0));
+
+        let router = EventRouter::builder()
+            .stream_status(Arc::new(Counter(stream_status.clone())))
+            .fallback(Arc::new(Counter(fallback.clone())))
+            .build()
+            .unwrap();
+
+        router
+            .handle(Event::StreamStatus(StreamStatus::new(true)))
+            .await
+            .unwrap();
+
+        assert_eq!(stream_status.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.load(Ordering::SeqCst), 0);
+    }
+}
@@ -11,4 +11,6 @@ pub(crate) use chat_router::ChatRouter;
 pub(crate) use command_router::CommandRouter;
 pub(crate) use event_router::EventRouter;
 pub(crate) use reward_router::RewardRouter;
+pub(crate) use route::Route;
 pub(crate) use traits::Handler;
+pub(crate) use traits::HandlerStack;
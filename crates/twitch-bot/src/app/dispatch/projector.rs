@@ -1,16 +1,34 @@
 use crate::{
-    app::dispatch::request::{ChatRequest, RewardRequest, SystemRequest},
+    app::{
+        command::CommandParser,
+        dispatch::request::{
+            ChatRequest, DonationRequest, RewardRequest, StreamStatusRequest, SystemRequest,
+            WhisperRequest,
+        },
+    },
     model::Event,
 };
 
-pub(crate) fn project_chat(event: Event) -> anyhow::Result<ChatRequest> {
-    ChatRequest::try_from(event)
+pub(crate) fn project_chat(event: Event, parser: &CommandParser) -> anyhow::Result<ChatRequest> {
+    ChatRequest::from_event(event, parser)
 }
 
 pub(crate) fn project_reward(event: Event) -> anyhow::Result<RewardRequest> {
     RewardRequest::try_from(event)
 }
 
+pub(crate) fn project_donation(event: Event) -> anyhow::Result<DonationRequest> {
+    DonationRequest::try_from(event)
+}
+
+pub(crate) fn project_whisper(event: Event) -> anyhow::Result<WhisperRequest> {
+    WhisperRequest::try_from(event)
+}
+
+pub(crate) fn project_stream_status(event: Event) -> anyhow::Result<StreamStatusRequest> {
+    StreamStatusRequest::try_from(event)
+}
+
 pub(crate) fn project_system(event: Event) -> anyhow::Result<SystemRequest> {
     SystemRequest::try_from(event)
 }
@@ -12,20 +12,16 @@ pub(crate) enum ChatRequest {
 }
 
 impl ChatRequest {
-    pub fn from_message(message: ChatMessage) -> Self {
-        match CommandParser::parse_chat_text(&message.text) {
+    pub fn from_message(message: ChatMessage, parser: &CommandParser) -> Self {
+        match parser.parse_in_channel(&message.target.channel_login, &message.text) {
             Some(command) => Self::Command(CommandRequest { message, command }),
             None => Self::Plain(PlainMessageRequest { message }),
         }
     }
-}
-
-impl TryFrom<Event> for ChatRequest {
-    type Error = anyhow::Error;
 
-    fn try_from(event: Event) -> Result<Self> {
+    pub fn from_event(event: Event, parser: &CommandParser) -> Result<Self> {
         match event {
-            Event::ChatMessage(message) => Ok(Self::from_message(message)),
+            Event::ChatMessage(message) => Ok(Self::from_message(message, parser)),
             other => bail!("expected chat event, got {other:?}"),
         }
     }
@@ -0,0 +1,19 @@
+use anyhow::{Result, bail};
+
+use crate::model::{Donation, Event};
+
+#[derive(Debug, Clone)]
+pub(crate) struct DonationRequest {
+    pub donation: Donation,
+}
+
+impl TryFrom<Event> for DonationRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(event: Event) -> Result<Self> {
+        match event {
+            Event::Donation(donation) => Ok(Self { donation }),
+            other => bail!("expected donation event, got {other:?}"),
+        }
+    }
+}
@@ -1,7 +1,13 @@
 mod chat;
+mod donation;
 mod reward;
+mod stream_status;
 mod system;
+mod whisper;
 
 pub(crate) use chat::{ChatRequest, CommandRequest, PlainMessageRequest};
+pub(crate) use donation::DonationRequest;
 pub(crate) use reward::{RewardId, RewardRequest};
+pub(crate) use stream_status::StreamStatusRequest;
 pub(crate) use system::SystemRequest;
+pub(crate) use whisper::WhisperRequest;
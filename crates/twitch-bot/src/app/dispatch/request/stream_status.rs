@@ -0,0 +1,19 @@
+use anyhow::{Result, bail};
+
+use crate::model::{Event, StreamStatus};
+
+#[derive(Debug, Clone)]
+pub(crate) struct StreamStatusRequest {
+    pub status: StreamStatus,
+}
+
+impl TryFrom<Event> for StreamStatusRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(event: Event) -> Result<Self> {
+        match event {
+            Event::StreamStatus(status) => Ok(Self { status }),
+            other => bail!("expected stream status event, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use anyhow::{Result, bail};
+
+use crate::model::{Event, Whisper};
+
+#[derive(Debug, Clone)]
+pub(crate) struct WhisperRequest {
+    pub whisper: Whisper,
+}
+
+impl TryFrom<Event> for WhisperRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(event: Event) -> Result<Self> {
+        match event {
+            Event::Whisper(whisper) => Ok(Self { whisper }),
+            other => bail!("expected whisper event, got {other:?}"),
+        }
+    }
+}
@@ -5,6 +5,9 @@ use crate::model::Event;
 pub(crate) enum Route {
     Chat,
     Reward,
+    Donation,
+    Whisper,
+    StreamStatus,
     System,
 }
 
@@ -13,6 +16,9 @@ impl From<&Event> for Route {
         match event {
             Event::ChatMessage(_) => Self::Chat,
             Event::RewardRedemption(_) => Self::Reward,
+            Event::Donation(_) => Self::Donation,
+            Event::Whisper(_) => Self::Whisper,
+            Event::StreamStatus(_) => Self::StreamStatus,
             Event::System(_) => Self::System,
         }
     }
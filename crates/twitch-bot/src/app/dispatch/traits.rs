@@ -28,8 +28,156 @@ pub(crate) fn apply_interceptors<Request>(
 where
     Request: Send + 'static,
 {
-    interceptors
-        .into_iter()
-        .rev()
-        .fold(handler, |next, interceptor| interceptor.wrap(next))
+    HandlerStack { interceptors }.finish(handler)
+}
+
+/// Builds a handler wrapped in interceptors, listed outermost first:
+///
+/// ```ignore
+/// let skip = HandlerStack::new()
+///     .with(PlatformInterceptor::only(Platform::Twitch))
+///     .with(AuthInterceptor::new(Role::MODERATOR))
+///     .finish(skip_handler);
+/// ```
+///
+/// Here the platform check sees every request and the role check only those it lets through.
+pub(crate) struct HandlerStack<Request> {
+    interceptors: Vec<Arc<dyn Interceptor<Request>>>,
+}
+
+impl<Request> Default for HandlerStack<Request> {
+    fn default() -> Self {
+        Self {
+            interceptors: Vec::new(),
+        }
+    }
+}
+
+impl<Request> HandlerStack<Request>
+where
+    Request: Send + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, interceptor: impl Interceptor<Request>) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    pub fn finish(self, handler: Arc<dyn Handler<Request>>) -> Arc<dyn Handler<Request>> {
+        self.interceptors
+            .into_iter()
+            .rev()
+            .fold(handler, |next, interceptor| interceptor.wrap(next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use tokio::sync::Mutex;
+
+    use super::*;
+    use crate::model::{Event, EventContext, SystemEvent};
+
+    struct SpamScore(u32);
+
+    struct SpamScorer;
+
+    struct ScoringHandler {
+        next: Arc<dyn Handler<Event>>,
+    }
+
+    #[async_trait]
+    impl Handler<Event> for ScoringHandler {
+        async fn handle(&self, mut event: Event) -> anyhow::Result<()> {
+            event.context_mut().extensions.insert(SpamScore(42));
+            self.next.handle(event).await
+        }
+    }
+
+    impl Interceptor<Event> for SpamScorer {
+        fn wrap(&self, next: Arc<dyn Handler<Event>>) -> Arc<dyn Handler<Event>> {
+            Arc::new(ScoringHandler { next })
+        }
+    }
+
+    struct ScoreReader {
+        seen: Arc<Mutex<Option<u32>>>,
+    }
+
+    #[async_trait]
+    impl Handler<Event> for ScoreReader {
+        async fn handle(&self, event: Event) -> anyhow::Result<()> {
+            let score = event.context().extensions.get::<SpamScore>().map(|s| s.0);
+            *self.seen.lock().await = score;
+            Ok(())
+        }
+    }
+
+    struct Tag(&'static str);
+
+    struct TagHandler {
+        tag: &'static str,
+        next: Arc<dyn Handler<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl Handler<Vec<&'static str>> for TagHandler {
+        async fn handle(&self, mut request: Vec<&'static str>) -> anyhow::Result<()> {
+            request.push(self.tag);
+            self.next.handle(request).await
+        }
+    }
+
+    impl Interceptor<Vec<&'static str>> for Tag {
+        fn wrap(
+            &self,
+            next: Arc<dyn Handler<Vec<&'static str>>>,
+        ) -> Arc<dyn Handler<Vec<&'static str>>> {
+            Arc::new(TagHandler { tag: self.0, next })
+        }
+    }
+
+    struct TagRecorder(Arc<Mutex<Vec<&'static str>>>);
+
+    #[async_trait]
+    impl Handler<Vec<&'static str>> for TagRecorder {
+        async fn handle(&self, request: Vec<&'static str>) -> anyhow::Result<()> {
+            *self.0.lock().await = request;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_stack_runs_interceptors_in_order_added() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let handler = HandlerStack::new()
+            .with(Tag("logging"))
+            .with(Tag("auth"))
+            .finish(Arc::new(TagRecorder(seen.clone())));
+
+        handler.handle(Vec::new()).await.unwrap();
+
+        assert_eq!(*seen.lock().await, ["logging", "auth"]);
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_passes_extension_to_handler() {
+        let seen = Arc::new(Mutex::new(None));
+        let reader: Arc<dyn Handler<Event>> = Arc::new(ScoreReader { seen: seen.clone() });
+        let handler = apply_interceptors(reader, vec![Arc::new(SpamScorer)]);
+
+        let event = Event::System(SystemEvent {
+            message: "hello".to_string(),
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        });
+        handler.handle(event).await.unwrap();
+
+        assert_eq!(*seen.lock().await, Some(42));
+    }
 }
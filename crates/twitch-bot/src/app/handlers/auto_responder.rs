@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::trace;
+
+use crate::app::{
+    dispatch::{Handler, request::PlainMessageRequest},
+    ports::MessageSink,
+};
+
+pub(crate) enum Matcher {
+    /// Case-insensitive substring.
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn contains(needle: &str) -> Self {
+        Self::Contains(needle.to_lowercase())
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Contains(needle) => text.to_lowercase().contains(needle),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+pub(crate) struct AutoResponse {
+    matcher: Matcher,
+    response: String,
+}
+
+impl AutoResponse {
+    pub fn new(matcher: Matcher, response: impl Into<String>) -> Self {
+        Self {
+            matcher,
+            response: response.into(),
+        }
+    }
+}
+
+/// Answers plain chat messages with a canned response from the first matching rule.
+///
+/// Each rule stays quiet in a channel for `cooldown` after it fires there.
+pub(crate) struct AutoResponderHandler<S> {
+    sender: Arc<S>,
+    rules: Vec<AutoResponse>,
+    cooldown: Duration,
+    /// When each `(rule index, channel)` last fired.
+    last_sent: Mutex<HashMap<(usize, String), Instant>>,
+}
+
+impl<S> AutoResponderHandler<S> {
+    pub fn new(sender: Arc<S>, rules: Vec<AutoResponse>, cooldown: Duration) -> Self {
+        Self {
+            sender,
+            rules,
+            cooldown,
+            last_sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Marks the rule as fired unless it is still cooling down in this channel.
+    fn try_fire(&self, rule: usize, channel: &str) -> bool {
+        let mut last_sent = self
+            .last_sent
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let key = (rule, channel.to_string());
+
+        match last_sent.get(&key) {
+            Some(sent_at) if now.duration_since(*sent_at) < self.cooldown => false,
+            _ => {
+                last_sent.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Handler<PlainMessageRequest> for AutoResponderHandler<S>
+where
+    S: MessageSink,
+{
+    async fn handle(&self, request: PlainMessageRequest) -> anyhow::Result<()> {
+        let message = &request.message;
+        let Some((index, rule)) = self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matcher.matches(&message.text))
+        else {
+            trace!(
+                author = %message.author.display_name,
+                text = %message.text,
+                "ignoring plain chat message"
+            );
+            return Ok(());
+        };
+
+        if !self.try_fire(index, &message.target.channel_login) {
+            trace!(rule = index, "auto response on cooldown");
+            return Ok(());
+        }
+
+        self.sender
+            .send(&message.target, &rule.response)
+            .await
+            .context("failed to send auto response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::test_support::{RecordingSink, chat_message},
+        model::{ChatTarget, User},
+    };
+
+    fn request(channel: &str, text: &str) -> PlainMessageRequest {
+        let mut message = chat_message(User::system(), text);
+        message.target = ChatTarget::new("1", channel);
+        PlainMessageRequest { message }
+    }
+
+    fn responder(
+        sink: Arc<RecordingSink>,
+        cooldown: Duration,
+    ) -> AutoResponderHandler<RecordingSink> {
+        AutoResponderHandler::new(
+            sink,
+            vec![
+                AutoResponse::new(Matcher::contains("Discord"), "discord.gg/example"),
+                AutoResponse::new(
+                    Matcher::Regex(Regex::new(r"^what (game|song)\b").unwrap()),
+                    "check the stream title",
+                ),
+            ],
+            cooldown,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_responds_to_first_matching_rule() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = responder(sink.clone(), Duration::ZERO);
+
+        for text in ["is there a DISCORD?", "what song is this", "hello chat"] {
+            handler.handle(request("chan", text)).await.unwrap();
+        }
+
+        assert_eq!(
+            sink.messages(),
+            ["discord.gg/example", "check the stream title"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_is_per_rule_and_channel() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = responder(sink.clone(), Duration::from_secs(60));
+
+        handler.handle(request("chan", "discord?")).await.unwrap();
+        handler
+            .handle(request("chan", "discord pls"))
+            .await
+            .unwrap();
+        handler.handle(request("other", "discord?")).await.unwrap();
+        handler.handle(request("chan", "what game")).await.unwrap();
+
+        assert_eq!(sink.messages().len(), 3);
+    }
+}
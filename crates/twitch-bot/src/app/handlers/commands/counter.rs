@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::app::{
+    dispatch::{Handler, request::CommandRequest},
+    ports::{CounterStore, MessageSink},
+};
+
+/// Suffix that turns a counter's command into its increment, e.g. `!deaths+`.
+pub(crate) const INCREMENT_SUFFIX: &str = "+";
+
+/// Reports a named counter, or bumps it first when invoked with [`INCREMENT_SUFFIX`].
+pub(crate) struct CounterHandler<S, C> {
+    sender: Arc<S>,
+    store: Arc<C>,
+    counter: String,
+}
+
+impl<S, C> CounterHandler<S, C> {
+    pub fn new(sender: Arc<S>, store: Arc<C>, counter: impl Into<String>) -> Self {
+        Self {
+            sender,
+            store,
+            counter: counter.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S, C> Handler<CommandRequest> for CounterHandler<S, C>
+where
+    S: MessageSink,
+    C: CounterStore,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let value = if request.name().as_str().ends_with(INCREMENT_SUFFIX) {
+            self.store.increment(&self.counter).await?
+        } else {
+            self.store.get(&self.counter).await?
+        };
+
+        self.sender
+            .send(
+                &request.message.target,
+                &format!("{}: {value}", self.counter),
+            )
+            .await
+            .context("failed to send counter response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+    use crate::{
+        app::test_support::{RecordingSink, command},
+        model::User,
+    };
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<HashMap<String, u64>>);
+
+    #[async_trait]
+    impl CounterStore for MemoryStore {
+        async fn get(&self, name: &str) -> anyhow::Result<u64> {
+            Ok(self.0.lock().unwrap().get(name).copied().unwrap_or(0))
+        }
+
+        async fn increment(&self, name: &str) -> anyhow::Result<u64> {
+            let mut counters = self.0.lock().unwrap();
+            let value = counters.entry(name.to_string()).or_default();
+            *value += 1;
+            Ok(*value)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reports_and_increments() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = CounterHandler::new(sink.clone(), Arc::new(MemoryStore::default()), "deaths");
+
+        for name in ["deaths", "deaths+", "deaths+", "deaths"] {
+            handler
+                .handle(command(User::system(), &format!("!{name}")))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            sink.messages(),
+            ["deaths: 0", "deaths: 1", "deaths: 2", "deaths: 2"]
+        );
+    }
+}
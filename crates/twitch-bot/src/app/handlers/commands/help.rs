@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::{
+    app::{
+        dispatch::{Handler, request::CommandRequest},
+        ports::{MessageSink, WhisperSink},
+    },
+    model::Platform,
+};
+
+/// Lists the registered commands, either in chat or, with `whisper` set, in
+/// a whisper to whoever asked so the list doesn't clutter chat.
+pub(crate) struct HelpHandler<S> {
+    sender: Arc<S>,
+    reply: String,
+    whisper: bool,
+}
+
+impl<S> HelpHandler<S> {
+    pub fn new(sender: Arc<S>, mut commands: Vec<String>, whisper: bool) -> Self {
+        commands.sort();
+        commands.dedup();
+        Self {
+            sender,
+            reply: format!("commands: {}", commands.join(", ")),
+            whisper,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Handler<CommandRequest> for HelpHandler<S>
+where
+    S: MessageSink + WhisperSink,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let author = &request.message.author;
+        // only Twitch users can be whispered, so everyone else gets the chat reply
+        if self.whisper && author.platform == Platform::Twitch {
+            return self
+                .sender
+                .whisper(&author.id, &self.reply)
+                .await
+                .context("failed to whisper help response");
+        }
+
+        self.sender
+            .send(&request.message.target, &self.reply)
+            .await
+            .context("failed to send help response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::test_support::{RecordingSink, command},
+        model::User,
+    };
+
+    fn commands() -> Vec<String> {
+        vec!["ping".into(), "help".into(), "music".into()]
+    }
+
+    fn twitch_viewer() -> User {
+        let mut author = User::system();
+        author.id = "42".to_string();
+        author.platform = Platform::Twitch;
+        author
+    }
+
+    #[tokio::test]
+    async fn test_replies_in_chat_by_default() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = HelpHandler::new(sink.clone(), commands(), false);
+
+        handler
+            .handle(command(twitch_viewer(), "!help"))
+            .await
+            .unwrap();
+
+        assert_eq!(sink.messages(), ["commands: help, music, ping"]);
+    }
+
+    #[tokio::test]
+    async fn test_whispers_twitch_users_when_enabled() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = HelpHandler::new(sink.clone(), commands(), true);
+
+        handler
+            .handle(command(twitch_viewer(), "!help"))
+            .await
+            .unwrap();
+        handler
+            .handle(command(User::system(), "!help"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            sink.messages(),
+            [
+                "/w 42 commands: help, music, ping",
+                "commands: help, music, ping"
+            ]
+        );
+    }
+}
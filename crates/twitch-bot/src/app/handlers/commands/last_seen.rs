@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::app::{
+    dispatch::{Handler, request::CommandRequest},
+    history::MessageHistory,
+    ports::MessageSink,
+};
+
+/// `lastseen <name>` quotes the latest message remembered for `name`, which
+/// may be written with or without a leading `@`.
+pub(crate) struct LastSeenHandler<S> {
+    sender: Arc<S>,
+    history: Arc<MessageHistory>,
+}
+
+impl<S> LastSeenHandler<S> {
+    pub fn new(sender: Arc<S>, history: Arc<MessageHistory>) -> Self {
+        Self { sender, history }
+    }
+}
+
+#[async_trait]
+impl<S> Handler<CommandRequest> for LastSeenHandler<S>
+where
+    S: MessageSink,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let reply = match request.command.args.first() {
+            Some(arg) => {
+                let name = arg.strip_prefix('@').unwrap_or(arg);
+                let last = self
+                    .history
+                    .find_user(name)
+                    .and_then(|id| self.history.recent(&id, 1).pop());
+                match last {
+                    Some(text) => format!("{name} last said: {text}"),
+                    None => format!("haven't seen {name}"),
+                }
+            }
+            None => "usage: lastseen <name>".to_string(),
+        };
+
+        self.sender
+            .send(&request.message.target, &reply)
+            .await
+            .context("failed to send lastseen response")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::{
+        app::test_support::{RecordingSink, command},
+        model::User,
+    };
+
+    #[tokio::test]
+    async fn test_quotes_latest_message() {
+        let sink = Arc::new(RecordingSink::default());
+        let history = Arc::new(MessageHistory::new(
+            NonZeroUsize::new(5).unwrap(),
+            NonZeroUsize::new(5).unwrap(),
+        ));
+        history.record("7", "Streamer", "first");
+        history.record("7", "Streamer", "brb");
+        let handler = LastSeenHandler::new(sink.clone(), history);
+
+        for text in ["!lastseen @streamer", "!lastseen nobody", "!lastseen"] {
+            handler.handle(command(User::system(), text)).await.unwrap();
+        }
+
+        assert_eq!(
+            sink.messages(),
+            [
+                "streamer last said: brb",
+                "haven't seen nobody",
+                "usage: lastseen <name>",
+            ]
+        );
+    }
+}
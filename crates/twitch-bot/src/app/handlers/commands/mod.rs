@@ -1,7 +1,17 @@
+mod counter;
+mod help;
+mod last_seen;
 mod music;
+mod ping;
+mod quote;
 mod skip;
 mod unknown;
 
+pub(crate) use counter::{CounterHandler, INCREMENT_SUFFIX};
+pub(crate) use help::HelpHandler;
+pub(crate) use last_seen::LastSeenHandler;
 pub(crate) use music::MusicHandler;
+pub(crate) use ping::PingHandler;
+pub(crate) use quote::QuoteHandler;
 pub(crate) use skip::SkipHandler;
 pub(crate) use unknown::UnknownCommandHandler;
@@ -0,0 +1,75 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use crate::app::{
+    dispatch::{Handler, request::CommandRequest},
+    ports::MessageSink,
+};
+
+/// Replies "pong" with how long the bot has been running.
+pub(crate) struct PingHandler<S> {
+    sender: Arc<S>,
+    started_at: Instant,
+}
+
+impl<S> PingHandler<S> {
+    pub fn new(sender: Arc<S>) -> Self {
+        Self {
+            sender,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> Handler<CommandRequest> for PingHandler<S>
+where
+    S: MessageSink,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let response = format!("pong, uptime {}", format_uptime(self.started_at.elapsed()));
+
+        self.sender
+            .send(&request.message.target, &response)
+            .await
+            .context("failed to send ping response")
+    }
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds / 3_600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(42)), "42s");
+        assert_eq!(format_uptime(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_uptime(Duration::from_secs(3_725)), "1h 2m 5s");
+        assert_eq!(format_uptime(Duration::from_secs(90_061)), "1d 1h 1m");
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use rand::seq::IndexedRandom;
+use tokio::sync::Mutex;
+
+use crate::app::{
+    dispatch::{Handler, HandlerStack, request::CommandRequest, traits::Interceptor},
+    ports::{KvStore, MessageSink},
+};
+
+const KEY_PREFIX: &str = "quote:";
+const ADD_SUBCOMMAND: &str = "add";
+
+/// `quote` picks a random quote, `quote <n>` shows quote number `n` and
+/// `quote add <text>` stores a new one.
+///
+/// Quotes live in the [`KvStore`] as `quote:<n>`, numbered from 1.
+pub(crate) struct QuoteHandler<S> {
+    sender: Arc<S>,
+    store: Arc<dyn KvStore>,
+    add: Arc<dyn Handler<CommandRequest>>,
+}
+
+impl<S> QuoteHandler<S>
+where
+    S: MessageSink,
+{
+    /// `add_guard` wraps only `quote add`, e.g. an `AuthInterceptor` so that
+    /// viewers can read quotes but not add them.
+    pub fn new(
+        sender: Arc<S>,
+        store: Arc<dyn KvStore>,
+        add_guard: impl Interceptor<CommandRequest>,
+    ) -> Self {
+        let add = HandlerStack::new()
+            .with(add_guard)
+            .finish(Arc::new(AddQuoteHandler {
+                sender: sender.clone(),
+                store: store.clone(),
+                adding: Mutex::new(()),
+            }));
+
+        Self { sender, store, add }
+    }
+
+    async fn reply(&self, request: &CommandRequest, text: &str) -> anyhow::Result<()> {
+        self.sender
+            .send(&request.message.target, text)
+            .await
+            .context("failed to send quote response")
+    }
+}
+
+#[async_trait]
+impl<S> Handler<CommandRequest> for QuoteHandler<S>
+where
+    S: MessageSink,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let reply = match request.command.args.first().map(String::as_str) {
+            Some(ADD_SUBCOMMAND) => return self.add.handle(request).await,
+            Some(arg) => match arg.parse::<usize>() {
+                Ok(number) => match self.store.get(&key(number)).await? {
+                    Some(quote) => format!("#{number}: {quote}"),
+                    None => format!("no quote #{number}"),
+                },
+                Err(_) => "usage: quote [number] or quote add <text>".to_string(),
+            },
+            None => {
+                let quotes = self.store.list_prefix(KEY_PREFIX).await?;
+                match quotes.choose(&mut rand::rng()) {
+                    Some((key, quote)) => format!("#{}: {quote}", &key[KEY_PREFIX.len()..]),
+                    None => "no quotes yet".to_string(),
+                }
+            }
+        };
+
+        self.reply(&request, &reply).await
+    }
+}
+
+struct AddQuoteHandler<S> {
+    sender: Arc<S>,
+    store: Arc<dyn KvStore>,
+    /// Numbering reads then writes, so adds are serialized.
+    adding: Mutex<()>,
+}
+
+#[async_trait]
+impl<S> Handler<CommandRequest> for AddQuoteHandler<S>
+where
+    S: MessageSink,
+{
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let text = request.command.args[1..].join(" ");
+        let reply = if text.is_empty() {
+            "usage: quote add <text>".to_string()
+        } else {
+            let _guard = self.adding.lock().await;
+            let number = self.store.list_prefix(KEY_PREFIX).await?.len() + 1;
+            self.store.set(&key(number), text).await?;
+            format!("added quote #{number}")
+        };
+
+        self.sender
+            .send(&request.message.target, &reply)
+            .await
+            .context("failed to send quote response")
+    }
+}
+
+fn key(number: usize) -> String {
+    format!("{KEY_PREFIX}{number}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        adapters::storage::MemoryKvStore,
+        app::{
+            interceptors::AuthInterceptor,
+            test_support::{self, RecordingSink},
+        },
+        model::{Role, User},
+    };
+
+    fn command(role: Role, text: &str) -> CommandRequest {
+        let mut author = User::system();
+        author.role = role;
+        test_support::command(author, text)
+    }
+
+    #[tokio::test]
+    async fn test_add_get_and_random() {
+        let sink = Arc::new(RecordingSink::default());
+        let handler = QuoteHandler::new(
+            sink.clone(),
+            Arc::new(MemoryKvStore::new()),
+            AuthInterceptor::new(Role::MODERATOR),
+        );
+
+        for (role, text) in [
+            (Role::PLEB, "!quote"),
+            (Role::PLEB, "!quote add sneaky"),
+            (Role::MODERATOR, "!quote add it's fine, I have a plan"),
+            (Role::BROADCASTER, "!quote add \"never again\" - me"),
+            (Role::PLEB, "!quote 2"),
+            (Role::PLEB, "!quote 3"),
+            (Role::PLEB, "!quote nope"),
+        ] {
+            handler.handle(command(role, text)).await.unwrap();
+        }
+
+        assert_eq!(
+            sink.messages(),
+            [
+                "no quotes yet",
+                "added quote #1",
+                "added quote #2",
+                "#2: never again - me",
+                "no quote #3",
+                "usage: quote [number] or quote add <text>",
+            ]
+        );
+
+        handler.handle(command(Role::PLEB, "!quote")).await.unwrap();
+        let random = sink.messages().pop().unwrap();
+        assert!(random.starts_with("#1: ") || random.starts_with("#2: "));
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::app::dispatch::{Handler, request::DonationRequest};
+
+pub(crate) struct DonationHandler;
+
+impl DonationHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Handler<DonationRequest> for DonationHandler {
+    async fn handle(&self, request: DonationRequest) -> anyhow::Result<()> {
+        let donation = &request.donation;
+        info!(
+            donor = %donation.donor.display_name,
+            amount = donation.amount,
+            currency = ?donation.currency,
+            "received donation"
+        );
+
+        Ok(())
+    }
+}
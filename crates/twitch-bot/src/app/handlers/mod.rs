@@ -1,7 +1,17 @@
+mod auto_responder;
 pub(crate) mod commands;
+mod donation;
 mod plain_message;
+mod publish;
 pub(crate) mod rewards;
+mod stream_status;
 mod system;
+mod whisper;
 
+pub(crate) use auto_responder::{AutoResponderHandler, AutoResponse, Matcher};
+pub(crate) use donation::DonationHandler;
 pub(crate) use plain_message::PlainMessageHandler;
+pub(crate) use publish::PublishHandler;
+pub(crate) use stream_status::StreamStatusHandler;
 pub(crate) use system::SystemHandler;
+pub(crate) use whisper::WhisperHandler;
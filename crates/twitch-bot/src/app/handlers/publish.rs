@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::{
+    app::{dispatch::Handler, ports::EventPublisher},
+    model::Event,
+};
+
+/// Hands every event to an [`EventPublisher`]. It runs on its own consumer,
+/// next to the dispatch pipeline rather than inside it.
+pub(crate) struct PublishHandler {
+    publisher: Arc<dyn EventPublisher>,
+}
+
+impl PublishHandler {
+    pub fn new(publisher: Arc<dyn EventPublisher>) -> Self {
+        Self { publisher }
+    }
+}
+
+#[async_trait]
+impl Handler<Event> for PublishHandler {
+    #[instrument(name = "publish", skip_all)]
+    async fn handle(&self, event: Event) -> anyhow::Result<()> {
+        self.publisher.publish(&event);
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::app::dispatch::{Handler, request::StreamStatusRequest};
+
+/// Tracks whether the stream is live, for components that only act while it is,
+/// such as the [`Scheduler`](crate::app::scheduler::Scheduler).
+///
+/// The stream is assumed offline until the first status event arrives.
+pub(crate) struct StreamStatusHandler {
+    live: watch::Sender<bool>,
+}
+
+impl StreamStatusHandler {
+    pub fn new() -> Self {
+        Self {
+            live: watch::Sender::new(false),
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.live.subscribe()
+    }
+}
+
+#[async_trait]
+impl Handler<StreamStatusRequest> for StreamStatusHandler {
+    async fn handle(&self, request: StreamStatusRequest) -> anyhow::Result<()> {
+        let live = request.status.live;
+        info!(live, "stream status changed");
+
+        self.live.send_replace(live);
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use tracing::{info, trace};
+
+use crate::app::dispatch::{Handler, request::WhisperRequest};
+
+/// Logs whispers sent to the bot; they are private, so the text only shows
+/// at `trace`.
+pub(crate) struct WhisperHandler;
+
+impl WhisperHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Handler<WhisperRequest> for WhisperHandler {
+    async fn handle(&self, request: WhisperRequest) -> anyhow::Result<()> {
+        let whisper = &request.whisper;
+        info!(from = %whisper.author.display_name, "received whisper");
+        trace!(text = %whisper.text, "whisper text");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    num::NonZeroUsize,
+    sync::Mutex,
+};
+
+/// Recent chat messages per user, shared as an `Arc` between the interceptor
+/// that records them and the handlers that read them.
+///
+/// Memory is bounded by `per_user * max_users` messages. A user's oldest
+/// message is dropped once they exceed `per_user`; when a new user would
+/// exceed `max_users`, the user who has been quiet the longest is forgotten.
+pub(crate) struct MessageHistory {
+    per_user: usize,
+    max_users: usize,
+    state: Mutex<HistoryState>,
+}
+
+#[derive(Default)]
+struct HistoryState {
+    users: HashMap<String, UserHistory>,
+    /// Bumped on every message, so the smallest `last_active` is the quietest user.
+    clock: u64,
+}
+
+struct UserHistory {
+    /// Display name from the user's latest message.
+    name: String,
+    messages: VecDeque<String>,
+    last_active: u64,
+}
+
+impl MessageHistory {
+    pub fn new(per_user: NonZeroUsize, max_users: NonZeroUsize) -> Self {
+        Self {
+            per_user: per_user.get(),
+            max_users: max_users.get(),
+            state: Mutex::new(HistoryState::default()),
+        }
+    }
+
+    pub fn record(&self, user_id: &str, name: &str, text: &str) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.clock += 1;
+        let now = state.clock;
+
+        if !state.users.contains_key(user_id) && state.users.len() >= self.max_users {
+            evict_quietest(&mut state.users);
+        }
+
+        let user = state
+            .users
+            .entry(user_id.to_string())
+            .or_insert_with(|| UserHistory {
+                name: String::new(),
+                messages: VecDeque::with_capacity(self.per_user),
+                last_active: now,
+            });
+        if user.name != name {
+            user.name = name.to_string();
+        }
+        if user.messages.len() == self.per_user {
+            user.messages.pop_front();
+        }
+        user.messages.push_back(text.to_string());
+        user.last_active = now;
+    }
+
+    /// Id of a remembered user whose display name matches, ignoring case.
+    pub fn find_user(&self, name: &str) -> Option<String> {
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        state
+            .users
+            .iter()
+            .find(|(_, user)| user.name.eq_ignore_ascii_case(name))
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Up to `n` of the user's latest messages, oldest first.
+    pub fn recent(&self, user_id: &str, n: usize) -> Vec<String> {
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(user) = state.users.get(user_id) else {
+            return Vec::new();
+        };
+
+        let skip = user.messages.len().saturating_sub(n);
+        user.messages.iter().skip(skip).cloned().collect()
+    }
+}
+
+fn evict_quietest(users: &mut HashMap<String, UserHistory>) {
+    let quietest = users
+        .iter()
+        .min_by_key(|(_, user)| user.last_active)
+        .map(|(id, _)| id.clone());
+
+    if let Some(id) = quietest {
+        users.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(per_user: usize, max_users: usize) -> MessageHistory {
+        MessageHistory::new(
+            NonZeroUsize::new(per_user).unwrap(),
+            NonZeroUsize::new(max_users).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_recent_returns_latest_messages_oldest_first() {
+        let history = history(3, 10);
+        for text in ["one", "two", "three", "four"] {
+            history.record("1", "One", text);
+        }
+
+        assert_eq!(history.recent("1", 10), ["two", "three", "four"]);
+        assert_eq!(history.recent("1", 2), ["three", "four"]);
+        assert!(history.recent("2", 2).is_empty());
+    }
+
+    #[test]
+    fn test_quietest_user_is_evicted_when_full() {
+        let history = history(2, 2);
+        history.record("1", "One", "first");
+        history.record("2", "Two", "second");
+        history.record("1", "One", "again");
+        history.record("3", "Three", "third");
+
+        assert_eq!(history.recent("1", 2), ["first", "again"]);
+        assert!(history.recent("2", 2).is_empty());
+        assert_eq!(history.recent("3", 2), ["third"]);
+    }
+
+    #[test]
+    fn test_find_user_by_latest_display_name() {
+        let history = history(2, 10);
+        history.record("1", "Old", "hi");
+        history.record("1", "NewName", "renamed");
+
+        assert_eq!(history.find_user("newname").as_deref(), Some("1"));
+        assert_eq!(history.find_user("old"), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use tracing::{info, instrument};
+
+use crate::{
+    app::{
+        dispatch::{Handler, request::CommandRequest, traits::Interceptor},
+        ports::MessageSink,
+    },
+    model::Role,
+};
+
+/// Lets a command through only when its author ranks at least `required`,
+/// see [`Role::at_least`]. Anyone else is ignored without an error, unless a
+/// denial reply is configured.
+pub(crate) struct AuthInterceptor {
+    required: Role,
+    denial: Option<DenialReply>,
+}
+
+/// Message sent back to the channel when a command is denied.
+#[derive(Clone)]
+struct DenialReply {
+    sender: Arc<dyn MessageSink>,
+    template: String,
+}
+
+impl DenialReply {
+    fn render(&self, user: &str) -> String {
+        self.template.replace("{user}", user)
+    }
+}
+
+impl AuthInterceptor {
+    pub fn new(required: Role) -> Self {
+        Self {
+            required,
+            denial: None,
+        }
+    }
+
+    /// Like [`AuthInterceptor::new`], but answers denied commands in chat.
+    /// `{user}` in `template` is replaced with the author's display name,
+    /// e.g. `"@{user} you need to be a mod for that"`.
+    pub fn with_denial_reply(
+        required: Role,
+        sender: Arc<dyn MessageSink>,
+        template: impl Into<String>,
+    ) -> Self {
+        Self {
+            required,
+            denial: Some(DenialReply {
+                sender,
+                template: template.into(),
+            }),
+        }
+    }
+}
+
+impl Interceptor<CommandRequest> for AuthInterceptor {
+    fn wrap(&self, next: Arc<dyn Handler<CommandRequest>>) -> Arc<dyn Handler<CommandRequest>> {
+        Arc::new(AuthHandler {
+            required: self.required,
+            denial: self.denial.clone(),
+            next,
+        })
+    }
+}
+
+struct AuthHandler {
+    required: Role,
+    denial: Option<DenialReply>,
+    next: Arc<dyn Handler<CommandRequest>>,
+}
+
+#[async_trait]
+impl Handler<CommandRequest> for AuthHandler {
+    #[instrument(name = "auth", skip_all)]
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let author = &request.message.author;
+        if !author.role.at_least(self.required) {
+            info!(
+                user = %author.display_name,
+                command = %request.name(),
+                "command denied: insufficient role"
+            );
+
+            if let Some(denial) = &self.denial {
+                let reply = denial.render(&author.display_name);
+                denial
+                    .sender
+                    .send(&request.message.target, &reply)
+                    .await
+                    .context("failed to send permission denied reply")?;
+            }
+            return Ok(());
+        }
+
+        self.next.handle(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        app::test_support::{Counter, RecordingSink, command},
+        model::User,
+    };
+
+    fn command_from(role: Role) -> CommandRequest {
+        let mut author = User::system();
+        author.display_name = "viewer".to_string();
+        author.role = role;
+        command(author, "!ban someone")
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_is_denied_moderator_command() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let handler =
+            AuthInterceptor::new(Role::MODERATOR).wrap(Arc::new(Counter(handled.clone())));
+
+        handler
+            .handle(command_from(Role::SUBSCRIBER))
+            .await
+            .unwrap();
+
+        assert_eq!(handled.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_broadcaster_is_allowed_moderator_command() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let handler =
+            AuthInterceptor::new(Role::MODERATOR).wrap(Arc::new(Counter(handled.clone())));
+
+        handler
+            .handle(command_from(Role::BROADCASTER))
+            .await
+            .unwrap();
+
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_denial_reply_is_opt_in() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let sink = Arc::new(RecordingSink::default());
+        let handler = AuthInterceptor::with_denial_reply(
+            Role::MODERATOR,
+            sink.clone(),
+            "@{user} you need to be a mod for that",
+        )
+        .wrap(Arc::new(Counter(handled.clone())));
+
+        handler.handle(command_from(Role::VIP)).await.unwrap();
+        handler.handle(command_from(Role::MODERATOR)).await.unwrap();
+
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+        assert_eq!(sink.messages(), ["@viewer you need to be a mod for that"]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::{
+    app::{
+        dispatch::{Handler, traits::Interceptor},
+        history::MessageHistory,
+    },
+    model::Event,
+};
+
+/// Records every chat message into a [`MessageHistory`] before passing it on.
+///
+/// Place it after [`NormalizeInterceptor`](super::NormalizeInterceptor) to store
+/// the cleaned-up text.
+pub(crate) struct HistoryInterceptor {
+    history: Arc<MessageHistory>,
+}
+
+impl HistoryInterceptor {
+    pub fn new(history: Arc<MessageHistory>) -> Self {
+        Self { history }
+    }
+}
+
+impl Interceptor<Event> for HistoryInterceptor {
+    fn wrap(&self, next: Arc<dyn Handler<Event>>) -> Arc<dyn Handler<Event>> {
+        Arc::new(HistoryHandler {
+            history: self.history.clone(),
+            next,
+        })
+    }
+}
+
+struct HistoryHandler {
+    history: Arc<MessageHistory>,
+    next: Arc<dyn Handler<Event>>,
+}
+
+#[async_trait]
+impl Handler<Event> for HistoryHandler {
+    #[instrument(name = "history", skip_all)]
+    async fn handle(&self, event: Event) -> anyhow::Result<()> {
+        if let Event::ChatMessage(message) = &event {
+            self.history.record(
+                &message.author.id,
+                &message.author.display_name,
+                &message.text,
+            );
+        }
+        self.next.handle(event).await
+    }
+}
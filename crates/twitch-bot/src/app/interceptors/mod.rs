@@ -0,0 +1,11 @@
+mod auth;
+mod history;
+mod moderation;
+mod normalize;
+mod platform;
+
+pub(crate) use auth::AuthInterceptor;
+pub(crate) use history::HistoryInterceptor;
+pub(crate) use moderation::{ModerationAction, ModerationInterceptor, ModerationPolicy};
+pub(crate) use normalize::NormalizeInterceptor;
+pub(crate) use platform::PlatformInterceptor;
@@ -0,0 +1,316 @@
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    app::{
+        dispatch::{Handler, request::PlainMessageRequest, traits::Interceptor},
+        ports::ChatModerator,
+    },
+    model::{ChatMessage, Role},
+};
+
+/// Bare domains are only caught for common TLDs, to spare "e.g." and "lol...ok".
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:https?://|www\.)\S+|\b[a-z0-9-]+\.(?:com|net|org|tv|gg|io|ly|me|co|ru)\b")
+        .expect("link pattern is valid")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ModerationAction {
+    Delete,
+    Timeout(Duration),
+}
+
+/// What counts as a violation. Moderators and the broadcaster are never checked.
+#[derive(Debug, Clone)]
+pub(crate) struct ModerationPolicy {
+    /// Lowest role allowed to post links.
+    pub link_min_role: Role,
+    /// Largest share of uppercase letters, in percent, before a message counts as shouting.
+    pub max_caps_percent: Option<u8>,
+    /// Messages with fewer letters than this are never caps-checked.
+    pub caps_min_letters: usize,
+    /// Longest allowed run of one repeated character.
+    pub max_repeated_chars: Option<usize>,
+    pub action: ModerationAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    Link,
+    Caps,
+    RepeatedChars,
+}
+
+impl Violation {
+    fn reason(self) -> &'static str {
+        match self {
+            Self::Link => "links are not allowed",
+            Self::Caps => "excessive caps",
+            Self::RepeatedChars => "repeated characters",
+        }
+    }
+}
+
+impl ModerationPolicy {
+    fn check(&self, message: &ChatMessage) -> Option<Violation> {
+        let role = message.author.role;
+        if role.at_least(Role::MODERATOR) {
+            return None;
+        }
+
+        if !role.at_least(self.link_min_role) && LINK.is_match(&message.text) {
+            return Some(Violation::Link);
+        }
+        if let Some(max) = self.max_caps_percent
+            && caps_percent(&message.text, self.caps_min_letters).is_some_and(|caps| caps > max)
+        {
+            return Some(Violation::Caps);
+        }
+        if let Some(max) = self.max_repeated_chars
+            && longest_run(&message.text) > max
+        {
+            return Some(Violation::RepeatedChars);
+        }
+
+        None
+    }
+}
+
+/// Share of uppercase among the letters, or `None` when there are too few letters to judge.
+fn caps_percent(text: &str, min_letters: usize) -> Option<u8> {
+    let (letters, upper) = text
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .fold((0usize, 0usize), |(letters, upper), c| {
+            (letters + 1, upper + usize::from(c.is_uppercase()))
+        });
+
+    (letters >= min_letters.max(1)).then(|| (upper * 100 / letters) as u8)
+}
+
+fn longest_run(text: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+
+    for c in text.chars() {
+        run = if previous == Some(c) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(c);
+    }
+
+    longest
+}
+
+/// Deletes or times out plain messages that break the [`ModerationPolicy`];
+/// everything else goes on to the wrapped handler.
+pub(crate) struct ModerationInterceptor<M> {
+    moderator: Arc<M>,
+    policy: ModerationPolicy,
+}
+
+impl<M> ModerationInterceptor<M> {
+    pub fn new(moderator: Arc<M>, policy: ModerationPolicy) -> Self {
+        Self { moderator, policy }
+    }
+}
+
+impl<M> Interceptor<PlainMessageRequest> for ModerationInterceptor<M>
+where
+    M: ChatModerator,
+{
+    fn wrap(
+        &self,
+        next: Arc<dyn Handler<PlainMessageRequest>>,
+    ) -> Arc<dyn Handler<PlainMessageRequest>> {
+        Arc::new(ModerationHandler {
+            moderator: self.moderator.clone(),
+            policy: self.policy.clone(),
+            next,
+        })
+    }
+}
+
+struct ModerationHandler<M> {
+    moderator: Arc<M>,
+    policy: ModerationPolicy,
+    next: Arc<dyn Handler<PlainMessageRequest>>,
+}
+
+#[async_trait]
+impl<M> Handler<PlainMessageRequest> for ModerationHandler<M>
+where
+    M: ChatModerator,
+{
+    #[instrument(name = "moderation", skip_all)]
+    async fn handle(&self, request: PlainMessageRequest) -> anyhow::Result<()> {
+        let message = &request.message;
+        let Some(violation) = self.policy.check(message) else {
+            return self.next.handle(request).await;
+        };
+
+        info!(
+            author = %message.author.display_name,
+            reason = violation.reason(),
+            "moderating chat message"
+        );
+
+        match self.policy.action {
+            ModerationAction::Delete => match &message.message_id {
+                Some(message_id) => {
+                    self.moderator
+                        .delete_message(&message.target, message_id)
+                        .await
+                }
+                None => {
+                    warn!("cannot delete a message without an id");
+                    Ok(())
+                }
+            },
+            ModerationAction::Timeout(duration) => {
+                self.moderator
+                    .timeout(
+                        &message.target,
+                        &message.author.id,
+                        duration,
+                        violation.reason(),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::{
+        app::test_support::{Counter, chat_message},
+        model::{ChatTarget, Platform, User},
+    };
+
+    #[derive(Default)]
+    struct RecordingModerator(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl ChatModerator for RecordingModerator {
+        async fn delete_message(
+            &self,
+            _target: &ChatTarget,
+            message_id: &str,
+        ) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(format!("delete {message_id}"));
+            Ok(())
+        }
+
+        async fn timeout(
+            &self,
+            _target: &ChatTarget,
+            user_id: &str,
+            duration: Duration,
+            reason: &str,
+        ) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(format!(
+                "timeout {user_id} {}s: {reason}",
+                duration.as_secs()
+            ));
+            Ok(())
+        }
+    }
+
+    fn policy(action: ModerationAction) -> ModerationPolicy {
+        ModerationPolicy {
+            link_min_role: Role::SUBSCRIBER,
+            max_caps_percent: Some(70),
+            caps_min_letters: 10,
+            max_repeated_chars: Some(8),
+            action,
+        }
+    }
+
+    fn message(role: Role, text: &str) -> ChatMessage {
+        let author = User {
+            id: "42".to_string(),
+            display_name: "viewer".to_string(),
+            platform: Platform::Twitch,
+            role,
+        };
+        let mut message = chat_message(author, text);
+        message.message_id = Some("m1".to_string());
+        message
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let policy = policy(ModerationAction::Delete);
+        let check = |role, text| policy.check(&message(role, text));
+
+        assert_eq!(
+            check(Role::PLEB, "go to https://spam.example"),
+            Some(Violation::Link)
+        );
+        assert_eq!(
+            check(Role::PLEB, "cheap followers at spam.ru"),
+            Some(Violation::Link)
+        );
+        assert_eq!(
+            check(Role::SUBSCRIBER, "clip: https://clips.twitch.tv/x"),
+            None
+        );
+        assert_eq!(
+            check(Role::PLEB, "THIS IS SO LOUD RIGHT NOW"),
+            Some(Violation::Caps)
+        );
+        assert_eq!(check(Role::PLEB, "LOL ok"), None);
+        assert_eq!(
+            check(Role::PLEB, "nooooooooooooo"),
+            Some(Violation::RepeatedChars)
+        );
+        assert_eq!(check(Role::PLEB, "e.g. this is fine..."), None);
+        assert_eq!(
+            check(Role::MODERATOR, "HTTPS://ANYTHING.COM!!!!!!!!!!!"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_acts_on_violations_and_forwards_the_rest() {
+        let moderator = Arc::new(RecordingModerator::default());
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let next: Arc<dyn Handler<PlainMessageRequest>> = Arc::new(Counter(forwarded.clone()));
+
+        let delete =
+            ModerationInterceptor::new(moderator.clone(), policy(ModerationAction::Delete))
+                .wrap(next.clone());
+        let timeout = ModerationInterceptor::new(
+            moderator.clone(),
+            policy(ModerationAction::Timeout(Duration::from_secs(60))),
+        )
+        .wrap(next);
+
+        let request = |text: &str| PlainMessageRequest {
+            message: message(Role::PLEB, text),
+        };
+        delete.handle(request("www.spam.example")).await.unwrap();
+        timeout.handle(request("www.spam.example")).await.unwrap();
+        delete.handle(request("hello chat")).await.unwrap();
+
+        assert_eq!(
+            *moderator.0.lock().unwrap(),
+            ["delete m1", "timeout 42 60s: links are not allowed"]
+        );
+        assert_eq!(forwarded.load(Ordering::SeqCst), 1);
+    }
+}
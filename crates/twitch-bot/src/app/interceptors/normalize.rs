@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::instrument;
+
+use crate::{
+    app::dispatch::{Handler, traits::Interceptor},
+    model::Event,
+};
+
+/// Cleans up chat message text before routing so lookalike input such as
+/// `!p\u{200c}ing` still matches its command.
+///
+/// Zero-width and control characters are removed, any Unicode whitespace
+/// (including NBSP) becomes a single space, and the result is trimmed.
+pub(crate) struct NormalizeInterceptor;
+
+impl NormalizeInterceptor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Interceptor<Event> for NormalizeInterceptor {
+    fn wrap(&self, next: Arc<dyn Handler<Event>>) -> Arc<dyn Handler<Event>> {
+        Arc::new(NormalizingHandler { next })
+    }
+}
+
+struct NormalizingHandler {
+    next: Arc<dyn Handler<Event>>,
+}
+
+#[async_trait]
+impl Handler<Event> for NormalizingHandler {
+    #[instrument(name = "normalize", skip_all)]
+    async fn handle(&self, mut event: Event) -> anyhow::Result<()> {
+        if let Event::ChatMessage(message) = &mut event {
+            message.text = normalize(&message.text);
+        }
+        self.next.handle(event).await
+    }
+}
+
+fn normalize(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+
+    for word in text
+        .split(char::is_whitespace)
+        .map(|word| {
+            word.chars()
+                .filter(|c| !is_invisible(*c))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+    {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(&word);
+    }
+
+    normalized
+}
+
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{200b}'..='\u{200f}' | '\u{2060}'..='\u{2064}' | '\u{00ad}' | '\u{feff}'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_zero_width_characters() {
+        assert_eq!(normalize("!p\u{200c}ing"), "!ping");
+        assert_eq!(normalize("!pi\u{200d}ng"), "!ping");
+        assert_eq!(normalize("\u{feff}!ping\u{200b}"), "!ping");
+    }
+
+    #[test]
+    fn test_collapses_whitespace_including_nbsp() {
+        assert_eq!(normalize("  !music \u{00a0}\u{00a0} now  "), "!music now");
+        assert_eq!(normalize("!skip\u{00a0}please"), "!skip please");
+        assert_eq!(normalize("line\r\nbreak\tand tab"), "line break and tab");
+    }
+
+    #[test]
+    fn test_keeps_regular_text() {
+        assert_eq!(normalize("привет, чат!"), "привет, чат!");
+        assert_eq!(normalize("\u{200b} \u{200b}"), "");
+    }
+}
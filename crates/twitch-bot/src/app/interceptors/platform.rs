@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use crate::{
+    app::dispatch::{Handler, request::CommandRequest, traits::Interceptor},
+    model::Platform,
+};
+
+/// Lets a command through only when its author is on `platform`, e.g. a debug
+/// command that should only answer on the console. Others are ignored.
+pub(crate) struct PlatformInterceptor {
+    platform: Platform,
+}
+
+impl PlatformInterceptor {
+    pub fn only(platform: Platform) -> Self {
+        Self { platform }
+    }
+}
+
+impl Interceptor<CommandRequest> for PlatformInterceptor {
+    fn wrap(&self, next: Arc<dyn Handler<CommandRequest>>) -> Arc<dyn Handler<CommandRequest>> {
+        Arc::new(PlatformHandler {
+            platform: self.platform,
+            next,
+        })
+    }
+}
+
+struct PlatformHandler {
+    platform: Platform,
+    next: Arc<dyn Handler<CommandRequest>>,
+}
+
+#[async_trait]
+impl Handler<CommandRequest> for PlatformHandler {
+    #[instrument(name = "platform", skip_all)]
+    async fn handle(&self, request: CommandRequest) -> anyhow::Result<()> {
+        let platform = request.message.author.platform;
+        if platform != self.platform {
+            debug!(
+                command = %request.name(),
+                ?platform,
+                "command skipped: wrong platform"
+            );
+            return Ok(());
+        }
+
+        self.next.handle(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        app::test_support::{Counter, command},
+        model::User,
+    };
+
+    fn command_from(platform: Platform) -> CommandRequest {
+        let mut author = User::system();
+        author.platform = platform;
+        command(author, "!debug")
+    }
+
+    #[tokio::test]
+    async fn test_only_matching_platform_reaches_handler() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let handler =
+            PlatformInterceptor::only(Platform::Console).wrap(Arc::new(Counter(handled.clone())));
+
+        handler
+            .handle(command_from(Platform::Twitch))
+            .await
+            .unwrap();
+        assert_eq!(handled.load(Ordering::SeqCst), 0);
+
+        handler
+            .handle(command_from(Platform::Console))
+            .await
+            .unwrap();
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,4 +1,9 @@
 pub mod command;
 pub mod dispatch;
 pub mod handlers;
+pub mod history;
+pub mod interceptors;
 pub mod ports;
+pub mod scheduler;
+#[cfg(test)]
+pub(crate) mod test_support;
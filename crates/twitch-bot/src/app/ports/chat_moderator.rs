@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::model::ChatTarget;
+
+#[async_trait]
+pub trait ChatModerator: Send + Sync + 'static {
+    async fn delete_message(&self, target: &ChatTarget, message_id: &str) -> anyhow::Result<()>;
+
+    async fn timeout(
+        &self,
+        target: &ChatTarget,
+        user_id: &str,
+        duration: Duration,
+        reason: &str,
+    ) -> anyhow::Result<()>;
+}
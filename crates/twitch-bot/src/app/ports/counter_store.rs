@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+/// Named counters that survive restarts.
+#[async_trait]
+pub trait CounterStore: Send + Sync + 'static {
+    /// The current value; a counter never incremented reads as 0.
+    async fn get(&self, name: &str) -> anyhow::Result<u64>;
+
+    /// Adds one and returns the new value.
+    async fn increment(&self, name: &str) -> anyhow::Result<u64>;
+}
@@ -0,0 +1,8 @@
+use crate::model::Event;
+
+/// Receives a copy of every event the sources produce, after normalization.
+///
+/// Called on the consumer task for each event, so implementations must not block.
+pub trait EventPublisher: Send + Sync + 'static {
+    fn publish(&self, event: &Event);
+}
@@ -5,5 +5,7 @@ use crate::model::Event;
 
 #[async_trait]
 pub trait EventSource: Send + Sync + 'static {
-    async fn fetch(&self) -> mpsc::Receiver<Event>;
+    /// Starts the source. An error means it could not start at all, e.g. the
+    /// initial connection failed.
+    async fn fetch(&self) -> anyhow::Result<mpsc::Receiver<Event>>;
 }
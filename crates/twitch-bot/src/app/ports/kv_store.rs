@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+/// String keys to string values, shared by every feature that keeps state.
+///
+/// Features namespace their keys with a prefix such as `counter:`, so
+/// [`list_prefix`](KvStore::list_prefix) returns one feature's entries.
+#[async_trait]
+pub trait KvStore: Send + Sync + 'static {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<String>>;
+
+    async fn set(&self, key: &str, value: String) -> anyhow::Result<()>;
+
+    /// Removing a missing key is not an error.
+    #[allow(dead_code)] // nothing removes entries yet
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Every entry whose key starts with `prefix`, sorted by key.
+    async fn list_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>>;
+}
@@ -1,7 +1,17 @@
+pub mod chat_moderator;
+pub mod counter_store;
+pub mod event_publisher;
 pub mod event_source;
+pub mod kv_store;
 pub mod message_sink;
 pub mod now_playing;
+pub mod whisper_sink;
 
+pub use chat_moderator::ChatModerator;
+pub use counter_store::CounterStore;
+pub use event_publisher::EventPublisher;
 pub use event_source::EventSource;
+pub use kv_store::KvStore;
 pub use message_sink::MessageSink;
 pub use now_playing::{MusicSkipProvider, NowPlayingProvider};
+pub use whisper_sink::WhisperSink;
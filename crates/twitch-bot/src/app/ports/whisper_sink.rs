@@ -0,0 +1,6 @@
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait WhisperSink: Send + Sync + 'static {
+    async fn whisper(&self, user_id: &str, message: &str) -> anyhow::Result<()>;
+}
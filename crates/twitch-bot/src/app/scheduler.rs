@@ -0,0 +1,140 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::watch, time::Instant};
+use tracing::{info, warn};
+
+use crate::{app::ports::MessageSink, model::ChatTarget};
+
+pub(crate) struct ScheduledMessage {
+    pub interval: Duration,
+    pub message: String,
+}
+
+/// Posts each [`ScheduledMessage`] every `interval` while the stream is live.
+///
+/// Unlike handlers it is not driven by incoming events: [`Scheduler::run`] is
+/// a long-lived future fed only the live state, e.g. from
+/// [`StreamStatusHandler::subscribe`](crate::app::handlers::StreamStatusHandler::subscribe).
+/// Going offline pauses every timer; going live again restarts them, so nothing
+/// is posted the moment the stream starts.
+pub(crate) struct Scheduler<S> {
+    sender: Arc<S>,
+    target: ChatTarget,
+    messages: Vec<ScheduledMessage>,
+}
+
+impl<S> Scheduler<S>
+where
+    S: MessageSink,
+{
+    pub fn new(sender: Arc<S>, target: ChatTarget, messages: Vec<ScheduledMessage>) -> Self {
+        Self {
+            sender,
+            target,
+            messages,
+        }
+    }
+
+    /// Runs until the live state's sender is dropped.
+    pub async fn run(self, mut live: watch::Receiver<bool>) {
+        if self.messages.is_empty() {
+            return;
+        }
+
+        loop {
+            if live.wait_for(|live| *live).await.is_err() {
+                return;
+            }
+            info!(
+                messages = self.messages.len(),
+                "stream is live, starting scheduled messages"
+            );
+
+            let now = Instant::now();
+            let mut due: Vec<Instant> = self
+                .messages
+                .iter()
+                .map(|message| now + message.interval)
+                .collect();
+
+            loop {
+                let (index, at) = due
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .min_by_key(|(_, at)| *at)
+                    .expect("messages is not empty");
+
+                tokio::select! {
+                    () = tokio::time::sleep_until(at) => {
+                        let message = &self.messages[index];
+                        if let Err(error) = self.sender.send(&self.target, &message.message).await {
+                            warn!(error = %error, "failed to send scheduled message");
+                        }
+                        due[index] = Instant::now() + message.interval;
+                    }
+                    changed = live.changed() => {
+                        if changed.is_err() {
+                            return;
+                        }
+                        if !*live.borrow_and_update() {
+                            info!("stream is offline, pausing scheduled messages");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_support::RecordingSink;
+
+    fn scheduler(sink: Arc<RecordingSink>) -> Scheduler<RecordingSink> {
+        Scheduler::new(
+            sink,
+            ChatTarget::new("1", "chan"),
+            vec![
+                ScheduledMessage {
+                    interval: Duration::from_secs(60),
+                    message: "follow me".to_string(),
+                },
+                ScheduledMessage {
+                    interval: Duration::from_secs(150),
+                    message: "join the discord".to_string(),
+                },
+            ],
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_posts_only_while_live() {
+        let sink = Arc::new(RecordingSink::default());
+        let (live, receiver) = watch::channel(false);
+        let task = tokio::spawn(scheduler(sink.clone()).run(receiver));
+
+        tokio::time::sleep(Duration::from_secs(600)).await;
+        assert!(sink.messages().is_empty());
+
+        live.send_replace(true);
+        tokio::time::sleep(Duration::from_secs(190)).await;
+        assert_eq!(
+            sink.messages(),
+            ["follow me", "follow me", "join the discord", "follow me"]
+        );
+
+        live.send_replace(false);
+        tokio::time::sleep(Duration::from_secs(600)).await;
+        assert_eq!(sink.messages().len(), 4);
+
+        live.send_replace(true);
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert_eq!(sink.messages().len(), 4);
+
+        drop(live);
+        task.await.unwrap();
+    }
+}
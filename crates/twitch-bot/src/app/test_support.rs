@@ -0,0 +1,151 @@
+//! Fixtures shared by the handler, interceptor and runtime tests.
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{
+    adapters::fake::FakeSource,
+    app::{
+        command::CommandParser,
+        dispatch::{
+            Handler,
+            request::{ChatRequest, CommandRequest},
+        },
+        ports::{EventSource, MessageSink, WhisperSink},
+    },
+    model::{ChatMessage, ChatTarget, Event, EventContext, SystemEvent, User},
+    runtime::Shutdowner,
+};
+
+/// A [`MessageSink`] that keeps every message it is asked to send.
+#[derive(Default)]
+pub(crate) struct RecordingSink(Mutex<Vec<String>>);
+
+impl RecordingSink {
+    /// The messages sent so far, oldest first.
+    pub fn messages(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MessageSink for RecordingSink {
+    async fn send(&self, _target: &ChatTarget, message: &str) -> anyhow::Result<()> {
+        self.0.lock().unwrap().push(message.to_string());
+        Ok(())
+    }
+}
+
+/// Whispers are recorded as `/w <user_id> <message>`.
+#[async_trait]
+impl WhisperSink for RecordingSink {
+    async fn whisper(&self, user_id: &str, message: &str) -> anyhow::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("/w {user_id} {message}"));
+        Ok(())
+    }
+}
+
+/// A message from `author` in `#chan`, without a message id.
+pub(crate) fn chat_message(author: User, text: &str) -> ChatMessage {
+    ChatMessage {
+        author,
+        target: ChatTarget::new("1", "chan"),
+        text: text.to_string(),
+        message_id: None,
+        received_at: SystemTime::now(),
+        context: EventContext::default(),
+    }
+}
+
+/// Parses `text` with the `!` prefix; panics unless it is a command.
+pub(crate) fn command(author: User, text: &str) -> CommandRequest {
+    let message = chat_message(author, text);
+    CommandRequest::try_from(ChatRequest::from_message(message, &CommandParser::new("!"))).unwrap()
+}
+
+/// A handler for any request that only counts how often it ran.
+pub(crate) struct Counter(pub Arc<AtomicUsize>);
+
+#[async_trait]
+impl<Request: Send + 'static> Handler<Request> for Counter {
+    async fn handle(&self, _request: Request) -> anyhow::Result<()> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// A system event carrying `message`.
+pub(crate) fn system_event(message: &str) -> Event {
+    Event::System(SystemEvent {
+        message: message.to_string(),
+        received_at: SystemTime::now(),
+        context: EventContext::default(),
+    })
+}
+
+/// A [`FakeSource`] that records being shut down and can be told to fail its
+/// `fetch` or its `shutdown`.
+pub(crate) struct ProbeSource {
+    source: FakeSource,
+    fail_fetch: bool,
+    fail_shutdown: bool,
+    shut_down: Arc<AtomicBool>,
+}
+
+impl ProbeSource {
+    pub fn new(events: Vec<Event>) -> Self {
+        Self {
+            source: FakeSource::new(events),
+            fail_fetch: false,
+            fail_shutdown: false,
+            shut_down: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn failing_fetch(mut self) -> Self {
+        self.fail_fetch = true;
+        self
+    }
+
+    pub fn failing_shutdown(mut self) -> Self {
+        self.fail_shutdown = true;
+        self
+    }
+
+    /// Set once `shutdown` is called; keep it to check after the source is moved.
+    pub fn shut_down(&self) -> Arc<AtomicBool> {
+        self.shut_down.clone()
+    }
+}
+
+#[async_trait]
+impl EventSource for ProbeSource {
+    async fn fetch(&self) -> anyhow::Result<mpsc::Receiver<Event>> {
+        if self.fail_fetch {
+            anyhow::bail!("connect failed");
+        }
+        self.source.fetch().await
+    }
+}
+
+#[async_trait]
+impl Shutdowner for ProbeSource {
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.shut_down.store(true, Ordering::SeqCst);
+        if self.fail_shutdown {
+            anyhow::bail!("shutdown failed");
+        }
+        self.source.shutdown().await
+    }
+}
@@ -1,95 +1,378 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+
+use arc_swap::ArcSwap;
+use regex::Regex;
 
 use crate::{
     adapters::{
+        storage::{JsonKvStore, KvCounterStore, MemoryKvStore},
         system::{PlayerctlNowPlayingProvider, PlayerctlSkipProvider},
-        twitch::{TwitchChatSink, TwitchEventSubSource},
+        twitch::{
+            ChatRoomTracker, ReplaySource, TwitchChatSink, TwitchEventSubSource, TwitchModerator,
+        },
+        webhook::WebhookSink,
     },
     app::{
-        command::{MUSIC_COMMAND_NAME, SKIP_COMMAND_NAME},
-        dispatch::{ChatRouter, CommandRouter, EventRouter, Handler, RewardRouter},
-        dispatch::request::{ChatRequest, CommandRequest, RewardRequest},
+        command::{
+            CommandParser, HELP_COMMAND_NAME, LAST_SEEN_COMMAND_NAME, MUSIC_COMMAND_NAME,
+            PING_COMMAND_NAME, QUOTE_COMMAND_NAME, SKIP_COMMAND_NAME,
+        },
+        dispatch::{ChatRouter, CommandRouter, EventRouter, Handler, HandlerStack, RewardRouter},
+        dispatch::request::{ChatRequest, CommandRequest, PlainMessageRequest, RewardRequest},
         handlers::{
-            PlainMessageHandler, SystemHandler,
-            commands::{MusicHandler, SkipHandler, UnknownCommandHandler},
+            AutoResponderHandler, AutoResponse, DonationHandler, Matcher, PlainMessageHandler,
+            PublishHandler, StreamStatusHandler, SystemHandler, WhisperHandler,
+            commands::{
+                CounterHandler, HelpHandler, INCREMENT_SUFFIX, LastSeenHandler, MusicHandler,
+                PingHandler, QuoteHandler, SkipHandler, UnknownCommandHandler,
+            },
             rewards::RewardRedemptionHandler,
         },
+        history::MessageHistory,
+        interceptors::{
+            AuthInterceptor, HistoryInterceptor, ModerationAction, ModerationInterceptor,
+            ModerationPolicy, NormalizeInterceptor, PlatformInterceptor,
+        },
+        ports::{EventPublisher, KvStore},
+        scheduler::{ScheduledMessage, Scheduler},
+    },
+    config::{
+        Config, ConfigLoader,
+        model::{ModerationActionKind, ModerationConfig, SchedulerConfig, StorageConfig},
+    },
+    model::{ChatTarget, Event, Platform, Role},
+    runtime::{
+        BatchConsumer, Consumer, DefaultSignalHandler, EventConsumer, FanOutConsumer,
+        ManagedSource, StopCondition, Supervisor,
     },
-    config::ConfigLoader,
-    model::Event,
-    runtime::{Consumer, Supervisor, UnixSignalHandler},
 };
-use twitch_sdk::TokenManager;
+use twitch_sdk::{EventSubTopic, TokenManager};
 
 fn build_command_router(
     twitch_sender: Arc<TwitchChatSink>,
     now_playing: Arc<PlayerctlNowPlayingProvider>,
     skip_provider: Arc<PlayerctlSkipProvider>,
+    storage: Arc<dyn KvStore>,
+    counter_store: Arc<KvCounterStore>,
+    history: Option<Arc<MessageHistory>>,
+    config: &Config,
 ) -> anyhow::Result<Arc<dyn Handler<CommandRequest>>> {
-    CommandRouter::builder()
+    let moderator_only = || match config.twitch.bot.denial_reply.as_deref() {
+        Some(template) => AuthInterceptor::with_denial_reply(Role::MODERATOR, twitch_sender.clone(), template),
+        None => AuthInterceptor::new(Role::MODERATOR),
+    };
+    let mut builder = CommandRouter::builder()
         .route(MUSIC_COMMAND_NAME, Arc::new(MusicHandler::new(
             twitch_sender.clone(),
             now_playing,
         )))
-        .route(SKIP_COMMAND_NAME, Arc::new(SkipHandler::new(twitch_sender, skip_provider)))
+        .route(PING_COMMAND_NAME, Arc::new(PingHandler::new(twitch_sender.clone())))
+        .route(QUOTE_COMMAND_NAME, Arc::new(QuoteHandler::new(
+            twitch_sender.clone(),
+            storage,
+            moderator_only(),
+        )))
+        // skipping drives the local player, so only Twitch chat may do it
+        .route(SKIP_COMMAND_NAME, HandlerStack::new()
+            .with(PlatformInterceptor::only(Platform::Twitch))
+            .finish(Arc::new(SkipHandler::new(twitch_sender.clone(), skip_provider))));
+    let mut commands: Vec<String> = [MUSIC_COMMAND_NAME, PING_COMMAND_NAME, QUOTE_COMMAND_NAME, SKIP_COMMAND_NAME]
+        .map(String::from)
+        .into();
+
+    if let Some(counters) = &config.counters {
+        for name in &counters.names {
+            let handler: Arc<dyn Handler<CommandRequest>> = Arc::new(CounterHandler::new(
+                twitch_sender.clone(),
+                counter_store.clone(),
+                name.as_str(),
+            ));
+            let increment = HandlerStack::new()
+                .with(moderator_only())
+                .finish(handler.clone());
+            builder = builder
+                .route(name.as_str(), handler)
+                .route(format!("{name}{INCREMENT_SUFFIX}"), increment);
+            commands.push(name.clone());
+        }
+    }
+
+    if let Some(history) = history {
+        builder = builder.route(LAST_SEEN_COMMAND_NAME, Arc::new(LastSeenHandler::new(twitch_sender.clone(), history)));
+        commands.push(LAST_SEEN_COMMAND_NAME.to_string());
+    }
+
+    commands.push(HELP_COMMAND_NAME.to_string());
+    let whisper_help = config.whispers.as_ref().is_some_and(|whispers| whispers.help);
+    builder = builder.route(HELP_COMMAND_NAME, Arc::new(HelpHandler::new(twitch_sender, commands, whisper_help)));
+
+    builder
         .fallback(Arc::new(UnknownCommandHandler::new()))
         .build()
 }
 
 fn build_chat_router(
     command_router: Arc<dyn Handler<CommandRequest>>,
+    plain_message_handler: Arc<dyn Handler<PlainMessageRequest>>,
 ) -> anyhow::Result<Arc<dyn Handler<ChatRequest>>> {
     ChatRouter::builder()
-        .plain_message(Arc::new(PlainMessageHandler::new()))
+        .plain_message(plain_message_handler)
         .command(command_router)
         .build()
 }
 
+fn build_plain_message_handler(
+    config: &Config,
+    twitch_sender: Arc<TwitchChatSink>,
+) -> anyhow::Result<Arc<dyn Handler<PlainMessageRequest>>> {
+    let Some(auto_responder) = &config.auto_responder else {
+        return Ok(Arc::new(PlainMessageHandler::new()));
+    };
+
+    let rules = auto_responder
+        .rules
+        .iter()
+        .map(|rule| {
+            let matcher = if rule.regex {
+                Matcher::Regex(Regex::new(&rule.pattern)?)
+            } else {
+                Matcher::contains(&rule.pattern)
+            };
+            Ok(AutoResponse::new(matcher, rule.response.as_str()))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok(Arc::new(AutoResponderHandler::new(
+        twitch_sender,
+        rules,
+        Duration::from_secs(auto_responder.cooldown_secs),
+    )))
+}
+
+fn build_moderation_interceptor(
+    config: &ModerationConfig,
+    moderator: Arc<TwitchModerator>,
+) -> ModerationInterceptor<TwitchModerator> {
+    let action = match config.action {
+        ModerationActionKind::Delete => ModerationAction::Delete,
+        ModerationActionKind::Timeout => {
+            ModerationAction::Timeout(Duration::from_secs(config.timeout_secs))
+        }
+    };
+    let policy = ModerationPolicy {
+        link_min_role: config.link_min_role,
+        max_caps_percent: config.max_caps_percent,
+        caps_min_letters: config.caps_min_letters,
+        max_repeated_chars: config.max_repeated_chars,
+        action,
+    };
+
+    ModerationInterceptor::new(moderator, policy)
+}
+
+async fn build_storage(config: &StorageConfig) -> anyhow::Result<Arc<dyn KvStore>> {
+    Ok(match &config.file {
+        Some(file) => Arc::new(JsonKvStore::open(file.as_str()).await?),
+        None => Arc::new(MemoryKvStore::new()),
+    })
+}
+
 fn build_reward_router() -> anyhow::Result<Arc<dyn Handler<RewardRequest>>> {
     RewardRouter::builder()
         .fallback(Arc::new(RewardRedemptionHandler::new()))
         .build()
 }
 
+fn build_command_parser(config: &Config) -> CommandParser {
+    let bot = &config.twitch.bot;
+    let mut command_parser = CommandParser::new(bot.command_prefix.as_str());
+    for (channel, prefix) in &bot.channel_prefixes {
+        command_parser = command_parser.with_channel_prefix(channel, prefix.as_str());
+    }
+    if bot.respond_to_mentions {
+        command_parser = command_parser.with_mention(bot.nick.as_str());
+    }
+    command_parser
+}
+
+fn build_scheduler(
+    config: &Config,
+    scheduler: &SchedulerConfig,
+    twitch_sender: Arc<TwitchChatSink>,
+) -> Scheduler<TwitchChatSink> {
+    let channel = config.twitch.bot.channels.first().map_or("", |channel| channel.as_str());
+    let target = ChatTarget::new(config.twitch.auth.broadcaster_id.as_str(), channel);
+    let messages = scheduler
+        .messages
+        .iter()
+        .map(|message| ScheduledMessage {
+            interval: Duration::from_secs(message.interval_mins * 60),
+            message: message.message.clone(),
+        })
+        .collect();
+
+    Scheduler::new(twitch_sender, target, messages)
+}
+
 fn build_event_router(
+    command_parser: Arc<ArcSwap<CommandParser>>,
     chat_router: Arc<dyn Handler<ChatRequest>>,
     reward_router: Arc<dyn Handler<RewardRequest>>,
+    stream_status: Arc<StreamStatusHandler>,
+    history: Option<Arc<MessageHistory>>,
 ) -> anyhow::Result<Arc<dyn Handler<Event>>> {
-    EventRouter::builder()
+    let mut builder = EventRouter::builder()
+        .shared_command_parser(command_parser)
+        .interceptor(Arc::new(NormalizeInterceptor::new()))
         .chat(chat_router)
         .reward(reward_router)
-        .system(Arc::new(SystemHandler::new()))
-        .build()
+        .donation(Arc::new(DonationHandler::new()))
+        .whisper(Arc::new(WhisperHandler::new()))
+        .stream_status(stream_status)
+        .system(Arc::new(SystemHandler::new()));
+
+    // after normalization, so the history keeps the cleaned-up text
+    if let Some(history) = history {
+        builder = builder.interceptor(Arc::new(HistoryInterceptor::new(history)));
+    }
+
+    builder.build()
 }
 
 pub(crate) async fn run() -> anyhow::Result<()> {
+    let stop_condition = StopCondition::from_args(std::env::args().skip(1))?;
     let config = ConfigLoader::load()?;
     let token_manager = Arc::new(TokenManager::new(
         config.twitch.auth.client_id.as_str().to_string(),
         config.twitch.auth.client_secret.as_str().to_string(),
         config.twitch.auth.refresh_token.as_str().to_string(),
     ));
-    let _token_refresh = token_manager.clone().start_background_loop();
+    let token_refresh_cancel = token_manager.cancel_token();
+    let mut token_refresh = token_manager.clone().start_background_loop();
 
+    let room_tracker = Arc::new(ChatRoomTracker::new(
+        config.twitch.auth.broadcaster_id.as_str(),
+        config.twitch.auth.writer_id.as_str(),
+    ));
     let twitch_sender = Arc::new(TwitchChatSink::new(
         &config.twitch.auth,
         token_manager.clone(),
-    )?);
+    )?.with_room_tracker(room_tracker.clone()));
     let now_playing = Arc::new(PlayerctlNowPlayingProvider::new());
     let skip_provider = Arc::new(PlayerctlSkipProvider::new());
 
+    let storage = build_storage(&config.storage).await?;
+    let counter_store = Arc::new(KvCounterStore::new(storage.clone()));
+    let history = config
+        .history
+        .as_ref()
+        .map(|history| Arc::new(MessageHistory::new(history.per_user, history.max_users)));
     let command_router = build_command_router(
         twitch_sender.clone(),
         now_playing.clone(),
         skip_provider,
+        storage,
+        counter_store,
+        history.clone(),
+        &config,
     )?;
-    let chat_router = build_chat_router(command_router)?;
+    let mut plain_message_stack = HandlerStack::new();
+    if let Some(moderation) = &config.moderation {
+        let moderator = Arc::new(TwitchModerator::new(&config.twitch.auth, token_manager.clone())?);
+        plain_message_stack = plain_message_stack.with(build_moderation_interceptor(moderation, moderator));
+    }
+    let plain_message_handler =
+        plain_message_stack.finish(build_plain_message_handler(&config, twitch_sender.clone())?);
+    let chat_router = build_chat_router(command_router, plain_message_handler)?;
     let reward_router = build_reward_router()?;
-    let event_router = build_event_router(chat_router, reward_router)?;
+    let command_parser = Arc::new(ArcSwap::from_pointee(build_command_parser(&config)));
+    let publisher = match &config.webhook {
+        Some(webhook) => Some(Arc::new(WebhookSink::new(webhook)?) as Arc<dyn EventPublisher>),
+        None => None,
+    };
+    let stream_status = Arc::new(StreamStatusHandler::new());
+    let scheduler = config.scheduler.as_ref().map(|scheduler| {
+        let scheduler = build_scheduler(&config, scheduler, twitch_sender.clone());
+        tokio::spawn(scheduler.run(stream_status.subscribe()))
+    });
+    let event_router = build_event_router(
+        command_parser.clone(),
+        chat_router,
+        reward_router,
+        stream_status,
+        history,
+    )?;
 
-    let consumer = Consumer::new(event_router);
-    let fetcher = TwitchEventSubSource::new(&config.twitch.auth, token_manager)?;
-    let app = Supervisor::new(UnixSignalHandler::new(), fetcher, consumer)?;
+    let consumer: Box<dyn EventConsumer> = match config.consumer.max_batch {
+        Some(max_batch) => Box::new(BatchConsumer::new(event_router, max_batch)),
+        None => Box::new(Consumer::new(event_router)),
+    };
+    let mut consumers = vec![consumer];
+    if let Some(publisher) = publisher {
+        // next to the dispatch pipeline, so publishing doesn't wait on a handler
+        let publish = HandlerStack::new()
+            .with(NormalizeInterceptor::new())
+            .finish(Arc::new(PublishHandler::new(publisher)));
+        consumers.push(Box::new(Consumer::new(publish)));
+    }
+    let consumer = FanOutConsumer::combine(consumers);
+    let source: Box<dyn ManagedSource> = match &config.replay {
+        Some(replay) => Box::new(ReplaySource::new(replay)?),
+        None => {
+            // the chat sink skips messages the room's modes would drop
+            let mut topics = vec![EventSubTopic::ChatSettings];
+            // the scheduler pauses while offline, so it needs to hear about it
+            if config.scheduler.is_some() {
+                topics.push(EventSubTopic::StreamStatus);
+            }
+            if config.whispers.is_some() {
+                topics.push(EventSubTopic::Whispers);
+            }
+            Box::new(
+                TwitchEventSubSource::new(&config.twitch.auth, token_manager, &topics)?
+                    .with_room_tracker(room_tracker),
+            )
+        }
+    };
+    let app = Supervisor::builder()
+        .signal_handler(DefaultSignalHandler::new())
+        .source(source)
+        .consumer(consumer)
+        .shutdown_timeout(Duration::from_secs(config.shutdown.timeout_secs))
+        .fail_on_shutdown_timeout(config.shutdown.fail_on_timeout)
+        .logging(config.logging)
+        // only the command parser settings are reloadable for now
+        .on_reload(Box::new(move || {
+            let config = ConfigLoader::load()?;
+            command_parser.store(Arc::new(build_command_parser(&config)));
+            Ok(())
+        }))
+        .build()?;
+    let run = async {
+        match stop_condition {
+            Some(condition) => app.run_until(condition).await,
+            None => app.run().await,
+        }
+    };
+    tokio::pin!(run);
 
-    app.run().await
+    // a revoked refresh token can't recover on its own, so stop instead of
+    // running without working credentials
+    let result = tokio::select! {
+        result = &mut run => result,
+        stopped = &mut token_refresh => match stopped? {
+            // cancelled along with the EventSub source, so the app is shutting down
+            Ok(()) => run.await,
+            Err(error) => Err(error.into()),
+        },
+    };
+    if let Some(scheduler) = scheduler {
+        scheduler.abort();
+    }
+    // stop the refresh loop too; the run's own result is what gets reported
+    token_refresh_cancel.cancel();
+    if !token_refresh.is_finished() {
+        let _ = token_refresh.await;
+    }
+    result
 }
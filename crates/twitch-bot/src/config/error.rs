@@ -9,6 +9,17 @@ pub(crate) enum ConfigError {
     ParseError(#[from] serde_yaml::Error),
     #[error("validation error: {field} - {message}")]
     ValidationError { field: String, message: String },
+    /// Every problem found in one pass, so they can all be fixed at once.
+    #[error("{} validation errors: {}", .0.len(), join_errors(.0))]
+    ValidationErrors(Vec<ConfigError>),
+}
+
+fn join_errors(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 impl From<WrapperValidationError> for ConfigError {
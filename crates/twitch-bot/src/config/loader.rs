@@ -12,9 +12,7 @@ impl ConfigLoader {
         let content = fs::read_to_string(&config_path)
             .map_err(|_| ConfigError::FileNotFound(config_path.display().to_string()))?;
 
-        let config: Config = serde_yaml::from_str(&content)?;
-
-        validate::validate(config)
+        validate::parse(&content)
     }
 }
 
@@ -1,8 +1,12 @@
 #![allow(dead_code)]
 
+use std::{collections::HashMap, num::NonZeroUsize};
+
 use macros::WrapperType;
 use serde::Deserialize;
 
+use crate::{app::command::DEFAULT_COMMAND_PREFIX, model::Role};
+
 #[derive(Debug, WrapperType)]
 pub(crate) struct ClientId(String);
 #[derive(Debug, WrapperType)]
@@ -19,12 +23,58 @@ pub(crate) struct Channel(String);
 pub(crate) struct BroadcasterId(String);
 #[derive(Debug, WrapperType)]
 pub(crate) struct WriterId(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct CommandPrefix(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct WebhookUrl(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct WebhookSecret(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct ReplayPath(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct StorageFile(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct LogDir(String);
+#[derive(Debug, WrapperType)]
+pub(crate) struct LogFilePrefix(String);
 
 #[derive(Debug, Deserialize)]
 pub(crate) struct Config {
     #[serde(default = "default_environment")]
     pub environment: Environment,
     pub twitch: TwitchConfig,
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    /// Reads events from a recorded file instead of connecting to Twitch.
+    #[serde(default)]
+    pub replay: Option<ReplayConfig>,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub consumer: ConsumerConfig,
+    /// Canned answers to plain chat messages.
+    #[serde(default)]
+    pub auto_responder: Option<AutoResponderConfig>,
+    /// Automatic deletes or timeouts for links, caps and character spam.
+    #[serde(default)]
+    pub moderation: Option<ModerationConfig>,
+    /// Where stateful features such as counters keep their data.
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Chat counters such as `!deaths`, kept in `storage`.
+    #[serde(default)]
+    pub counters: Option<CountersConfig>,
+    /// Messages posted on a timer while the stream is live.
+    #[serde(default)]
+    pub scheduler: Option<SchedulerConfig>,
+    /// Recent chat messages kept in memory for `!lastseen`.
+    #[serde(default)]
+    pub history: Option<HistoryConfig>,
+    /// Whispers sent to the bot account, and whether `!help` answers by whisper.
+    #[serde(default)]
+    pub whispers: Option<WhispersConfig>,
 }
 
 fn default_environment() -> Environment {
@@ -70,4 +120,231 @@ pub(crate) struct TwitchBot {
     pub channels: Vec<Channel>,
     pub broadcaster_id: BroadcasterId,
     pub writer_id: WriterId,
+    #[serde(default = "default_command_prefix")]
+    pub command_prefix: CommandPrefix,
+    /// Per-channel overrides of `command_prefix`, keyed by channel login.
+    #[serde(default)]
+    pub channel_prefixes: HashMap<String, CommandPrefix>,
+    #[serde(default)]
+    pub respond_to_mentions: bool,
+    /// Reply to moderator-only commands from anyone else, with `{user}`
+    /// replaced by their display name; unset ignores them silently.
+    #[serde(default)]
+    pub denial_reply: Option<String>,
+}
+
+fn default_command_prefix() -> CommandPrefix {
+    CommandPrefix(DEFAULT_COMMAND_PREFIX.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WebhookConfig {
+    pub url: WebhookUrl,
+    pub secret: WebhookSecret,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReplayConfig {
+    pub path: ReplayPath,
+    #[serde(default)]
+    pub speed: ReplaySpeed,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ShutdownConfig {
+    /// Seconds to wait for in-flight handlers once shutdown starts.
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Exit with an error instead of `Ok` when the timeout elapses.
+    #[serde(default)]
+    pub fail_on_timeout: bool,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_shutdown_timeout_secs(),
+            fail_on_timeout: false,
+        }
+    }
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AutoResponderConfig {
+    /// Seconds a rule stays quiet in a channel after it answered there.
+    #[serde(default = "default_auto_response_cooldown_secs")]
+    pub cooldown_secs: u64,
+    pub rules: Vec<AutoResponseRule>,
+}
+
+fn default_auto_response_cooldown_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct AutoResponseRule {
+    /// A case-insensitive substring, or a regex when `regex` is set.
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+    pub response: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ModerationConfig {
+    /// Lowest role allowed to post links.
+    #[serde(default = "default_link_min_role")]
+    pub link_min_role: Role,
+    /// Percent of uppercase letters above which a message is moderated; unset disables the check.
+    #[serde(default)]
+    pub max_caps_percent: Option<u8>,
+    /// Messages with fewer letters are never caps-checked.
+    #[serde(default = "default_caps_min_letters")]
+    pub caps_min_letters: usize,
+    /// Longest allowed run of one character; unset disables the check.
+    #[serde(default)]
+    pub max_repeated_chars: Option<usize>,
+    #[serde(default)]
+    pub action: ModerationActionKind,
+    /// Used when `action` is `timeout`.
+    #[serde(default = "default_moderation_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_link_min_role() -> Role {
+    Role::SUBSCRIBER
+}
+
+fn default_caps_min_letters() -> usize {
+    10
+}
+
+fn default_moderation_timeout_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub(crate) enum ModerationActionKind {
+    #[default]
+    #[serde(rename = "delete")]
+    Delete,
+    #[serde(rename = "timeout")]
+    Timeout,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct StorageConfig {
+    /// JSON file holding all stored state, created on the first write; unset
+    /// keeps state in memory only.
+    #[serde(default)]
+    pub file: Option<StorageFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CountersConfig {
+    /// Each name becomes a command that reports the counter, plus the same
+    /// name with a `+` that increments it (moderators only).
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SchedulerConfig {
+    pub messages: Vec<ScheduledMessageConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ScheduledMessageConfig {
+    /// Minutes between posts, counted from when the stream goes live.
+    pub interval_mins: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct HistoryConfig {
+    /// Messages kept per user; older ones are dropped first.
+    #[serde(default = "default_history_per_user")]
+    pub per_user: NonZeroUsize,
+    /// Users remembered at once; the one quiet the longest is forgotten first.
+    #[serde(default = "default_history_max_users")]
+    pub max_users: NonZeroUsize,
+}
+
+fn default_history_per_user() -> NonZeroUsize {
+    NonZeroUsize::new(20).expect("non-zero")
+}
+
+fn default_history_max_users() -> NonZeroUsize {
+    NonZeroUsize::new(1000).expect("non-zero")
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WhispersConfig {
+    /// Answer `!help` with a whisper to the user who asked instead of in chat.
+    #[serde(default)]
+    pub help: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ConsumerConfig {
+    /// Hand queued events to handlers in batches of up to this many; unset
+    /// spawns one task per event.
+    #[serde(default)]
+    pub max_batch: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LoggingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    #[serde(default = "default_log_stdout")]
+    pub stdout: bool,
+    /// Also writes logs to files rotated daily.
+    #[serde(default)]
+    pub file: Option<LogFileConfig>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            stdout: default_log_stdout(),
+            file: None,
+        }
+    }
+}
+
+fn default_log_stdout() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LogFileConfig {
+    pub dir: LogDir,
+    /// File names are `<prefix>.<yyyy-mm-dd>`.
+    pub prefix: LogFilePrefix,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub(crate) enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    #[serde(rename = "text")]
+    Text,
+    /// One JSON object per line.
+    #[serde(rename = "json")]
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub(crate) enum ReplaySpeed {
+    /// Waits out the gaps between the recorded timestamps.
+    #[serde(rename = "real_time")]
+    RealTime,
+    #[default]
+    #[serde(rename = "fast")]
+    AsFastAsPossible,
 }
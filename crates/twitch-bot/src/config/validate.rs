@@ -1,5 +1,527 @@
-use crate::config::{Config, ConfigError};
+use regex::Regex;
+use serde_yaml::Value;
 
+use crate::{
+    app::handlers::commands::INCREMENT_SUFFIX,
+    config::{Config, ConfigError},
+};
+
+const CHANNELS_FIELD: &str = "twitch.bot.channels";
+const COMMAND_PREFIX_FIELD: &str = "twitch.bot.command_prefix";
+const CHANNEL_PREFIXES_FIELD: &str = "twitch.bot.channel_prefixes";
+const DENIAL_REPLY_FIELD: &str = "twitch.bot.denial_reply";
+const AUTO_RESPONDER_RULES_FIELD: &str = "auto_responder.rules";
+const MODERATION_FIELD: &str = "moderation";
+const COUNTER_NAMES_FIELD: &str = "counters.names";
+const SCHEDULER_MESSAGES_FIELD: &str = "scheduler.messages";
+/// Keys without a default. A key is only required when its parent is set, so
+/// `webhook.url` only matters once there is a `webhook`; `[]` checks every
+/// item of a list.
+const REQUIRED_FIELDS: &[&str] = &[
+    "twitch",
+    "twitch.auth",
+    "twitch.auth.client_id",
+    "twitch.auth.client_secret",
+    "twitch.auth.access_token",
+    "twitch.auth.broadcaster_id",
+    "twitch.auth.refresh_token",
+    "twitch.auth.writer_id",
+    "twitch.bot",
+    "twitch.bot.nick",
+    "twitch.bot.channels",
+    "twitch.bot.broadcaster_id",
+    "twitch.bot.writer_id",
+    "webhook.url",
+    "webhook.secret",
+    "replay.path",
+    "auto_responder.rules",
+    "auto_responder.rules[].pattern",
+    "auto_responder.rules[].response",
+    "counters.names",
+    "scheduler.messages",
+    "scheduler.messages[].interval_mins",
+    "scheduler.messages[].message",
+    "logging.file.dir",
+    "logging.file.prefix",
+];
+/// Twitch's longest timeout, two weeks.
+const MAX_TIMEOUT_SECS: u64 = 1_209_600;
+const MAX_LOGIN_LEN: usize = 25;
+
+/// Parses `content` and validates it. Missing keys are all reported together
+/// before parsing; anything else is checked by [`validate`].
+pub(crate) fn parse(content: &str) -> Result<Config, ConfigError> {
+    let value: Value = serde_yaml::from_str(content)?;
+
+    let mut errors = Vec::new();
+    for path in REQUIRED_FIELDS {
+        let segments: Vec<_> = path.split('.').collect();
+        find_missing(&value, String::new(), &segments, &mut errors);
+    }
+    into_result(errors)?;
+
+    validate(serde_yaml::from_value(value)?)
+}
+
+fn find_missing(value: &Value, prefix: String, segments: &[&str], errors: &mut Vec<ConfigError>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if let Some(key) = segment.strip_suffix("[]") {
+        if let Some(Value::Sequence(items)) = value.get(key) {
+            for (index, item) in items.iter().enumerate() {
+                find_missing(item, format!("{prefix}{key}[{index}]."), rest, errors);
+            }
+        }
+        return;
+    }
+
+    match value.get(segment) {
+        None | Some(Value::Null) if rest.is_empty() => errors.push(ConfigError::ValidationError {
+            field: format!("{prefix}{segment}"),
+            message: "is required".to_string(),
+        }),
+        Some(child @ Value::Mapping(_)) => {
+            find_missing(child, format!("{prefix}{segment}."), rest, errors)
+        }
+        _ => {}
+    }
+}
+
+/// Checks everything and reports all problems together; a single problem is
+/// returned as is.
 pub(crate) fn validate(config: Config) -> Result<Config, ConfigError> {
-    Ok(config)
+    let mut errors = Vec::new();
+    validate_channels(&config, &mut errors);
+    validate_command_prefix(&config, &mut errors);
+    validate_channel_prefixes(&config, &mut errors);
+    validate_denial_reply(&config, &mut errors);
+    validate_auto_responder(&config, &mut errors);
+    validate_moderation(&config, &mut errors);
+    validate_counters(&config, &mut errors);
+    validate_scheduler(&config, &mut errors);
+
+    into_result(errors).map(|()| config)
+}
+
+fn into_result(mut errors: Vec<ConfigError>) -> Result<(), ConfigError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(ConfigError::ValidationErrors(errors)),
+    }
+}
+
+fn validate_channels(config: &Config, errors: &mut Vec<ConfigError>) {
+    let channels = &config.twitch.bot.channels;
+
+    if channels.is_empty() {
+        errors.push(ConfigError::ValidationError {
+            field: CHANNELS_FIELD.to_string(),
+            message: "is set but lists no channels".to_string(),
+        });
+    }
+
+    for (index, channel) in channels.iter().enumerate() {
+        if let Err(reason) = check_login(channel.as_str()) {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{CHANNELS_FIELD}[{index}]"),
+                message: format!("invalid channel name {:?}: {reason}", channel.as_str()),
+            });
+        }
+    }
+}
+
+fn validate_command_prefix(config: &Config, errors: &mut Vec<ConfigError>) {
+    if let Err(reason) = check_prefix(config.twitch.bot.command_prefix.as_str()) {
+        errors.push(ConfigError::ValidationError {
+            field: COMMAND_PREFIX_FIELD.to_string(),
+            message: reason.to_string(),
+        });
+    }
+}
+
+fn validate_channel_prefixes(config: &Config, errors: &mut Vec<ConfigError>) {
+    let mut prefixes: Vec<_> = config.twitch.bot.channel_prefixes.iter().collect();
+    prefixes.sort_by_key(|(channel, _)| *channel);
+
+    for (channel, prefix) in prefixes {
+        let field = format!("{CHANNEL_PREFIXES_FIELD}.{channel}");
+        if let Err(reason) = check_login(channel) {
+            errors.push(ConfigError::ValidationError {
+                field: field.clone(),
+                message: format!("invalid channel name {channel:?}: {reason}"),
+            });
+        }
+        if let Err(reason) = check_prefix(prefix.as_str()) {
+            errors.push(ConfigError::ValidationError {
+                field,
+                message: reason.to_string(),
+            });
+        }
+    }
+}
+
+/// A blank prefix would make every message a command, and whitespace in one
+/// is almost always a quoting mistake that no chatter would type.
+fn check_prefix(prefix: &str) -> Result<(), &'static str> {
+    if prefix.trim().is_empty() {
+        Err("cannot be blank")
+    } else if prefix.contains(char::is_whitespace) {
+        Err("cannot contain whitespace")
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_denial_reply(config: &Config, errors: &mut Vec<ConfigError>) {
+    if config
+        .twitch
+        .bot
+        .denial_reply
+        .as_ref()
+        .is_some_and(|reply| reply.trim().is_empty())
+    {
+        errors.push(ConfigError::ValidationError {
+            field: DENIAL_REPLY_FIELD.to_string(),
+            message: "cannot be blank, leave it unset to ignore denied commands".to_string(),
+        });
+    }
+}
+
+fn validate_auto_responder(config: &Config, errors: &mut Vec<ConfigError>) {
+    let Some(auto_responder) = &config.auto_responder else {
+        return;
+    };
+
+    for (index, rule) in auto_responder.rules.iter().enumerate() {
+        let field = format!("{AUTO_RESPONDER_RULES_FIELD}[{index}]");
+        if rule.pattern.trim().is_empty() {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{field}.pattern"),
+                message: "cannot be blank".to_string(),
+            });
+        } else if rule.regex
+            && let Err(error) = Regex::new(&rule.pattern)
+        {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{field}.pattern"),
+                message: format!("invalid regex: {error}"),
+            });
+        }
+        if rule.response.trim().is_empty() {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{field}.response"),
+                message: "cannot be blank".to_string(),
+            });
+        }
+    }
+}
+
+fn validate_moderation(config: &Config, errors: &mut Vec<ConfigError>) {
+    let Some(moderation) = &config.moderation else {
+        return;
+    };
+
+    if moderation
+        .max_caps_percent
+        .is_some_and(|percent| percent > 100)
+    {
+        errors.push(ConfigError::ValidationError {
+            field: format!("{MODERATION_FIELD}.max_caps_percent"),
+            message: "must be at most 100".to_string(),
+        });
+    }
+    if moderation.max_repeated_chars.is_some_and(|max| max < 2) {
+        errors.push(ConfigError::ValidationError {
+            field: format!("{MODERATION_FIELD}.max_repeated_chars"),
+            message: "must be at least 2".to_string(),
+        });
+    }
+    if !(1..=MAX_TIMEOUT_SECS).contains(&moderation.timeout_secs) {
+        errors.push(ConfigError::ValidationError {
+            field: format!("{MODERATION_FIELD}.timeout_secs"),
+            message: format!("must be between 1 and {MAX_TIMEOUT_SECS}"),
+        });
+    }
+}
+
+fn validate_counters(config: &Config, errors: &mut Vec<ConfigError>) {
+    let Some(counters) = &config.counters else {
+        return;
+    };
+
+    for (index, name) in counters.names.iter().enumerate() {
+        let message = if name.trim().is_empty() {
+            "cannot be blank"
+        } else if name.contains(char::is_whitespace) {
+            "cannot contain whitespace"
+        } else if name.ends_with(INCREMENT_SUFFIX) {
+            "cannot end with \"+\", that is added for the increment command"
+        } else {
+            continue;
+        };
+        errors.push(ConfigError::ValidationError {
+            field: format!("{COUNTER_NAMES_FIELD}[{index}]"),
+            message: message.to_string(),
+        });
+    }
+}
+
+fn validate_scheduler(config: &Config, errors: &mut Vec<ConfigError>) {
+    let Some(scheduler) = &config.scheduler else {
+        return;
+    };
+
+    if scheduler.messages.is_empty() {
+        errors.push(ConfigError::ValidationError {
+            field: SCHEDULER_MESSAGES_FIELD.to_string(),
+            message: "is set but lists no messages".to_string(),
+        });
+    }
+
+    for (index, message) in scheduler.messages.iter().enumerate() {
+        let field = format!("{SCHEDULER_MESSAGES_FIELD}[{index}]");
+        if message.interval_mins == 0 {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{field}.interval_mins"),
+                message: "must be at least 1".to_string(),
+            });
+        }
+        if message.message.trim().is_empty() {
+            errors.push(ConfigError::ValidationError {
+                field: format!("{field}.message"),
+                message: "cannot be blank".to_string(),
+            });
+        }
+    }
+}
+
+/// Twitch logins are 1 to 25 ASCII letters, digits or underscores.
+fn check_login(login: &str) -> Result<(), &'static str> {
+    if login.trim().is_empty() {
+        return Err("cannot be blank");
+    }
+    if login.len() > MAX_LOGIN_LEN {
+        return Err("longer than 25 characters");
+    }
+    if !login.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("only letters, digits and underscores are allowed");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(channels: &str) -> Result<Config, ConfigError> {
+        let yaml = format!(
+            r#"
+twitch:
+  auth:
+    broadcaster_id: "1"
+    client_id: "id"
+    client_secret: "secret"
+    access_token: "access"
+    refresh_token: "refresh"
+    writer_id: "2"
+  bot:
+    nick: "bot"
+    broadcaster_id: "1"
+    writer_id: "2"
+{channels}
+"#
+        );
+        parse(&yaml)
+    }
+
+    fn validation_field(result: Result<Config, ConfigError>) -> String {
+        match result {
+            Err(ConfigError::ValidationError { field, .. }) => field,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_channels() {
+        assert!(load("    channels: [\"cool_user\", \"Streamer42\"]").is_ok());
+    }
+
+    #[test]
+    fn test_unset_channels() {
+        let error = load("").unwrap_err();
+        assert_eq!(validation_field(Err(error)), CHANNELS_FIELD);
+    }
+
+    #[test]
+    fn test_reports_every_missing_field_at_once() {
+        let yaml = r#"
+twitch:
+  auth:
+    client_id: "id"
+    access_token: "access"
+    broadcaster_id: "1"
+    refresh_token: "refresh"
+    writer_id: "2"
+  bot:
+    broadcaster_id: "1"
+    writer_id: "2"
+scheduler:
+  messages:
+    - interval_mins: 15
+webhook:
+"#;
+
+        let fields: Vec<_> = match parse(yaml).unwrap_err() {
+            ConfigError::ValidationErrors(errors) => errors
+                .into_iter()
+                .map(|error| validation_field(Err(error)))
+                .collect(),
+            other => panic!("expected several validation errors, got {other:?}"),
+        };
+        assert_eq!(
+            fields,
+            [
+                "twitch.auth.client_secret",
+                "twitch.bot.nick",
+                "twitch.bot.channels",
+                "scheduler.messages[0].message",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_channels() {
+        assert_eq!(validation_field(load("    channels: []")), CHANNELS_FIELD);
+    }
+
+    #[test]
+    fn test_blank_channel() {
+        let field = validation_field(load("    channels: [\"ok\", \"  \"]"));
+        assert_eq!(field, "twitch.bot.channels[1]");
+    }
+
+    #[test]
+    fn test_reports_every_problem_at_once() {
+        let error = load(
+            "    channels: [\"ok\", \"bad;name\", \"  \"]\n    channel_prefixes:\n      \"no way\": \"?\"",
+        )
+        .unwrap_err();
+
+        let fields: Vec<_> = match error {
+            ConfigError::ValidationErrors(errors) => errors
+                .into_iter()
+                .map(|error| validation_field(Err(error)))
+                .collect(),
+            other => panic!("expected several validation errors, got {other:?}"),
+        };
+        assert_eq!(
+            fields,
+            [
+                "twitch.bot.channels[1]",
+                "twitch.bot.channels[2]",
+                "twitch.bot.channel_prefixes.no way",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_auto_response_regex() {
+        let error = load(
+            "    channels: [\"ok\"]\nauto_responder:\n  rules:\n    - pattern: \"(unclosed\"\n      regex: true\n      response: \"hi\"",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("invalid regex"));
+        assert_eq!(
+            validation_field(Err(error)),
+            "auto_responder.rules[0].pattern"
+        );
+    }
+
+    #[test]
+    fn test_moderation_limits() {
+        let error = load(
+            "    channels: [\"ok\"]\nmoderation:\n  link_min_role: vip\n  max_caps_percent: 150\n  timeout_secs: 0",
+        )
+        .unwrap_err();
+
+        let fields: Vec<_> = match error {
+            ConfigError::ValidationErrors(errors) => errors
+                .into_iter()
+                .map(|error| validation_field(Err(error)))
+                .collect(),
+            other => panic!("expected several validation errors, got {other:?}"),
+        };
+        assert_eq!(
+            fields,
+            ["moderation.max_caps_percent", "moderation.timeout_secs"]
+        );
+    }
+
+    #[test]
+    fn test_command_prefixes() {
+        assert!(load("    channels: [\"ok\"]\n    command_prefix: \"?!\"").is_ok());
+        assert_eq!(
+            validation_field(load("    channels: [\"ok\"]\n    command_prefix: \"  \"")),
+            COMMAND_PREFIX_FIELD
+        );
+        assert_eq!(
+            validation_field(load("    channels: [\"ok\"]\n    command_prefix: \"! \"")),
+            COMMAND_PREFIX_FIELD
+        );
+        assert_eq!(
+            validation_field(load(
+                "    channels: [\"ok\"]\n    channel_prefixes:\n      ok: \"h ey\""
+            )),
+            "twitch.bot.channel_prefixes.ok"
+        );
+    }
+
+    #[test]
+    fn test_blank_denial_reply() {
+        assert!(load("    channels: [\"ok\"]\n    denial_reply: \"@{user} no\"").is_ok());
+        assert_eq!(
+            validation_field(load("    channels: [\"ok\"]\n    denial_reply: \" \"")),
+            DENIAL_REPLY_FIELD
+        );
+    }
+
+    #[test]
+    fn test_invalid_counter_names() {
+        let error = load(
+            "    channels: [\"ok\"]\ncounters:\n  names: [\"deaths\", \"wins+\", \"two words\"]",
+        )
+        .unwrap_err();
+
+        let fields: Vec<_> = match error {
+            ConfigError::ValidationErrors(errors) => errors
+                .into_iter()
+                .map(|error| validation_field(Err(error)))
+                .collect(),
+            other => panic!("expected several validation errors, got {other:?}"),
+        };
+        assert_eq!(fields, ["counters.names[1]", "counters.names[2]"]);
+    }
+
+    #[test]
+    fn test_scheduled_message_needs_an_interval() {
+        let error = load(
+            "    channels: [\"ok\"]\nscheduler:\n  messages:\n    - interval_mins: 15\n      message: \"follow!\"\n    - interval_mins: 0\n      message: \"too often\"",
+        )
+        .unwrap_err();
+        assert_eq!(
+            validation_field(Err(error)),
+            "scheduler.messages[1].interval_mins"
+        );
+    }
+
+    #[test]
+    fn test_invalid_channel_characters() {
+        let error = load("    channels: [\"ok\", \"bad;name\"]").unwrap_err();
+        assert!(error.to_string().contains("\"bad;name\""));
+        assert_eq!(validation_field(Err(error)), "twitch.bot.channels[1]");
+    }
 }
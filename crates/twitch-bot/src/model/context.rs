@@ -0,0 +1,34 @@
+use uuid::Uuid;
+
+use crate::model::Extensions;
+
+/// Per-event data that travels with an event through every dispatch stage.
+#[derive(Debug, Clone)]
+pub struct EventContext {
+    /// Generated when the event is created and recorded on its dispatch span,
+    /// so every log line about the event can be found by this id.
+    pub trace_id: Uuid,
+    pub extensions: Extensions,
+}
+
+impl Default for EventContext {
+    fn default() -> Self {
+        Self {
+            trace_id: Uuid::new_v4(),
+            extensions: Extensions::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_id_is_unique_per_event_and_kept_by_clones() {
+        let context = EventContext::default();
+
+        assert_ne!(context.trace_id, EventContext::default().trace_id);
+        assert_eq!(context.trace_id, context.clone().trace_id);
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// What Twitch pays streamers per Bit cheered, in USD.
+pub const DEFAULT_USD_PER_BIT: f64 = 0.01;
+
+/// Currency a donation was made in. Bits are Twitch's own currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Currency {
+    #[serde(rename = "USD")]
+    Usd,
+    #[serde(rename = "EUR")]
+    Euro,
+    #[serde(rename = "RUB")]
+    Rub,
+    #[serde(rename = "BITS")]
+    Bits,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown currency: {0}")]
+pub struct UnknownCurrency(pub String);
+
+impl Currency {
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Usd => "USD",
+            Self::Euro => "EUR",
+            Self::Rub => "RUB",
+            Self::Bits => "BITS",
+        }
+    }
+
+    /// Approximate USD value of `bits` at `usd_per_bit`, see [`DEFAULT_USD_PER_BIT`].
+    pub fn bits_to_usd(bits: u32, usd_per_bit: f64) -> f64 {
+        f64::from(bits) * usd_per_bit
+    }
+}
+
+/// Accepts ISO codes and common symbols, ignoring case and surrounding whitespace.
+impl FromStr for Currency {
+    type Err = UnknownCurrency;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "USD" | "$" | "US$" => Ok(Self::Usd),
+            "EUR" | "€" => Ok(Self::Euro),
+            "RUB" | "₽" | "RUR" => Ok(Self::Rub),
+            "BITS" | "BIT" => Ok(Self::Bits),
+            _ => Err(UnknownCurrency(value.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_iso_codes_case_insensitively() {
+        assert_eq!("USD".parse(), Ok(Currency::Usd));
+        assert_eq!("eur".parse(), Ok(Currency::Euro));
+        assert_eq!(" Rub ".parse(), Ok(Currency::Rub));
+        assert_eq!("bits".parse(), Ok(Currency::Bits));
+    }
+
+    #[test]
+    fn test_parses_symbols() {
+        assert_eq!("$".parse(), Ok(Currency::Usd));
+        assert_eq!("€".parse(), Ok(Currency::Euro));
+        assert_eq!("₽".parse(), Ok(Currency::Rub));
+    }
+
+    #[test]
+    fn test_rejects_unknown_currency() {
+        assert_eq!(
+            "GBP".parse::<Currency>(),
+            Err(UnknownCurrency("GBP".to_string()))
+        );
+        assert!("".parse::<Currency>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for currency in [Currency::Usd, Currency::Euro, Currency::Rub, Currency::Bits] {
+            assert_eq!(currency.to_string().parse(), Ok(currency));
+        }
+    }
+
+    #[test]
+    fn test_bits_to_usd() {
+        assert_eq!(Currency::bits_to_usd(500, DEFAULT_USD_PER_BIT), 5.0);
+        assert_eq!(Currency::bits_to_usd(250, 0.02), 5.0);
+    }
+}
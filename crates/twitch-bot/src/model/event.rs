@@ -1,16 +1,22 @@
 use std::time::SystemTime;
 
-use crate::model::{ChatTarget, Role, User};
+use serde::Serialize;
 
-#[derive(Debug, Clone)]
+use crate::model::{ChatTarget, Currency, EventContext, Role, User};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ChatMessage {
     pub author: User,
     pub target: ChatTarget,
     pub text: String,
+    /// The platform's id for the message, when it has one; needed to delete it.
+    pub message_id: Option<String>,
     pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RewardRedemption {
     pub user: User,
     pub reward_id: String,
@@ -18,18 +24,99 @@ pub struct RewardRedemption {
     pub cost: u32,
     pub user_input: Option<String>,
     pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
+}
+
+/// A donation from a source outside Twitch chat, e.g. a StreamElements or
+/// Streamlabs feed implemented as an [`EventSource`](crate::app::ports::EventSource).
+#[derive(Debug, Clone, Serialize)]
+pub struct Donation {
+    pub donor: User,
+    pub amount: f64,
+    pub currency: Currency,
+    pub message: Option<String>,
+    pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
+}
+
+impl Donation {
+    /// Builds a donation received now, with an empty context.
+    pub fn new(donor: User, amount: f64, currency: Currency, message: Option<String>) -> Self {
+        Self {
+            donor,
+            amount,
+            currency,
+            message,
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        }
+    }
+}
+
+/// A private message sent straight to the bot account.
+#[derive(Debug, Clone, Serialize)]
+pub struct Whisper {
+    pub author: User,
+    pub text: String,
+    pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
+}
+
+impl Whisper {
+    pub fn new(author: User, text: impl Into<String>) -> Self {
+        Self {
+            author,
+            text: text.into(),
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        }
+    }
+}
+
+/// The broadcaster's stream went live or offline.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStatus {
+    pub live: bool,
+    pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
 }
 
-#[derive(Debug, Clone)]
+impl StreamStatus {
+    pub fn new(live: bool) -> Self {
+        Self {
+            live,
+            received_at: SystemTime::now(),
+            context: EventContext::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct SystemEvent {
     pub message: String,
     pub received_at: SystemTime,
+    #[serde(skip)]
+    pub context: EventContext,
+}
+
+impl SystemEvent {
+    /// Message of the event a source emits when it has (re)connected, for
+    /// handlers that react to coming back online.
+    pub const CONNECTED: &'static str = "connected";
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     ChatMessage(ChatMessage),
     RewardRedemption(RewardRedemption),
+    Donation(Donation),
+    Whisper(Whisper),
+    StreamStatus(StreamStatus),
     System(SystemEvent),
 }
 
@@ -38,20 +125,49 @@ impl Event {
         match self {
             Event::ChatMessage(message) => Some(&message.author),
             Event::RewardRedemption(redemption) => Some(&redemption.user),
-            Event::System(_) => None,
+            Event::Donation(donation) => Some(&donation.donor),
+            Event::Whisper(whisper) => Some(&whisper.author),
+            Event::StreamStatus(_) | Event::System(_) => None,
         }
     }
 
     pub fn chat_target(&self) -> Option<&ChatTarget> {
         match self {
             Event::ChatMessage(message) => Some(&message.target),
-            Event::RewardRedemption(_) | Event::System(_) => None,
+            Event::RewardRedemption(_)
+            | Event::Donation(_)
+            | Event::Whisper(_)
+            | Event::StreamStatus(_)
+            | Event::System(_) => None,
+        }
+    }
+
+    pub fn context(&self) -> &EventContext {
+        match self {
+            Event::ChatMessage(message) => &message.context,
+            Event::RewardRedemption(redemption) => &redemption.context,
+            Event::Donation(donation) => &donation.context,
+            Event::Whisper(whisper) => &whisper.context,
+            Event::StreamStatus(status) => &status.context,
+            Event::System(event) => &event.context,
+        }
+    }
+
+    pub fn context_mut(&mut self) -> &mut EventContext {
+        match self {
+            Event::ChatMessage(message) => &mut message.context,
+            Event::RewardRedemption(redemption) => &mut redemption.context,
+            Event::Donation(donation) => &mut donation.context,
+            Event::Whisper(whisper) => &mut whisper.context,
+            Event::StreamStatus(status) => &mut status.context,
+            Event::System(event) => &mut event.context,
         }
     }
 
+    /// Whether the event's user ranks at least `required`, see [`Role::at_least`].
     pub fn has_role(&self, required: Role) -> bool {
         self.user()
-            .map(|user| user.role.contains(required))
+            .map(|user| user.role.at_least(required))
             .unwrap_or(false)
     }
 }
@@ -0,0 +1,80 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+type AnyMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// Type-keyed storage that lets interceptors hand data to later stages.
+///
+/// Values are stored behind `Arc`, so cloning an event stays cheap, and the
+/// map itself is only allocated on first insert.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: Option<Box<AnyMap>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, replacing any previous value of the same type.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.map
+            .get_or_insert_with(Box::default)
+            .insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .as_ref()?
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.as_ref().map_or(0, |map| map.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_insert_get_and_replace() {
+        let mut extensions = Extensions::new();
+        assert!(extensions.is_empty());
+        assert_eq!(extensions.get::<Score>(), None);
+
+        extensions.insert(Score(1));
+        extensions.insert(Score(2));
+        extensions.insert("label");
+
+        assert_eq!(extensions.get::<Score>(), Some(&Score(2)));
+        assert_eq!(extensions.get::<&str>(), Some(&"label"));
+        assert_eq!(extensions.len(), 2);
+    }
+}
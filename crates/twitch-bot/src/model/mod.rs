@@ -1,14 +1,22 @@
 #![allow(dead_code)]
 
 pub mod chat_target;
+pub mod context;
+pub mod currency;
 pub mod event;
+pub mod extensions;
 pub mod ids;
 pub mod role;
 pub mod track;
 pub mod user;
 
 pub use chat_target::ChatTarget;
-pub use event::{ChatMessage, Event, RewardRedemption, SystemEvent};
+pub use context::EventContext;
+pub use currency::Currency;
+pub use event::{
+    ChatMessage, Donation, Event, RewardRedemption, StreamStatus, SystemEvent, Whisper,
+};
+pub use extensions::Extensions;
 pub use role::Role;
 pub use track::TrackInfo;
 pub use user::{Platform, User};
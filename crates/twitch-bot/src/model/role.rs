@@ -1,3 +1,5 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[non_exhaustive]
 pub struct Role(u8);
@@ -33,6 +35,22 @@ impl Role {
         (self.0 & other.0) == other.0
     }
 
+    /// Whether this role ranks at or above `required` in the hierarchy
+    /// broadcaster > moderator > VIP > subscriber > pleb.
+    ///
+    /// Only the highest role held counts, so a broadcaster is at least a
+    /// moderator even if the moderator bit was never set.
+    pub fn at_least(&self, required: Role) -> bool {
+        self.rank() >= required.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self.0 {
+            0 => 0,
+            bits => u8::BITS as u8 - bits.leading_zeros() as u8,
+        }
+    }
+
     pub fn is_broadcaster(&self) -> bool {
         self.contains(Self::BROADCASTER)
     }
@@ -49,3 +67,72 @@ impl Role {
         self.contains(Self::SUBSCRIBER)
     }
 }
+
+/// Serializes as the name of the highest role held, e.g. `"moderator"`.
+impl Serialize for Role {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = if self.is_broadcaster() {
+            "broadcaster"
+        } else if self.is_moderator() {
+            "moderator"
+        } else if self.is_vip() {
+            "vip"
+        } else if self.is_subscriber() {
+            "subscriber"
+        } else {
+            "pleb"
+        };
+        serializer.serialize_str(name)
+    }
+}
+
+/// Reads the same names [`Serialize`] writes, e.g. `"vip"` in config.
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.to_ascii_lowercase().as_str() {
+            "broadcaster" => Ok(Self::BROADCASTER),
+            "moderator" => Ok(Self::MODERATOR),
+            "vip" => Ok(Self::VIP),
+            "subscriber" => Ok(Self::SUBSCRIBER),
+            "pleb" => Ok(Self::PLEB),
+            _ => Err(de::Error::unknown_variant(
+                &name,
+                &["broadcaster", "moderator", "vip", "subscriber", "pleb"],
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at_least_follows_hierarchy() {
+        assert!(Role::BROADCASTER.at_least(Role::MODERATOR));
+        assert!(Role::MODERATOR.at_least(Role::MODERATOR));
+        assert!(Role::VIP.at_least(Role::SUBSCRIBER));
+        assert!(Role::PLEB.at_least(Role::PLEB));
+        assert!(!Role::SUBSCRIBER.at_least(Role::MODERATOR));
+        assert!(!Role::PLEB.at_least(Role::SUBSCRIBER));
+    }
+
+    #[test]
+    fn test_deserialize_role_names() {
+        let roles: Vec<Role> = serde_yaml::from_str("[pleb, Subscriber, vip, moderator]").unwrap();
+        assert_eq!(
+            roles,
+            [Role::PLEB, Role::SUBSCRIBER, Role::VIP, Role::MODERATOR]
+        );
+        assert!(serde_yaml::from_str::<Role>("admin").is_err());
+    }
+
+    #[test]
+    fn test_at_least_uses_highest_bit_only() {
+        let broadcaster_only = Role(Role::BIT_BROADCASTER);
+        assert!(!broadcaster_only.contains(Role::MODERATOR));
+        assert!(broadcaster_only.at_least(Role::MODERATOR));
+        assert!(!Role(Role::BIT_VIP).at_least(Role::MODERATOR));
+    }
+}
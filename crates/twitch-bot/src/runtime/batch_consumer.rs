@@ -0,0 +1,154 @@
+use std::{num::NonZeroUsize, panic::AssertUnwindSafe, sync::Arc};
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use tokio::{sync::mpsc, task::JoinSet, time::timeout};
+use tracing::{Instrument, error};
+
+use crate::{app::dispatch::Handler, model::Event};
+
+use super::consumer::{
+    BUFFER_SIZE, EventConsumer, HANDLER_TIMEOUT, TaskOrigin, handle_error, panic_message,
+};
+
+/// Hands events to the handler in batches instead of one task per event.
+///
+/// Each wake-up drains whatever is already queued, up to `max_batch` events,
+/// and runs them one after another in a single task. Nothing waits for a batch
+/// to fill, so an idle channel adds no latency; the cost is that an event
+/// queued behind a slow handler in the same batch starts only once it finishes.
+#[non_exhaustive]
+pub struct BatchConsumer<H: Handler<Event>> {
+    handler: Arc<H>,
+    max_batch: NonZeroUsize,
+}
+
+impl<H: Handler<Event>> BatchConsumer<H> {
+    pub fn new(handler: H, max_batch: NonZeroUsize) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            max_batch,
+        }
+    }
+}
+
+#[async_trait]
+impl<H: Handler<Event>> EventConsumer for BatchConsumer<H> {
+    async fn consume(&self, mut ch: mpsc::Receiver<Event>) {
+        let max_batch = self.max_batch.get();
+        let mut tasks = JoinSet::new();
+        let mut batch = Vec::with_capacity(max_batch);
+
+        while ch.recv_many(&mut batch, max_batch).await > 0 {
+            while let Some(result) = tasks.try_join_next() {
+                log_batch_result(result);
+            }
+            if tasks.len() >= BUFFER_SIZE
+                && let Some(result) = tasks.join_next().await
+            {
+                log_batch_result(result);
+            }
+
+            let events = std::mem::replace(&mut batch, Vec::with_capacity(max_batch));
+            let handler = self.handler.clone();
+            tasks.spawn(async move {
+                for event in events {
+                    handle_event(handler.as_ref(), event).await;
+                }
+            });
+        }
+
+        // the channel is closed, so wait for in-flight batches before returning
+        while let Some(result) = tasks.join_next().await {
+            log_batch_result(result);
+        }
+    }
+}
+
+/// Runs one event of a batch; a panic is logged and the rest of the batch still runs.
+async fn handle_event<H: Handler<Event>>(handler: &H, event: Event) {
+    let origin = TaskOrigin::new(&event);
+    let span = origin.span();
+    let handled = AssertUnwindSafe(timeout(HANDLER_TIMEOUT, handler.handle(event)))
+        .catch_unwind()
+        .instrument(span.clone())
+        .await;
+
+    span.in_scope(|| match handled {
+        Ok(Ok(Ok(()))) => {}
+        Ok(Ok(Err(err))) => handle_error(err),
+        Ok(Err(_)) => error!("handler timeout"),
+        Err(payload) => error!(
+            user = ?origin.user,
+            panic = panic_message(payload.as_ref()),
+            "handler panicked"
+        ),
+    });
+}
+
+fn log_batch_result(result: Result<(), tokio::task::JoinError>) {
+    if let Err(err) = result {
+        error!("batch task failed: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::app::test_support::{Counter, system_event};
+
+    struct RecordingHandler(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Handler<Event> for RecordingHandler {
+        async fn handle(&self, event: Event) -> anyhow::Result<()> {
+            if let Event::System(system) = event {
+                if system.message == "panic" {
+                    panic!("handler bug");
+                }
+                self.0.lock().unwrap().push(system.message);
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_runs_in_order_and_survives_panics() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let consumer = BatchConsumer::new(
+            RecordingHandler(seen.clone()),
+            NonZeroUsize::new(10).unwrap(),
+        );
+
+        let (tx, rx) = mpsc::channel(8);
+        for message in ["a", "panic", "b", "c"] {
+            tx.send(system_event(message)).await.unwrap();
+        }
+        drop(tx);
+
+        consumer.consume(rx).await;
+
+        assert_eq!(*seen.lock().unwrap(), ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_splits_queue_into_max_batch_chunks() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let consumer = BatchConsumer::new(Counter(handled.clone()), NonZeroUsize::new(3).unwrap());
+
+        let (tx, rx) = mpsc::channel(100);
+        for _ in 0..10 {
+            tx.send(system_event("event")).await.unwrap();
+        }
+        drop(tx);
+
+        consumer.consume(rx).await;
+
+        assert_eq!(handled.load(Ordering::SeqCst), 10);
+    }
+}
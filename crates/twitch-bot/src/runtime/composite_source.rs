@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::{app::ports::EventSource, model::Event};
+
+use super::Shutdowner;
+
+const BUFFER_SIZE: usize = 100;
+
+/// An [`EventSource`] the supervisor can also stop.
+pub trait ManagedSource: EventSource + Shutdowner {}
+
+impl<T: EventSource + Shutdowner> ManagedSource for T {}
+
+/// Merges several sources, e.g. EventSub and a donation feed, into one stream.
+///
+/// Events from one source keep their order; there is no ordering between
+/// sources. The merged channel closes once every source has closed.
+#[non_exhaustive]
+pub struct CompositeSource {
+    sources: Vec<Box<dyn ManagedSource>>,
+}
+
+impl CompositeSource {
+    pub fn new(sources: Vec<Box<dyn ManagedSource>>) -> Self {
+        Self { sources }
+    }
+}
+
+#[async_trait]
+impl EventSource for CompositeSource {
+    async fn fetch(&self) -> anyhow::Result<mpsc::Receiver<Event>> {
+        let (tx, rx) = mpsc::channel(BUFFER_SIZE);
+
+        for source in &self.sources {
+            let mut source_rx = source.fetch().await?;
+            let tx = tx.clone();
+
+            tokio::spawn(async move {
+                while let Some(event) = source_rx.recv().await {
+                    if tx.send(event).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Ok(rx)
+    }
+}
+
+#[async_trait]
+impl Shutdowner for CompositeSource {
+    /// Shuts every source down, even if an earlier one fails.
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+
+        for source in &self.sources {
+            if let Err(err) = source.shutdown().await {
+                error!("failed to shut down event source: {}", err);
+                result = result.and(Err(err));
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::{
+        adapters::fake::FakeSource,
+        app::test_support::{ProbeSource, system_event},
+    };
+
+    #[tokio::test]
+    async fn test_merges_all_sources_until_they_close() {
+        let first = FakeSource::new(vec![system_event("a1"), system_event("a2")]);
+        let second = FakeSource::new(vec![system_event("b1")]);
+        let composite = CompositeSource::new(vec![Box::new(first), Box::new(second)]);
+
+        let mut rx = composite.fetch().await.unwrap();
+        let mut messages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                Event::System(event) => messages.push(event.message),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+
+        messages.sort();
+        assert_eq!(messages, ["a1", "a2", "b1"]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reaches_every_source() {
+        let first = ProbeSource::new(Vec::new()).failing_shutdown();
+        let second = ProbeSource::new(Vec::new());
+        let (first_down, second_down) = (first.shut_down(), second.shut_down());
+        let composite = CompositeSource::new(vec![Box::new(first), Box::new(second)]);
+
+        assert!(composite.shutdown().await.is_err());
+        assert!(first_down.load(Ordering::SeqCst));
+        assert!(second_down.load(Ordering::SeqCst));
+    }
+}
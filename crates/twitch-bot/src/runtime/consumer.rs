@@ -1,23 +1,35 @@
-use std::{sync::Arc, time::Duration};
+use std::{any::Any, collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::Error;
 use async_trait::async_trait;
 use tokio::{
-    sync::{Semaphore, mpsc},
+    sync::mpsc,
+    task::{self, JoinError, JoinSet},
     time::timeout,
 };
-use tracing::error;
+use tracing::{Instrument, Span, debug_span, error};
+use uuid::Uuid;
 
-use crate::{app::dispatch::Handler, model::Event};
+use crate::{
+    app::dispatch::{Handler, Route},
+    model::Event,
+};
 
-const BUFFER_SIZE: usize = 30;
-const HANDLER_TIMEOUT: Duration = Duration::from_secs(1);
+pub(super) const BUFFER_SIZE: usize = 30;
+pub(super) const HANDLER_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[async_trait]
 pub trait EventConsumer: Send + Sync + 'static {
     async fn consume(&self, ch: mpsc::Receiver<Event>);
 }
 
+#[async_trait]
+impl EventConsumer for Box<dyn EventConsumer> {
+    async fn consume(&self, ch: mpsc::Receiver<Event>) {
+        self.as_ref().consume(ch).await
+    }
+}
+
 #[non_exhaustive]
 pub struct Consumer<H: Handler<Event>> {
     handler: Arc<H>,
@@ -34,34 +46,220 @@ impl<H: Handler<Event>> Consumer<H> {
 #[async_trait]
 impl<H: Handler<Event>> EventConsumer for Consumer<H> {
     async fn consume(&self, mut ch: mpsc::Receiver<Event>) {
-        let sem = Arc::new(Semaphore::new(BUFFER_SIZE));
+        let mut tasks = JoinSet::new();
+        let mut origins = HashMap::new();
 
         while let Some(event) = ch.recv().await {
-            let permit = match sem.clone().acquire_owned().await {
-                Ok(p) => p,
-                Err(_) => break,
-            };
+            while let Some(result) = tasks.try_join_next_with_id() {
+                log_task_result(&mut origins, result);
+            }
+            if tasks.len() >= BUFFER_SIZE
+                && let Some(result) = tasks.join_next_with_id().await
+            {
+                log_task_result(&mut origins, result);
+            }
 
+            let origin = TaskOrigin::new(&event);
+            let span = origin.span();
             let handler = self.handler.clone();
 
-            tokio::spawn(async move {
-                let _permit = permit;
-
-                match timeout(HANDLER_TIMEOUT, handler.handle(event)).await {
-                    Ok(res) => {
-                        if let Err(err) = res {
-                            handle_error(err);
+            let handle = tasks.spawn(
+                async move {
+                    match timeout(HANDLER_TIMEOUT, handler.handle(event)).await {
+                        Ok(res) => {
+                            if let Err(err) = res {
+                                handle_error(err);
+                            }
                         }
+                        Err(_) => error!("handler timeout"),
                     }
-                    Err(_) => error!("handler timeout"),
                 }
-            });
+                .instrument(span),
+            );
+            origins.insert(handle.id(), origin);
         }
 
-        let _ = sem.acquire_many(BUFFER_SIZE as u32).await;
+        // the channel is closed, so wait for in-flight handlers before returning
+        while let Some(result) = tasks.join_next_with_id().await {
+            log_task_result(&mut origins, result);
+        }
     }
 }
 
-fn handle_error(err: Error) {
+pub(super) fn handle_error(err: Error) {
     error!("something wrong: {}", err);
 }
+
+/// What a handler task was processing, kept so a panic can be traced back to it.
+#[derive(Debug)]
+pub(super) struct TaskOrigin {
+    pub(super) trace_id: Uuid,
+    pub(super) route: Route,
+    pub(super) user: Option<String>,
+}
+
+impl TaskOrigin {
+    pub(super) fn new(event: &Event) -> Self {
+        Self {
+            trace_id: event.context().trace_id,
+            route: Route::from(event),
+            user: event.user().map(|user| user.display_name.clone()),
+        }
+    }
+
+    /// The span the event's handler runs in; interceptor spans nest under it.
+    pub(super) fn span(&self) -> Span {
+        debug_span!("event", trace_id = %self.trace_id, route = ?self.route)
+    }
+}
+
+fn log_task_result(
+    origins: &mut HashMap<task::Id, TaskOrigin>,
+    result: Result<(task::Id, ()), JoinError>,
+) {
+    let err = match result {
+        Ok((id, ())) => {
+            origins.remove(&id);
+            return;
+        }
+        Err(err) => err,
+    };
+
+    let origin = origins.remove(&err.id());
+    let trace_id = origin.as_ref().map(|origin| origin.trace_id);
+    let route = origin.as_ref().map(|origin| origin.route);
+    let user = origin.and_then(|origin| origin.user);
+
+    match err.try_into_panic() {
+        Ok(payload) => error!(
+            ?trace_id,
+            ?route,
+            ?user,
+            panic = panic_message(payload.as_ref()),
+            "handler panicked"
+        ),
+        Err(_) => error!(?trace_id, ?route, ?user, "handler task cancelled"),
+    }
+}
+
+pub(super) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "non-string panic payload"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::app::test_support::system_event;
+
+    struct SlowHandler(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Handler<Event> for SlowHandler {
+        async fn handle(&self, _event: Event) -> anyhow::Result<()> {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consume_waits_for_in_flight_handlers() {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let consumer = Consumer::new(SlowHandler(finished.clone()));
+
+        let (tx, rx) = mpsc::channel(8);
+        for _ in 0..3 {
+            tx.send(system_event("system")).await.unwrap();
+        }
+        drop(tx);
+
+        consumer.consume(rx).await;
+
+        assert_eq!(finished.load(Ordering::SeqCst), 3);
+    }
+
+    struct ConcurrencyHandler {
+        running: AtomicUsize,
+        peak: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Handler<Event> for ConcurrencyHandler {
+        async fn handle(&self, _event: Event) -> anyhow::Result<()> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_is_bounded() {
+        let peak = Arc::new(AtomicUsize::new(0));
+        let consumer = Consumer::new(ConcurrencyHandler {
+            running: AtomicUsize::new(0),
+            peak: peak.clone(),
+        });
+
+        let (tx, rx) = mpsc::channel(100);
+        for _ in 0..BUFFER_SIZE * 2 {
+            tx.send(system_event("system")).await.unwrap();
+        }
+        drop(tx);
+
+        consumer.consume(rx).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), BUFFER_SIZE);
+    }
+
+    struct PanickingHandler(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Handler<Event> for PanickingHandler {
+        async fn handle(&self, event: Event) -> anyhow::Result<()> {
+            if let Event::System(system) = &event
+                && system.message == "panic"
+            {
+                panic!("handler bug");
+            }
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_panic_does_not_stop_consumer() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let consumer = Consumer::new(PanickingHandler(handled.clone()));
+
+        let (tx, rx) = mpsc::channel(8);
+        tx.send(system_event("panic")).await.unwrap();
+        tx.send(system_event("system")).await.unwrap();
+        drop(tx);
+
+        consumer.consume(rx).await;
+
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload: Box<dyn Any + Send> = Box::new("static");
+        assert_eq!(panic_message(payload.as_ref()), "static");
+
+        let payload: Box<dyn Any + Send> = Box::new(format!("formatted {}", 1));
+        assert_eq!(panic_message(payload.as_ref()), "formatted 1");
+
+        let payload: Box<dyn Any + Send> = Box::new(1);
+        assert_eq!(panic_message(payload.as_ref()), "non-string panic payload");
+    }
+}
@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use tokio::sync::mpsc;
+
+use crate::model::Event;
+
+use super::EventConsumer;
+
+const BUFFER_SIZE: usize = 30;
+
+/// Hands a copy of every event to each consumer, e.g. the command pipeline and
+/// an analytics logger.
+///
+/// Each consumer reads from its own bounded channel, so nothing is dropped: a
+/// consumer that falls `30` events behind holds up the others until it catches
+/// up. A `tokio::sync::broadcast` channel would instead drop the oldest events
+/// for the lagging consumer only. A consumer that returns early stops
+/// receiving copies without affecting the rest.
+#[non_exhaustive]
+pub struct FanOutConsumer {
+    consumers: Vec<Box<dyn EventConsumer>>,
+}
+
+impl FanOutConsumer {
+    pub fn new(consumers: Vec<Box<dyn EventConsumer>>) -> Self {
+        Self { consumers }
+    }
+
+    /// Fans out only when there is more than one consumer; a single one gets
+    /// the source channel directly, without the extra copy and channel hop.
+    pub fn combine(mut consumers: Vec<Box<dyn EventConsumer>>) -> Box<dyn EventConsumer> {
+        match consumers.len() {
+            1 => consumers.remove(0),
+            _ => Box::new(Self::new(consumers)),
+        }
+    }
+}
+
+#[async_trait]
+impl EventConsumer for FanOutConsumer {
+    async fn consume(&self, mut ch: mpsc::Receiver<Event>) {
+        let (senders, receivers): (Vec<_>, Vec<_>) = self
+            .consumers
+            .iter()
+            .map(|_| mpsc::channel(BUFFER_SIZE))
+            .unzip();
+
+        let forward = async move {
+            while let Some(event) = ch.recv().await {
+                for tx in &senders {
+                    let _ = tx.send(event.clone()).await;
+                }
+            }
+        };
+        let consume = join_all(
+            self.consumers
+                .iter()
+                .zip(receivers)
+                .map(|(consumer, rx)| consumer.consume(rx)),
+        );
+
+        tokio::join!(forward, consume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::app::test_support::system_event;
+
+    struct CountingConsumer {
+        limit: Option<usize>,
+        seen: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl EventConsumer for CountingConsumer {
+        async fn consume(&self, mut ch: mpsc::Receiver<Event>) {
+            while ch.recv().await.is_some() {
+                let seen = self.seen.fetch_add(1, Ordering::SeqCst) + 1;
+                if self.limit == Some(seen) {
+                    return;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_consumer_sees_every_event() {
+        let first = Arc::new(AtomicUsize::new(0));
+        let second = Arc::new(AtomicUsize::new(0));
+        let early = Arc::new(AtomicUsize::new(0));
+        let fan_out = FanOutConsumer::new(vec![
+            Box::new(CountingConsumer {
+                limit: None,
+                seen: first.clone(),
+            }),
+            Box::new(CountingConsumer {
+                limit: Some(1),
+                seen: early.clone(),
+            }),
+            Box::new(CountingConsumer {
+                limit: None,
+                seen: second.clone(),
+            }),
+        ]);
+
+        let (tx, rx) = mpsc::channel(8);
+        for _ in 0..5 {
+            tx.send(system_event("system")).await.unwrap();
+        }
+        drop(tx);
+
+        fan_out.consume(rx).await;
+
+        assert_eq!(first.load(Ordering::SeqCst), 5);
+        assert_eq!(second.load(Ordering::SeqCst), 5);
+        assert_eq!(early.load(Ordering::SeqCst), 1);
+    }
+
+    /// Records the capacity of the channel it was handed, which tells the
+    /// source channel apart from a fan-out copy.
+    struct CapacityConsumer(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl EventConsumer for CapacityConsumer {
+        async fn consume(&self, mut ch: mpsc::Receiver<Event>) {
+            self.0.store(ch.max_capacity(), Ordering::SeqCst);
+            while ch.recv().await.is_some() {}
+        }
+    }
+
+    async fn run_combined(capacities: &[Arc<AtomicUsize>]) {
+        let consumers = capacities
+            .iter()
+            .map(|capacity| Box::new(CapacityConsumer(capacity.clone())) as Box<dyn EventConsumer>)
+            .collect();
+        let combined = FanOutConsumer::combine(consumers);
+
+        let (tx, rx) = mpsc::channel(7);
+        tx.send(system_event("system")).await.unwrap();
+        drop(tx);
+
+        combined.consume(rx).await;
+    }
+
+    #[tokio::test]
+    async fn test_combine_hands_a_single_consumer_the_source_channel() {
+        let capacity = Arc::new(AtomicUsize::new(0));
+
+        run_combined(std::slice::from_ref(&capacity)).await;
+
+        assert_eq!(capacity.load(Ordering::SeqCst), 7);
+    }
+
+    #[tokio::test]
+    async fn test_combine_fans_out_to_several_consumers() {
+        let capacities = [Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0))];
+
+        run_combined(&capacities).await;
+
+        for capacity in &capacities {
+            assert_eq!(capacity.load(Ordering::SeqCst), BUFFER_SIZE);
+        }
+    }
+}
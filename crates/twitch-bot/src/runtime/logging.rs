@@ -1,24 +1,53 @@
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, fmt, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+use crate::config::model::{LogFormat, LoggingConfig};
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
 
 #[must_use = "LogGuard must be held to keep logging active"]
 #[non_exhaustive]
 pub struct LogGuard {
-    _guard: tracing_appender::non_blocking::WorkerGuard,
+    _guards: Vec<WorkerGuard>,
 }
 
 impl LogGuard {
-    pub fn init() -> Self {
-        let (non_blocking_writer, guard) = tracing_appender::non_blocking(std::io::stdout());
+    /// Logs to stdout and/or daily-rotated files, in the configured format.
+    pub fn init(config: &LoggingConfig) -> Self {
+        let mut layers = Vec::new();
+        let mut guards = Vec::new();
+
+        if config.stdout {
+            let (writer, guard) = tracing_appender::non_blocking(std::io::stdout());
+            layers.push(layer(config.format, writer));
+            guards.push(guard);
+        }
+        if let Some(file) = &config.file {
+            let appender =
+                tracing_appender::rolling::daily(file.dir.as_str(), file.prefix.as_str());
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            layers.push(layer(config.format, writer));
+            guards.push(guard);
+        }
 
         let filter = EnvFilter::try_from_default_env()
             .unwrap_or_else(|_| "twitch_bot=debug,twitch_api=info".into());
 
-        tracing_subscriber::registry()
+        // a global subscriber may already be installed (e.g. by a test harness); keep it
+        let _ = tracing_subscriber::registry()
+            .with(layers)
             .with(filter)
-            .with(fmt::layer().with_writer(non_blocking_writer))
-            .try_init()
-            .expect("failed to init tracing");
+            .try_init();
+
+        Self { _guards: guards }
+    }
+}
 
-        Self { _guard: guard }
+fn layer(format: LogFormat, writer: NonBlocking) -> BoxedLayer {
+    match format {
+        LogFormat::Text => fmt::layer().with_writer(writer).boxed(),
+        LogFormat::Json => fmt::layer().json().with_writer(writer).boxed(),
     }
 }
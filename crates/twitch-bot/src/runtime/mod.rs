@@ -1,11 +1,19 @@
+mod batch_consumer;
+mod composite_source;
 mod consumer;
+mod fan_out;
 mod logging;
 mod shutdown;
 mod signal;
+mod stop_condition;
 mod supervisor;
 
+pub use batch_consumer::BatchConsumer;
+pub use composite_source::{CompositeSource, ManagedSource};
 pub use consumer::{Consumer, EventConsumer};
+pub use fan_out::FanOutConsumer;
 pub use logging::LogGuard;
 pub use shutdown::Shutdowner;
-pub use signal::{SignalHandler, UnixSignalHandler};
+pub use signal::{DefaultSignalHandler, SignalEvent, SignalHandler};
+pub use stop_condition::StopCondition;
 pub use supervisor::Supervisor;
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use async_trait::async_trait;
+#[cfg(unix)]
 use tokio::signal::unix::{SignalKind, signal};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,25 +21,49 @@ impl Display for ShutdownKind {
     }
 }
 
+/// A signal the supervisor reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalEvent {
+    Shutdown(ShutdownKind),
+    /// Re-read the reloadable parts of the config (`SIGUSR1` on Unix).
+    Reload,
+}
+
 #[async_trait]
 pub trait SignalHandler: Send + Sync {
     async fn wait_for_shutdown(&self) -> ShutdownKind;
+
+    /// Waits for the next shutdown or reload signal. Handlers that know no
+    /// reload signal only ever report shutdowns.
+    async fn next_signal(&self) -> SignalEvent {
+        SignalEvent::Shutdown(self.wait_for_shutdown().await)
+    }
 }
 
+/// The signal handler for the target platform.
+#[cfg(unix)]
+pub type DefaultSignalHandler = UnixSignalHandler;
+#[cfg(not(unix))]
+pub type DefaultSignalHandler = CtrlCSignalHandler;
+
+#[cfg(unix)]
 pub struct UnixSignalHandler;
 
+#[cfg(unix)]
 impl Default for UnixSignalHandler {
     fn default() -> Self {
         Self
     }
 }
 
+#[cfg(unix)]
 impl UnixSignalHandler {
     pub fn new() -> Self {
         Self
     }
 }
 
+#[cfg(unix)]
 #[async_trait]
 impl SignalHandler for UnixSignalHandler {
     async fn wait_for_shutdown(&self) -> ShutdownKind {
@@ -52,4 +77,40 @@ impl SignalHandler for UnixSignalHandler {
             _ = sighup.recv() => ShutdownKind::Hangup,
         }
     }
+
+    async fn next_signal(&self) -> SignalEvent {
+        let mut sigusr1 = signal(SignalKind::user_defined1()).expect("SIGUSR1 handler");
+
+        tokio::select! {
+            kind = self.wait_for_shutdown() => SignalEvent::Shutdown(kind),
+            _ = sigusr1.recv() => SignalEvent::Reload,
+        }
+    }
+}
+
+/// Waits for Ctrl+C only, for targets without Unix signals such as Windows.
+#[cfg(not(unix))]
+pub struct CtrlCSignalHandler;
+
+#[cfg(not(unix))]
+impl Default for CtrlCSignalHandler {
+    fn default() -> Self {
+        Self
+    }
+}
+
+#[cfg(not(unix))]
+impl CtrlCSignalHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(not(unix))]
+#[async_trait]
+impl SignalHandler for CtrlCSignalHandler {
+    async fn wait_for_shutdown(&self) -> ShutdownKind {
+        tokio::signal::ctrl_c().await.expect("Ctrl+C handler");
+        ShutdownKind::Interrupt
+    }
 }
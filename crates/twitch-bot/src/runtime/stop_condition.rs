@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use futures::future::{BoxFuture, FutureExt};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::model::Event;
+
+/// Condition that ends a [`Supervisor::run_until`](super::Supervisor::run_until) run
+/// through the regular graceful shutdown path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopCondition {
+    /// Stop once this many events have been handed to the consumer.
+    EventCount(usize),
+    /// Stop after the given wall-clock duration.
+    Duration(Duration),
+}
+
+impl StopCondition {
+    /// Parses `--once <events>` or `--duration <seconds>` from command-line
+    /// arguments; anything after that one pair is rejected.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let Some(flag) = args.next() else {
+            return Ok(None);
+        };
+        let value = args
+            .next()
+            .with_context(|| format!("{flag} requires a value"))?;
+
+        let condition = match flag.as_str() {
+            "--once" => Self::EventCount(
+                value
+                    .parse()
+                    .with_context(|| format!("invalid event count: {value}"))?,
+            ),
+            "--duration" => Self::Duration(Duration::from_secs(
+                value
+                    .parse()
+                    .with_context(|| format!("invalid duration in seconds: {value}"))?,
+            )),
+            other => bail!("unknown argument: {other}"),
+        };
+        if let Some(extra) = args.next() {
+            bail!("unexpected argument: {extra}");
+        }
+
+        Ok(Some(condition))
+    }
+
+    /// Wraps the source channel so the condition can observe it.
+    ///
+    /// Returns the channel the consumer should read from and a future that
+    /// resolves once the condition is met.
+    pub(crate) fn watch(
+        self,
+        events: mpsc::Receiver<Event>,
+    ) -> (mpsc::Receiver<Event>, BoxFuture<'static, ()>) {
+        match self {
+            StopCondition::EventCount(limit) => limit_events(events, limit),
+            StopCondition::Duration(duration) => (events, tokio::time::sleep(duration).boxed()),
+        }
+    }
+}
+
+fn limit_events(
+    mut events: mpsc::Receiver<Event>,
+    limit: usize,
+) -> (mpsc::Receiver<Event>, BoxFuture<'static, ()>) {
+    let (tx, rx) = mpsc::channel(events.max_capacity());
+    let (done_tx, done_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let mut forwarded = 0;
+
+        while forwarded < limit {
+            let Some(event) = events.recv().await else {
+                break;
+            };
+            if tx.send(event).await.is_err() {
+                break;
+            }
+            forwarded += 1;
+        }
+
+        if forwarded == limit {
+            let _ = done_tx.send(());
+        }
+    });
+
+    let reached = async move {
+        if done_rx.await.is_err() {
+            // the source ended before the limit was reached; only a signal can stop us now
+            std::future::pending::<()>().await;
+        }
+    };
+
+    (rx, reached.boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Option<StopCondition>> {
+        StopCondition::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(parse(&[]).unwrap(), None);
+        assert_eq!(
+            parse(&["--once", "5"]).unwrap(),
+            Some(StopCondition::EventCount(5))
+        );
+        assert_eq!(
+            parse(&["--duration", "10"]).unwrap(),
+            Some(StopCondition::Duration(Duration::from_secs(10)))
+        );
+        assert!(parse(&["--once"]).is_err());
+        assert!(parse(&["--once", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_from_args_rejects_leftover_arguments() {
+        let error = parse(&["--once", "5", "--duration", "10"]).unwrap_err();
+        assert_eq!(error.to_string(), "unexpected argument: --duration");
+    }
+}
@@ -3,61 +3,207 @@ use std::time::Duration;
 use tokio::time::timeout;
 use tracing::{error, info};
 
-use crate::app::ports::EventSource;
+use anyhow::{Context, bail, ensure};
 
-use super::{EventConsumer, LogGuard, Shutdowner, SignalHandler};
+use crate::{app::ports::EventSource, config::model::LoggingConfig};
+
+use super::{
+    CompositeSource, EventConsumer, LogGuard, ManagedSource, Shutdowner, SignalEvent,
+    SignalHandler, StopCondition,
+};
 
 const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Called on a reload signal; an error is logged and the bot keeps running.
+pub type ReloadHook = Box<dyn Fn() -> anyhow::Result<()> + Send + Sync>;
+
 #[non_exhaustive]
 pub struct Supervisor<S, F, C> {
     _log_guard: LogGuard,
     signal_handler: S,
     fetcher: F,
     consumer: C,
+    shutdown_timeout: Duration,
+    fail_on_shutdown_timeout: bool,
+    on_reload: Option<ReloadHook>,
 }
 
-impl<S, F, C> Supervisor<S, F, C>
+/// Assembles a [`Supervisor`] whose sources are merged by a [`CompositeSource`].
+pub struct SupervisorBuilder<S, C> {
+    signal_handler: Option<S>,
+    sources: Vec<Box<dyn ManagedSource>>,
+    consumer: Option<C>,
+    shutdown_timeout: Duration,
+    fail_on_shutdown_timeout: bool,
+    on_reload: Option<ReloadHook>,
+    logging: LoggingConfig,
+}
+
+impl<S, C> Supervisor<S, CompositeSource, C>
+where
+    S: SignalHandler,
+    C: EventConsumer,
+{
+    pub fn builder() -> SupervisorBuilder<S, C> {
+        SupervisorBuilder {
+            signal_handler: None,
+            sources: Vec::new(),
+            consumer: None,
+            shutdown_timeout: SHUTDOWN_TIMEOUT,
+            fail_on_shutdown_timeout: false,
+            on_reload: None,
+            logging: LoggingConfig::default(),
+        }
+    }
+}
+
+impl<S, C> SupervisorBuilder<S, C>
 where
     S: SignalHandler,
-    F: EventSource + Shutdowner,
     C: EventConsumer,
 {
-    pub fn new(signal_handler: S, fetcher: F, consumer: C) -> anyhow::Result<Self> {
-        let log_guard = LogGuard::init();
+    pub fn signal_handler(mut self, signal_handler: S) -> Self {
+        self.signal_handler = Some(signal_handler);
+        self
+    }
+
+    /// Adds an event source; may be called more than once.
+    pub fn source(mut self, source: Box<dyn ManagedSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    pub fn consumer(mut self, consumer: C) -> Self {
+        self.consumer = Some(consumer);
+        self
+    }
+
+    /// How long shutdown waits for in-flight handlers, `10s` by default.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Makes `run` return an error when the shutdown timeout elapses, instead
+    /// of logging it and returning `Ok`.
+    pub fn fail_on_shutdown_timeout(mut self, fail: bool) -> Self {
+        self.fail_on_shutdown_timeout = fail;
+        self
+    }
+
+    /// Where and how to log; stdout as text by default.
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.logging = logging;
+        self
+    }
+
+    /// Runs `hook` on every reload signal instead of ignoring it.
+    pub fn on_reload(mut self, hook: ReloadHook) -> Self {
+        self.on_reload = Some(hook);
+        self
+    }
 
-        Ok(Self {
-            _log_guard: log_guard,
+    pub fn build(self) -> anyhow::Result<Supervisor<S, CompositeSource, C>> {
+        let Self {
             signal_handler,
-            fetcher,
+            sources,
+            consumer,
+            shutdown_timeout,
+            fail_on_shutdown_timeout,
+            on_reload,
+            logging,
+        } = self;
+
+        let signal_handler = signal_handler.context("supervisor requires a signal handler")?;
+        let consumer = consumer.context("supervisor requires a consumer")?;
+        ensure!(
+            !sources.is_empty(),
+            "supervisor requires at least one event source"
+        );
+
+        Ok(Supervisor {
+            _log_guard: LogGuard::init(&logging),
+            signal_handler,
+            fetcher: CompositeSource::new(sources),
             consumer,
+            shutdown_timeout,
+            fail_on_shutdown_timeout,
+            on_reload,
         })
     }
+}
 
+impl<S, F, C> Supervisor<S, F, C>
+where
+    S: SignalHandler,
+    F: EventSource + Shutdowner,
+    C: EventConsumer,
+{
     pub async fn run(self) -> anyhow::Result<()> {
+        self.run_with(None).await
+    }
+
+    /// Runs like [`Supervisor::run`], but also shuts down gracefully once
+    /// `condition` is met.
+    pub async fn run_until(self, condition: StopCondition) -> anyhow::Result<()> {
+        self.run_with(Some(condition)).await
+    }
+
+    async fn run_with(self, condition: Option<StopCondition>) -> anyhow::Result<()> {
         info!("app running...");
 
         let Self {
             signal_handler,
             fetcher,
             consumer,
+            shutdown_timeout,
+            fail_on_shutdown_timeout,
+            on_reload,
             ..
         } = self;
 
-        let event_ch = fetcher.fetch().await;
+        let event_ch = match fetcher.fetch().await {
+            Ok(event_ch) => event_ch,
+            Err(err) => {
+                error!("event source failed to start: {:#}", err);
+                if let Err(shutdown_err) = fetcher.shutdown().await {
+                    error!("failed to shut down event source: {}", shutdown_err);
+                }
+                return Err(err);
+            }
+        };
+        let (event_ch, stop) = match condition {
+            Some(condition) => {
+                let (event_ch, stop) = condition.watch(event_ch);
+                (event_ch, Some(stop))
+            }
+            None => (event_ch, None),
+        };
+
         let handle = tokio::spawn(async move {
             consumer.consume(event_ch).await;
         });
 
-        wait_for_signals(signal_handler).await;
+        match stop {
+            Some(stop) => {
+                tokio::select! {
+                    _ = wait_for_signals(signal_handler, on_reload) => {}
+                    _ = stop => info!("stop condition reached, stopping"),
+                }
+            }
+            None => wait_for_signals(signal_handler, on_reload).await,
+        }
 
         fetcher.shutdown().await?;
 
-        match timeout(SHUTDOWN_TIMEOUT, handle).await {
+        match timeout(shutdown_timeout, handle).await {
             Ok(res) => {
                 info!("graceful shutdown complete");
                 res?;
             }
+            Err(_) if fail_on_shutdown_timeout => {
+                bail!("shutdown timed out after {:?}", shutdown_timeout);
+            }
             Err(_) => {
                 error!("shutdown timeout exceeded, forcing exit");
             }
@@ -67,7 +213,225 @@ where
     }
 }
 
-async fn wait_for_signals<S: SignalHandler>(handler: S) {
-    let signal = handler.wait_for_shutdown().await;
-    info!("received signal {}, stopping", signal);
+async fn wait_for_signals<S: SignalHandler>(handler: S, on_reload: Option<ReloadHook>) {
+    loop {
+        match handler.next_signal().await {
+            SignalEvent::Shutdown(signal) => {
+                info!("received signal {}, stopping", signal);
+                return;
+            }
+            SignalEvent::Reload => match &on_reload {
+                Some(reload) => match reload() {
+                    Ok(()) => info!("config reloaded"),
+                    Err(err) => error!("config reload failed, keeping the old config: {:#}", err),
+                },
+                None => info!("reload requested, but nothing is reloadable"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        adapters::fake::FakeSource,
+        app::test_support::{Counter, ProbeSource, system_event},
+        model::Event,
+        runtime::{Consumer, signal::ShutdownKind},
+    };
+
+    struct NeverSignal;
+
+    #[async_trait]
+    impl SignalHandler for NeverSignal {
+        async fn wait_for_shutdown(&self) -> ShutdownKind {
+            std::future::pending().await
+        }
+    }
+
+    fn events(count: usize) -> Vec<Event> {
+        (0..count)
+            .map(|i| system_event(&format!("event {i}")))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_run_until_event_count_shuts_down_after_last_event() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let source = ProbeSource::new(events(3));
+        let shut_down = source.shut_down();
+        let consumer = Consumer::new(Counter(handled.clone()));
+        let supervisor = Supervisor::builder()
+            .signal_handler(NeverSignal)
+            .source(Box::new(source))
+            .consumer(consumer)
+            .build()
+            .unwrap();
+
+        tokio::time::timeout(
+            Duration::from_secs(2),
+            supervisor.run_until(StopCondition::EventCount(3)),
+        )
+        .await
+        .expect("supervisor did not stop")
+        .unwrap();
+
+        assert_eq!(handled.load(Ordering::SeqCst), 3);
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_is_returned_instead_of_panicking() {
+        let source = ProbeSource::new(Vec::new()).failing_fetch();
+        let shut_down = source.shut_down();
+        let consumer = Consumer::new(Counter(Arc::new(AtomicUsize::new(0))));
+        let supervisor = Supervisor::builder()
+            .signal_handler(NeverSignal)
+            .source(Box::new(source))
+            .consumer(consumer)
+            .build()
+            .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(2), supervisor.run())
+            .await
+            .expect("supervisor did not stop");
+
+        assert!(result.is_err());
+        assert!(shut_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_builder_requires_a_source() {
+        let consumer = Consumer::new(Counter(Arc::new(AtomicUsize::new(0))));
+        let result = Supervisor::builder()
+            .signal_handler(NeverSignal)
+            .consumer(consumer)
+            .build();
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_builder_runs_every_source() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let [first, second] = [2, 3].map(|count| FakeSource::new(events(count)));
+
+        let supervisor = Supervisor::builder()
+            .signal_handler(NeverSignal)
+            .source(Box::new(first))
+            .source(Box::new(second))
+            .consumer(Consumer::new(Counter(handled.clone())))
+            .shutdown_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        tokio::time::timeout(
+            Duration::from_secs(2),
+            supervisor.run_until(StopCondition::EventCount(5)),
+        )
+        .await
+        .expect("supervisor did not stop")
+        .unwrap();
+
+        assert_eq!(handled.load(Ordering::SeqCst), 5);
+    }
+
+    struct StuckConsumer;
+
+    #[async_trait]
+    impl EventConsumer for StuckConsumer {
+        async fn consume(&self, _ch: mpsc::Receiver<Event>) {
+            std::future::pending().await
+        }
+    }
+
+    struct StopAtOnce;
+
+    #[async_trait]
+    impl SignalHandler for StopAtOnce {
+        async fn wait_for_shutdown(&self) -> ShutdownKind {
+            ShutdownKind::Interrupt
+        }
+    }
+
+    fn fake_source() -> Box<FakeSource> {
+        Box::new(FakeSource::new(Vec::new()))
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout_is_ok_by_default() {
+        let supervisor = Supervisor::builder()
+            .signal_handler(StopAtOnce)
+            .source(fake_source())
+            .consumer(StuckConsumer)
+            .shutdown_timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        assert!(supervisor.run().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout_can_fail() {
+        let supervisor = Supervisor::builder()
+            .signal_handler(StopAtOnce)
+            .source(fake_source())
+            .consumer(StuckConsumer)
+            .shutdown_timeout(Duration::from_millis(50))
+            .fail_on_shutdown_timeout(true)
+            .build()
+            .unwrap();
+
+        assert!(supervisor.run().await.is_err());
+    }
+
+    struct ReloadThenStop(AtomicUsize);
+
+    #[async_trait]
+    impl SignalHandler for ReloadThenStop {
+        async fn wait_for_shutdown(&self) -> ShutdownKind {
+            ShutdownKind::Terminate
+        }
+
+        async fn next_signal(&self) -> SignalEvent {
+            match self.0.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => SignalEvent::Reload,
+                _ => SignalEvent::Shutdown(ShutdownKind::Terminate),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_signal_runs_hook_and_keeps_running() {
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = reloads.clone();
+
+        let supervisor = Supervisor::builder()
+            .signal_handler(ReloadThenStop(AtomicUsize::new(0)))
+            .source(fake_source())
+            .consumer(Consumer::new(Counter(Arc::new(AtomicUsize::new(0)))))
+            .shutdown_timeout(Duration::from_millis(50))
+            .on_reload(Box::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                anyhow::bail!("bad config")
+            }))
+            .build()
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), supervisor.run())
+            .await
+            .expect("supervisor did not stop")
+            .unwrap();
+
+        assert_eq!(reloads.load(Ordering::SeqCst), 2);
+    }
 }
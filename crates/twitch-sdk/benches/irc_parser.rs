@@ -0,0 +1,39 @@
+//! Parsing throughput for batched IRC frames.
+//!
+//! Run with `cargo bench -p twitch-sdk --bench irc_parser`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use twitch_sdk::irc::{parse_irc_messages, parse_irc_messages_iter};
+
+const PRIVMSG: &str = "@badge-info=subscriber/12;badges=subscriber/12,vip/1;color=#1E90FF;\
+                       display-name=Some\\sViewer;emotes=25:6-10;first-msg=0;id=1;mod=0;\
+                       room-id=1337;subscriber=1;tmi-sent-ts=1700000000000;user-id=42 \
+                       :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :hello Kappa !ping";
+
+/// A frame holding `lines` messages, the way Twitch batches busy channels.
+fn frame(lines: usize) -> String {
+    (0..lines).map(|_| format!("{PRIVMSG}\r\n")).collect()
+}
+
+fn bench_batched_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("irc_frame");
+
+    for lines in [1, 50] {
+        let frame = frame(lines);
+        group.throughput(Throughput::Bytes(frame.len() as u64));
+
+        group.bench_function(format!("vec/{lines}"), |b| {
+            b.iter(|| parse_irc_messages(black_box(&frame)))
+        });
+        group.bench_function(format!("iter/{lines}"), |b| {
+            b.iter(|| parse_irc_messages_iter(black_box(&frame)).count())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_batched_frame);
+criterion_main!(benches);
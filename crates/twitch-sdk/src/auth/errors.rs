@@ -0,0 +1,47 @@
+use reqwest::StatusCode;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum AuthError {
+    #[error("Refresh token was rejected with status {status}, re-authorization is required")]
+    Revoked { status: u16 },
+
+    #[error("Failed to reach the token endpoint: {0}")]
+    Network(reqwest::Error),
+
+    #[error("Token endpoint is rate limiting refreshes")]
+    RateLimited,
+
+    #[error("Token refresh failed: {0}")]
+    Other(reqwest::Error),
+}
+
+impl AuthError {
+    /// Whether retrying can't help, i.e. the refresh token has to be replaced.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Revoked { .. })
+    }
+
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::RateLimited => true,
+            Self::Other(error) => error.status().is_some_and(|s| s.is_server_error()),
+            Self::Revoked { .. } => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(status @ (StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED)) => Self::Revoked {
+                status: status.as_u16(),
+            },
+            Some(StatusCode::TOO_MANY_REQUESTS) => Self::RateLimited,
+            None if error.is_connect() || error.is_timeout() || error.is_request() => {
+                Self::Network(error)
+            }
+            _ => Self::Other(error),
+        }
+    }
+}
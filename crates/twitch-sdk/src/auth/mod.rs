@@ -1,3 +1,5 @@
+mod errors;
 mod token_manager;
 
+pub use errors::AuthError;
 pub use token_manager::{OnTokenRotation, TokenManager};
@@ -1,17 +1,22 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::Deserialize;
 use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
+use super::AuthError;
+use crate::chat::helix_types::CLIENT_TIMEOUT;
+
 const TOKEN_URL: &str = "https://id.twitch.tv/oauth2/token";
 const REFRESH_BUFFER_SECS: u64 = 600;
 const RETRY_DELAY_SECS: u64 = 30;
 const MIN_SLEEP_SECS: u64 = 60;
+const REFRESH_ATTEMPTS: u32 = 3;
+const REFRESH_BACKOFF: Duration = Duration::from_millis(500);
 
 #[derive(Deserialize, Debug)]
 struct TokenResponse {
@@ -27,10 +32,14 @@ pub struct TokenManager {
     client: Client,
     client_id: String,
     client_secret: String,
+    token_url: String,
+    retry_backoff: Duration,
+    request_timeout: Duration,
     refresh_token: RwLock<String>,
     current_token: RwLock<Option<String>>,
     init_lock: Mutex<()>,
     on_rotation: Option<OnTokenRotation>,
+    cancel_token: CancellationToken,
 }
 
 impl TokenManager {
@@ -40,10 +49,14 @@ impl TokenManager {
             client: Client::new(),
             client_id,
             client_secret,
+            token_url: TOKEN_URL.to_string(),
+            retry_backoff: REFRESH_BACKOFF,
+            request_timeout: CLIENT_TIMEOUT,
             refresh_token: RwLock::new(refresh_token),
             current_token: RwLock::new(None),
             init_lock: Mutex::new(()),
             on_rotation: None,
+            cancel_token: CancellationToken::new(),
         }
     }
 
@@ -53,7 +66,34 @@ impl TokenManager {
         self
     }
 
-    pub async fn get_token(&self) -> Result<String> {
+    #[must_use]
+    pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = token;
+        self
+    }
+
+    /// Cancelling it stops the background refresh loop.
+    #[must_use]
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel_token.clone()
+    }
+
+    /// How long a single refresh request may take before it fails as a
+    /// network error and is retried. Defaults to 10 seconds.
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Points token refreshes at another OAuth server, e.g. a mock in tests.
+    #[must_use]
+    pub fn with_token_url(mut self, token_url: impl Into<String>) -> Self {
+        self.token_url = token_url.into();
+        self
+    }
+
+    pub async fn get_token(&self) -> Result<String, AuthError> {
         if let Some(token) = self.current_token.read().await.as_ref() {
             return Ok(token.clone());
         }
@@ -68,53 +108,75 @@ impl TokenManager {
         Ok(token)
     }
 
-    pub fn start_background_loop(self: Arc<Self>) -> JoinHandle<()> {
+    /// Returns the last-known token without waiting for an in-flight refresh.
+    ///
+    /// The token may already be expired; `None` means no token has been fetched yet.
+    pub async fn get_token_allow_stale(&self) -> Option<String> {
+        self.current_token.read().await.clone()
+    }
+
+    /// Keeps the token fresh until the manager's cancel token fires, which the
+    /// returned handle resolves with `Ok`, or until a fatal error, which it
+    /// resolves with. Transient failures are retried every 30 seconds.
+    pub fn start_background_loop(self: Arc<Self>) -> JoinHandle<Result<(), AuthError>> {
         tokio::spawn(async move {
             info!("starting token refresh background task");
 
-            loop {
-                match self.refresh_now().await {
-                    Ok((_, expires_in)) => {
-                        let sleep_secs = expires_in
-                            .saturating_sub(REFRESH_BUFFER_SECS)
-                            .max(MIN_SLEEP_SECS);
-
-                        info!("token refreshed. next refresh in {} seconds", sleep_secs);
-                        tokio::time::sleep(Duration::from_secs(sleep_secs)).await;
-                    }
-                    Err(error) => {
-                        error!(
-                            "failed to refresh token: {:?}. retrying in {}s...",
-                            error, RETRY_DELAY_SECS
-                        );
-                        tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
-                    }
+            tokio::select! {
+                _ = self.cancel_token.cancelled() => {
+                    info!("token refresh background task cancelled");
+                    Ok(())
                 }
+                error = self.refresh_loop() => Err(error),
             }
         })
     }
 
-    async fn refresh_now(&self) -> Result<(String, u64)> {
-        let current_refresh = self.refresh_token.read().await.clone();
+    async fn refresh_loop(&self) -> AuthError {
+        loop {
+            match self.refresh_now().await {
+                Ok((_, expires_in)) => {
+                    let sleep_secs = expires_in
+                        .saturating_sub(REFRESH_BUFFER_SECS)
+                        .max(MIN_SLEEP_SECS);
 
-        let params = [
-            ("client_id", self.client_id.as_str()),
-            ("client_secret", self.client_secret.as_str()),
-            ("grant_type", "refresh_token"),
-            ("refresh_token", current_refresh.as_str()),
-        ];
+                    info!("token refreshed. next refresh in {} seconds", sleep_secs);
+                    tokio::time::sleep(Duration::from_secs(sleep_secs)).await;
+                }
+                Err(error) if error.is_fatal() => {
+                    error!("stopping token refresh: {}", error);
+                    return error;
+                }
+                Err(error) => {
+                    error!(
+                        "failed to refresh token: {:?}. retrying in {}s...",
+                        error, RETRY_DELAY_SECS
+                    );
+                    tokio::time::sleep(Duration::from_secs(RETRY_DELAY_SECS)).await;
+                }
+            }
+        }
+    }
 
-        let response = self
-            .client
-            .post(TOKEN_URL)
-            .form(&params)
-            .send()
-            .await?
-            .error_for_status()
-            .context("token refresh request failed")?
-            .json::<TokenResponse>()
-            .await
-            .context("failed to parse token response")?;
+    async fn refresh_now(&self) -> Result<(String, u64), AuthError> {
+        let current_refresh = self.refresh_token.read().await.clone();
+
+        let mut attempt = 1;
+        let response = loop {
+            match self.request_token(&current_refresh).await {
+                Ok(response) => break response,
+                Err(error) if attempt < REFRESH_ATTEMPTS && error.is_transient() => {
+                    let backoff = self.retry_backoff * 2u32.pow(attempt - 1);
+                    warn!(
+                        "token refresh attempt {} failed: {}. retrying in {:?}",
+                        attempt, error, backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        };
 
         let full_token = format!("oauth:{}", response.access_token);
 
@@ -143,6 +205,26 @@ impl TokenManager {
         Ok((full_token, response.expires_in))
     }
 
+    async fn request_token(&self, refresh_token: &str) -> Result<TokenResponse, AuthError> {
+        let params = [
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+
+        Ok(self
+            .client
+            .post(&self.token_url)
+            .timeout(self.request_timeout)
+            .form(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TokenResponse>()
+            .await?)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub async fn set_token_for_test(&self, token: String) {
         let mut lock = self.current_token.write().await;
@@ -152,6 +234,9 @@ impl TokenManager {
 
 #[cfg(test)]
 mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
     use super::*;
 
     fn make_manager() -> TokenManager {
@@ -162,6 +247,161 @@ mod tests {
         )
     }
 
+    /// Answers one request per response in order, returning how many were served.
+    async fn serve_responses(
+        listener: TcpListener,
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> usize {
+        let mut served = 0;
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            served += 1;
+        }
+        served
+    }
+
+    async fn manager_with_server(
+        responses: Vec<(&'static str, &'static str)>,
+    ) -> (TokenManager, JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/oauth2/token", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_responses(listener, responses));
+
+        let mut manager = make_manager().with_token_url(url);
+        manager.retry_backoff = Duration::from_millis(1);
+        (manager, server)
+    }
+
+    const TOKEN_BODY: &str =
+        r#"{"access_token":"fresh","expires_in":3600,"refresh_token":"test_refresh"}"#;
+
+    #[tokio::test]
+    async fn test_refresh_retries_server_errors() {
+        let (manager, server) = manager_with_server(vec![
+            ("503 Service Unavailable", "{}"),
+            ("500 Internal Server Error", "{}"),
+            ("200 OK", TOKEN_BODY),
+        ])
+        .await;
+
+        let token = manager.get_token().await.unwrap();
+        assert_eq!(token, "oauth:fresh");
+        assert_eq!(server.await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_gives_up_after_max_attempts() {
+        let (manager, server) = manager_with_server(vec![
+            ("502 Bad Gateway", "{}"),
+            ("502 Bad Gateway", "{}"),
+            ("502 Bad Gateway", "{}"),
+            ("200 OK", TOKEN_BODY),
+        ])
+        .await;
+
+        assert!(matches!(
+            manager.get_token().await,
+            Err(AuthError::Other(_))
+        ));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_refresh_fails_fast_on_rejected_refresh_token() {
+        let (manager, server) = manager_with_server(vec![
+            (
+                "400 Bad Request",
+                r#"{"status":400,"message":"Invalid refresh token"}"#,
+            ),
+            ("200 OK", TOKEN_BODY),
+        ])
+        .await;
+
+        assert!(matches!(
+            manager.get_token().await,
+            Err(AuthError::Revoked { status: 400 })
+        ));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_background_loop_stops_on_revoked_token() {
+        let (manager, _server) =
+            manager_with_server(vec![("401 Unauthorized", r#"{"status":401}"#)]).await;
+
+        let error = tokio::time::timeout(
+            Duration::from_secs(5),
+            Arc::new(manager).start_background_loop(),
+        )
+        .await
+        .expect("loop should stop instead of retrying")
+        .unwrap()
+        .unwrap_err();
+
+        assert!(error.is_fatal());
+    }
+
+    #[tokio::test]
+    async fn test_background_loop_stops_when_cancelled() {
+        let (manager, _server) = manager_with_server(vec![("200 OK", TOKEN_BODY)]).await;
+        let manager = Arc::new(manager);
+
+        let handle = manager.clone().start_background_loop();
+        manager.cancel_token().cancel();
+
+        let stopped = tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("loop should stop once cancelled")
+            .unwrap();
+        assert!(stopped.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_times_out_on_a_hung_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/oauth2/token", listener.local_addr().unwrap());
+        // accepts every connection and never answers
+        let server = tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                streams.push(stream);
+            }
+        });
+
+        let mut manager = make_manager()
+            .with_token_url(url)
+            .with_request_timeout(Duration::from_millis(50));
+        manager.retry_backoff = Duration::from_millis(1);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), manager.get_token())
+            .await
+            .expect("refresh should time out instead of hanging");
+
+        assert!(matches!(result, Err(AuthError::Network(_))));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_refresh_retries_rate_limit() {
+        let (manager, server) = manager_with_server(vec![
+            ("429 Too Many Requests", "{}"),
+            ("200 OK", TOKEN_BODY),
+        ])
+        .await;
+
+        assert_eq!(manager.get_token().await.unwrap(), "oauth:fresh");
+        assert_eq!(server.await.unwrap(), 2);
+    }
+
     #[tokio::test]
     async fn test_new_creates_empty_token() {
         let manager = make_manager();
@@ -182,6 +422,28 @@ mod tests {
         assert_eq!(result.unwrap(), "oauth:cached_token");
     }
 
+    #[tokio::test]
+    async fn test_get_token_allow_stale_is_none_before_first_fetch() {
+        let manager = make_manager();
+        assert!(manager.get_token_allow_stale().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_token_allow_stale_does_not_wait_for_refresh() {
+        let manager = make_manager();
+        manager
+            .set_token_for_test("oauth:last_known".to_string())
+            .await;
+
+        let _refresh_in_flight = manager.init_lock.lock().await;
+
+        let token =
+            tokio::time::timeout(Duration::from_millis(100), manager.get_token_allow_stale())
+                .await
+                .expect("should not block on the refresh lock");
+        assert_eq!(token.as_deref(), Some("oauth:last_known"));
+    }
+
     #[tokio::test]
     async fn test_refresh_token_stored_correctly() {
         let manager = make_manager();
@@ -1,8 +1,14 @@
+use super::restrictions::Restriction;
+use crate::auth::AuthError;
+
 #[derive(Debug, thiserror::Error)]
 pub enum SenderError {
     #[error("Failed to get access token: {0}")]
-    FailedGetAccessToken(#[from] anyhow::Error),
+    FailedGetAccessToken(#[from] AuthError),
 
     #[error("Failed send message: {0}")]
     FailedSendMessage(#[from] reqwest::Error),
+
+    #[error("Message would be dropped: channel is in {0} mode")]
+    WouldBeDropped(Restriction),
 }
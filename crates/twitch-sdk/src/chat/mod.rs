@@ -1,3 +1,4 @@
 pub mod errors;
 pub mod helix_types;
+pub mod restrictions;
 pub mod sender;
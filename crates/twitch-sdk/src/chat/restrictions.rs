@@ -0,0 +1,244 @@
+use std::fmt;
+
+use crate::model::{TwitchEvent, TwitchRole};
+
+/// Chat room modes, as reported by ROOMSTATE or an EventSub chat settings
+/// update, that make Twitch silently drop messages from senders who don't
+/// qualify.
+///
+/// Start from `ChatRestrictions::default()` and keep it current with
+/// [`ChatRestrictions::apply`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChatRestrictions {
+    pub emote_only: bool,
+    pub followers_only: bool,
+    pub subs_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restriction {
+    EmoteOnly,
+    FollowersOnly,
+    SubsOnly,
+}
+
+impl fmt::Display for Restriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Restriction::EmoteOnly => write!(f, "emote-only"),
+            Restriction::FollowersOnly => write!(f, "followers-only"),
+            Restriction::SubsOnly => write!(f, "subs-only"),
+        }
+    }
+}
+
+impl ChatRestrictions {
+    /// Updates the modes from a [`TwitchEvent::RoomState`] or
+    /// [`TwitchEvent::ChatSettingsUpdate`]; other events are ignored. Settings
+    /// a ROOMSTATE leaves out keep their current value, while a settings
+    /// update always carries the full set.
+    pub fn apply(&mut self, event: &TwitchEvent) {
+        let (followers_only, subs_only, emote_only) = match event {
+            TwitchEvent::RoomState {
+                followers_only,
+                subs_only,
+                emote_only,
+                ..
+            } => (followers_only, subs_only, emote_only),
+            TwitchEvent::ChatSettingsUpdate {
+                emote_mode,
+                follower_mode,
+                subscriber_mode,
+                ..
+            } => {
+                self.emote_only = *emote_mode;
+                self.followers_only = *follower_mode;
+                self.subs_only = *subscriber_mode;
+                return;
+            }
+            _ => return,
+        };
+
+        if let Some(minutes) = followers_only {
+            self.followers_only = *minutes >= 0;
+        }
+        if let Some(subs_only) = subs_only {
+            self.subs_only = *subs_only;
+        }
+        if let Some(emote_only) = emote_only {
+            self.emote_only = *emote_only;
+        }
+    }
+
+    /// Returns the restriction that would make Twitch drop a message from a
+    /// sender with `role`, if any.
+    ///
+    /// Broadcasters and moderators bypass every mode; VIPs bypass followers-only
+    /// and subs-only. Emote-only is treated as blocking for everyone else since
+    /// bot messages are plain text.
+    #[must_use]
+    pub fn blocking_restriction(
+        &self,
+        role: TwitchRole,
+        follows_channel: bool,
+    ) -> Option<Restriction> {
+        let highest = role.highest();
+        let is_staff = highest == TwitchRole::BROADCASTER || highest == TwitchRole::MODERATOR;
+        let is_vip = is_staff || highest == TwitchRole::VIP;
+        let is_subscriber = is_vip || highest == TwitchRole::SUBSCRIBER;
+
+        if self.emote_only && !is_staff {
+            return Some(Restriction::EmoteOnly);
+        }
+        if self.subs_only && !is_subscriber {
+            return Some(Restriction::SubsOnly);
+        }
+        if self.followers_only && !is_vip && !follows_channel {
+            return Some(Restriction::FollowersOnly);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subs_only() -> ChatRestrictions {
+        ChatRestrictions {
+            subs_only: true,
+            ..ChatRestrictions::default()
+        }
+    }
+
+    #[test]
+    fn test_subs_only_blocks_non_subscriber() {
+        assert_eq!(
+            subs_only().blocking_restriction(TwitchRole::empty(), true),
+            Some(Restriction::SubsOnly)
+        );
+    }
+
+    #[test]
+    fn test_subs_only_allows_moderator_and_subscriber() {
+        assert_eq!(
+            subs_only().blocking_restriction(TwitchRole::MODERATOR, false),
+            None
+        );
+        assert_eq!(
+            subs_only().blocking_restriction(TwitchRole::SUBSCRIBER, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_emote_only_blocks_vip_but_not_broadcaster() {
+        let restrictions = ChatRestrictions {
+            emote_only: true,
+            ..ChatRestrictions::default()
+        };
+
+        assert_eq!(
+            restrictions.blocking_restriction(TwitchRole::VIP, true),
+            Some(Restriction::EmoteOnly)
+        );
+        assert_eq!(
+            restrictions.blocking_restriction(TwitchRole::BROADCASTER, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_followers_only_depends_on_follow_status() {
+        let restrictions = ChatRestrictions {
+            followers_only: true,
+            ..ChatRestrictions::default()
+        };
+
+        assert_eq!(
+            restrictions.blocking_restriction(TwitchRole::empty(), false),
+            Some(Restriction::FollowersOnly)
+        );
+        assert_eq!(
+            restrictions.blocking_restriction(TwitchRole::empty(), true),
+            None
+        );
+        assert_eq!(
+            restrictions.blocking_restriction(TwitchRole::VIP, false),
+            None
+        );
+    }
+
+    fn room_state(
+        followers_only: Option<i32>,
+        subs_only: Option<bool>,
+        emote_only: Option<bool>,
+    ) -> TwitchEvent {
+        TwitchEvent::RoomState {
+            channel: "channel".to_string(),
+            slow: None,
+            followers_only,
+            subs_only,
+            emote_only,
+            unique_chat: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_room_state_updates_only_present_settings() {
+        let mut restrictions = ChatRestrictions::default();
+
+        restrictions.apply(&room_state(Some(10), Some(true), Some(false)));
+        assert_eq!(
+            restrictions,
+            ChatRestrictions {
+                emote_only: false,
+                followers_only: true,
+                subs_only: true,
+            }
+        );
+
+        restrictions.apply(&room_state(Some(-1), None, None));
+        assert!(!restrictions.followers_only);
+        assert!(restrictions.subs_only);
+
+        restrictions.apply(&TwitchEvent::StreamOffline);
+        assert!(restrictions.subs_only);
+    }
+
+    #[test]
+    fn test_apply_chat_settings_update_replaces_every_mode() {
+        let mut restrictions = ChatRestrictions {
+            emote_only: true,
+            ..ChatRestrictions::default()
+        };
+
+        restrictions.apply(&TwitchEvent::ChatSettingsUpdate {
+            emote_mode: false,
+            follower_mode: true,
+            follower_mode_duration: Some(0),
+            slow_mode: false,
+            slow_mode_wait_time: None,
+            subscriber_mode: true,
+            unique_chat_mode: false,
+        });
+
+        assert_eq!(
+            restrictions,
+            ChatRestrictions {
+                emote_only: false,
+                followers_only: true,
+                subs_only: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unrestricted_room_allows_everyone() {
+        assert_eq!(
+            ChatRestrictions::default().blocking_restriction(TwitchRole::empty(), false),
+            None
+        );
+    }
+}
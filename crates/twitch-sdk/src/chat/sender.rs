@@ -4,10 +4,15 @@ use reqwest::{Client, redirect};
 use serde_json::json;
 
 use crate::auth::TokenManager;
+use crate::model::TwitchRole;
 
 use super::{
     errors::SenderError,
-    helix_types::{CLIENT_TIMEOUT, CONNECTION_TIMEOUT, REDIRECT_LIMIT, TWITCH_HELIX_URL},
+    helix_types::{
+        CLIENT_TIMEOUT, CONNECTION_TIMEOUT, REDIRECT_LIMIT, TWITCH_HELIX_URL,
+        TWITCH_HELIX_WHISPERS_URL,
+    },
+    restrictions::ChatRestrictions,
 };
 
 #[non_exhaustive]
@@ -16,6 +21,8 @@ pub struct HelixSender {
     client_id: String,
     token_manager: Arc<TokenManager>,
     client: Client,
+    chat_url: String,
+    whispers_url: String,
 }
 
 impl HelixSender {
@@ -32,15 +39,31 @@ impl HelixSender {
             token_manager,
             client,
             writer_id,
+            chat_url: TWITCH_HELIX_URL.to_string(),
+            whispers_url: TWITCH_HELIX_WHISPERS_URL.to_string(),
         })
     }
 
+    /// Overrides the Helix chat messages endpoint, e.g. to point at a local mock.
+    #[must_use]
+    pub fn with_chat_url(mut self, url: impl Into<String>) -> Self {
+        self.chat_url = url.into();
+        self
+    }
+
+    /// Overrides the Helix whispers endpoint, e.g. to point at a local mock.
+    #[must_use]
+    pub fn with_whispers_url(mut self, url: impl Into<String>) -> Self {
+        self.whispers_url = url.into();
+        self
+    }
+
     pub async fn send(&self, channel: &str, message: &str) -> Result<(), SenderError> {
         let token = self.token_manager.get_token().await?;
         let access_token = token.strip_prefix("oauth:").unwrap_or(&token);
 
         self.client
-            .post(TWITCH_HELIX_URL)
+            .post(&self.chat_url)
             .bearer_auth(access_token)
             .header("Client-Id", &self.client_id)
             .json(&json!({
@@ -53,6 +76,48 @@ impl HelixSender {
 
         Ok(())
     }
+
+    /// Whispers `message` to the user with id `to_user_id` from the writer account.
+    ///
+    /// Twitch rejects whispers from accounts without a verified phone number and
+    /// rate-limits them separately from chat, so failures are surfaced as errors.
+    pub async fn send_whisper(&self, to_user_id: &str, message: &str) -> Result<(), SenderError> {
+        let token = self.token_manager.get_token().await?;
+        let access_token = token.strip_prefix("oauth:").unwrap_or(&token);
+
+        self.client
+            .post(&self.whispers_url)
+            .bearer_auth(access_token)
+            .header("Client-Id", &self.client_id)
+            .query(&[
+                ("from_user_id", self.writer_id.as_str()),
+                ("to_user_id", to_user_id),
+            ])
+            .json(&json!({ "message": message }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Sends a message unless the room's restrictions would make Twitch drop it.
+    ///
+    /// `role` and `follows_channel` describe the bot account in `channel`.
+    pub async fn send_checked(
+        &self,
+        channel: &str,
+        message: &str,
+        restrictions: &ChatRestrictions,
+        role: TwitchRole,
+        follows_channel: bool,
+    ) -> Result<(), SenderError> {
+        if let Some(restriction) = restrictions.blocking_restriction(role, follows_channel) {
+            return Err(SenderError::WouldBeDropped(restriction));
+        }
+
+        self.send(channel, message).await
+    }
 }
 
 fn build_request_client() -> Result<Client, SenderError> {
@@ -62,3 +127,112 @@ fn build_request_client() -> Result<Client, SenderError> {
         .redirect(redirect::Policy::limited(REDIRECT_LIMIT))
         .build()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    use super::*;
+    use crate::chat::restrictions::Restriction;
+
+    /// Accepts one request on `path`, answers it with `status` and returns it as text.
+    async fn mock_helix(path: &str, status: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{path}", listener.local_addr().unwrap());
+        let response =
+            format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"}") {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        (url, server)
+    }
+
+    async fn authorized(sender: HelixSender) -> HelixSender {
+        sender
+            .token_manager
+            .set_token_for_test("oauth:test_token".to_string())
+            .await;
+        sender
+    }
+
+    fn make_sender() -> HelixSender {
+        let token_manager = Arc::new(TokenManager::new(
+            "test_client_id".to_string(),
+            "test_secret".to_string(),
+            "test_refresh".to_string(),
+        ));
+        HelixSender::new("writer", "test_client_id", token_manager).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_checked_rejects_message_in_subs_only_room() {
+        let sender = make_sender();
+        let restrictions = ChatRestrictions {
+            subs_only: true,
+            ..ChatRestrictions::default()
+        };
+
+        let result = sender
+            .send_checked("1337", "hello", &restrictions, TwitchRole::empty(), true)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SenderError::WouldBeDropped(Restriction::SubsOnly))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_checked_lets_moderator_post_in_subs_only_room() {
+        let (url, server) = mock_helix("/chat/messages", "200 OK").await;
+        let sender = authorized(make_sender().with_chat_url(url)).await;
+        let restrictions = ChatRestrictions {
+            subs_only: true,
+            ..ChatRestrictions::default()
+        };
+
+        sender
+            .send_checked("1337", "hello", &restrictions, TwitchRole::MODERATOR, false)
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /chat/messages"));
+        assert!(request.contains("\"message\":\"hello\""));
+    }
+
+    #[tokio::test]
+    async fn test_send_whisper_posts_users_and_message() {
+        let (url, server) = mock_helix("/whispers", "204 No Content").await;
+        let sender = authorized(make_sender().with_whispers_url(url)).await;
+
+        sender.send_whisper("42", "psst").await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /whispers?from_user_id=writer&to_user_id=42 "));
+        assert!(request.contains("authorization: Bearer test_token"));
+        assert!(request.ends_with(r#"{"message":"psst"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_send_whisper_surfaces_rejection() {
+        let (url, server) = mock_helix("/whispers", "401 Unauthorized").await;
+        let sender = authorized(make_sender().with_whispers_url(url)).await;
+
+        let result = sender.send_whisper("42", "psst").await;
+
+        assert!(result.is_err());
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,33 @@
+use crate::auth::AuthError;
+
+/// Errors returned by the client APIs, e.g. [`EventSubClient::connect`]
+/// or [`EventSubClient::unsubscribe`].
+///
+/// Failures of a running connection are retried in the background and only
+/// logged. The exception is [`Error::LoginFailed`]: retrying a rejected token
+/// can't help, so [`IrcClient`] stops and returns it from its `shutdown`.
+///
+/// [`IrcClient`]: crate::IrcClient
+/// [`EventSubClient::connect`]: crate::EventSubClient::connect
+/// [`EventSubClient::unsubscribe`]: crate::EventSubClient::unsubscribe
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Failed to get access token: {0}")]
+    Auth(#[from] AuthError),
+
+    #[error("Twitch request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Twitch returned {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("Authentication failed, check your token: {0}")]
+    LoginFailed(String),
+
+    #[error("Client is already connected")]
+    AlreadyConnected,
+
+    #[error("Client task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
@@ -1,9 +1,13 @@
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use futures_util::StreamExt;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
 use serde::Serialize;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
@@ -13,17 +17,32 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+use super::dedup::SeenMessages;
 use super::protocol::{
-    ChatBadge, ChatMessageEvent, EventSubMessage, NotificationPayload, RewardRedemptionEvent,
-    Session, SessionPayload,
+    BanEvent, ChatBadge, ChatMessageEvent, ChatSettingsUpdateEvent, CreatedSubscriptions,
+    EventSubMessage, NotificationPayload, RaidEvent, ReconnectPayload, RewardRedemptionEvent,
+    Session, SessionPayload, StreamOnlineEvent, SubscribeEvent, SubscriptionMessageEvent,
+    SubscriptionPage, WhisperMessageEvent,
 };
+use super::subscriptions::{ActiveSubscriptions, SubscriptionCost, SubscriptionInfo};
+use super::topic::EventSubTopic;
+use crate::Error;
 use crate::auth::TokenManager;
-use crate::model::{TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser};
+use crate::chat::helix_types::CLIENT_TIMEOUT;
+use crate::model::{
+    MessageFragment, TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser, split_action,
+};
+use crate::recorder::RawRecorder;
 const EVENTSUB_WS_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
 const EVENTSUB_API_URL: &str = "https://api.twitch.tv/helix/eventsub/subscriptions";
 const CHANNEL_BUFFER_SIZE: usize = 100;
-const RECONNECT_DELAY_SECS: u64 = 5;
-const KEEPALIVE_TIMEOUT_BUFFER_SECS: u64 = 5;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const KEEPALIVE_TIMEOUT_BUFFER: Duration = Duration::from_secs(5);
+const DEDUP_WINDOW: Duration = Duration::from_secs(600);
+const SUBSCRIBE_ATTEMPTS: u32 = 3;
+const RATE_LIMIT_FALLBACK: Duration = Duration::from_secs(1);
+/// Twitch closes a session with no subscription 10 seconds after its welcome.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5);
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
@@ -49,8 +68,20 @@ pub struct EventSubClient {
     broadcaster_id: String,
     bot_user_id: String,
     client_id: String,
+    topics: Vec<EventSubTopic>,
+    required: HashSet<String>,
+    ws_url: String,
+    api_url: String,
+    request_timeout: Duration,
+    emit_unknown: bool,
+    emit_connected: bool,
+    keepalive_buffer: Duration,
+    reconnect_delay: Duration,
+    dedup_window: Duration,
+    recorder: Option<RawRecorder>,
+    subscriptions: ActiveSubscriptions,
     cancel_token: CancellationToken,
-    handle: Option<JoinHandle<()>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
 }
 
 struct EventSubLifecycleParams {
@@ -60,6 +91,17 @@ struct EventSubLifecycleParams {
     broadcaster_id: String,
     bot_user_id: String,
     client_id: String,
+    topics: Vec<EventSubTopic>,
+    required: HashSet<String>,
+    ws_url: String,
+    api_url: String,
+    request_timeout: Duration,
+    emit_unknown: bool,
+    emit_connected: bool,
+    keepalive_buffer: Duration,
+    dedup_window: Duration,
+    recorder: Option<RawRecorder>,
+    subscriptions: ActiveSubscriptions,
     cancel_token: CancellationToken,
 }
 
@@ -83,11 +125,118 @@ impl EventSubClient {
             broadcaster_id,
             bot_user_id,
             client_id,
+            topics: Vec::new(),
+            required: HashSet::new(),
+            ws_url: EVENTSUB_WS_URL.to_string(),
+            api_url: EVENTSUB_API_URL.to_string(),
+            request_timeout: CLIENT_TIMEOUT,
+            emit_unknown: false,
+            emit_connected: false,
+            keepalive_buffer: KEEPALIVE_TIMEOUT_BUFFER,
+            reconnect_delay: RECONNECT_DELAY,
+            dedup_window: DEDUP_WINDOW,
+            recorder: None,
+            subscriptions: ActiveSubscriptions::default(),
             cancel_token: CancellationToken::new(),
-            handle: None,
+            handle: Mutex::new(None),
         }
     }
 
+    /// Additionally subscribes to an optional topic on every (re)connect.
+    #[must_use]
+    pub fn with_topic(mut self, topic: EventSubTopic) -> Self {
+        if !self.topics.contains(&topic) {
+            self.topics.push(topic);
+        }
+        self
+    }
+
+    /// Makes a failure to create `sub_type`, e.g. `channel.chat.message`, fail
+    /// the connection. Other subscriptions that fail are logged and skipped,
+    /// and the session stays up with the ones that succeeded.
+    #[must_use]
+    pub fn with_required_subscription(mut self, sub_type: impl Into<String>) -> Self {
+        self.required.insert(sub_type.into());
+        self
+    }
+
+    /// Connects to another EventSub WebSocket server, e.g. a mock in tests.
+    #[must_use]
+    pub fn with_ws_url(mut self, url: impl Into<String>) -> Self {
+        self.ws_url = url.into();
+        self
+    }
+
+    /// Creates subscriptions against another endpoint, e.g. a mock in tests.
+    #[must_use]
+    pub fn with_api_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into();
+        self
+    }
+
+    /// Emits [`TwitchEvent::Unknown`] for notifications the SDK can't parse into a
+    /// typed event, instead of dropping them.
+    #[must_use]
+    pub fn with_unknown_notifications(mut self) -> Self {
+        self.emit_unknown = true;
+        self
+    }
+
+    /// Emits [`TwitchEvent::Connected`] once each new session has subscribed,
+    /// so consumers can tell the bot is (back) online. Sessions moved by a
+    /// `session_reconnect` continue the old one and emit nothing.
+    #[must_use]
+    pub fn with_connection_events(mut self) -> Self {
+        self.emit_connected = true;
+        self
+    }
+
+    /// Grace period on top of the session's keepalive interval before the
+    /// connection is considered dead.
+    #[must_use]
+    pub fn with_keepalive_buffer(mut self, buffer: Duration) -> Self {
+        self.keepalive_buffer = buffer;
+        self
+    }
+
+    /// How long a Helix subscription request may take. A create that times
+    /// out fails the connection attempt, which is then retried. Defaults to
+    /// 10 seconds.
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Delay before reconnecting after the connection is lost.
+    #[must_use]
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// How long a notification's `message_id` is remembered to drop redeliveries.
+    /// Defaults to the 10 minutes Twitch recommends.
+    #[must_use]
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Appends every raw inbound message after the welcome to the file at `path`,
+    /// one JSON document per line, producing a log that can be replayed later.
+    pub fn with_raw_recorder(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        self.recorder = Some(RawRecorder::open(path)?);
+        Ok(self)
+    }
+
+    /// Like [`EventSubClient::with_raw_recorder`], but writes to `writer`.
+    #[must_use]
+    pub fn with_raw_writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.recorder = Some(RawRecorder::from_writer(writer));
+        self
+    }
+
     #[must_use]
     pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
         self.cancel_token = token;
@@ -99,7 +248,17 @@ impl EventSubClient {
         self.cancel_token.clone()
     }
 
-    pub async fn connect(&mut self) -> Result<mpsc::Receiver<TwitchEvent>> {
+    /// Starts the connection task and returns its event receiver. Fails with
+    /// [`Error::AlreadyConnected`] while a previous task is still running.
+    ///
+    /// Takes `&self`, so a client shared through an `Arc` can be started and
+    /// shut down without a lock around it.
+    pub async fn connect(&self) -> Result<mpsc::Receiver<TwitchEvent>, Error> {
+        let mut handle = self.handle();
+        if handle.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return Err(Error::AlreadyConnected);
+        }
+
         let (tx, rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
 
         let tm = self.token_manager.clone();
@@ -107,9 +266,21 @@ impl EventSubClient {
         let broadcaster_id = self.broadcaster_id.clone();
         let bot_user_id = self.bot_user_id.clone();
         let client_id = self.client_id.clone();
+        let topics = self.topics.clone();
+        let required = self.required.clone();
+        let ws_url = self.ws_url.clone();
+        let api_url = self.api_url.clone();
+        let request_timeout = self.request_timeout;
+        let emit_unknown = self.emit_unknown;
+        let emit_connected = self.emit_connected;
+        let keepalive_buffer = self.keepalive_buffer;
+        let reconnect_delay = self.reconnect_delay;
+        let dedup_window = self.dedup_window;
+        let recorder = self.recorder.clone();
+        let subscriptions = self.subscriptions.clone();
         let cancel = self.cancel_token.clone();
 
-        self.handle = Some(tokio::spawn(async move {
+        *handle = Some(tokio::spawn(async move {
             info!("starting EventSub client lifecycle...");
 
             loop {
@@ -128,6 +299,17 @@ impl EventSubClient {
                         broadcaster_id: broadcaster_id.clone(),
                         bot_user_id: bot_user_id.clone(),
                         client_id: client_id.clone(),
+                        topics: topics.clone(),
+                        required: required.clone(),
+                        ws_url: ws_url.clone(),
+                        api_url: api_url.clone(),
+                        request_timeout,
+                        emit_unknown,
+                        emit_connected,
+                        keepalive_buffer,
+                        dedup_window,
+                        recorder: recorder.clone(),
+                        subscriptions: subscriptions.clone(),
                         cancel_token: cancel.clone(),
                     }) => {
                         if let Err(e) = result {
@@ -135,8 +317,8 @@ impl EventSubClient {
                                 info!("EventSub shutdown complete");
                                 break;
                             }
-                            error!("EventSub connection lost: {:?}. reconnecting in {}s...", e, RECONNECT_DELAY_SECS);
-                            tokio::time::sleep(Duration::from_secs(RECONNECT_DELAY_SECS)).await;
+                            error!("EventSub connection lost: {:?}. reconnecting in {:?}...", e, reconnect_delay);
+                            tokio::time::sleep(reconnect_delay).await;
                         }
                     }
                 }
@@ -146,14 +328,106 @@ impl EventSubClient {
         Ok(rx)
     }
 
-    pub async fn shutdown(&mut self) -> anyhow::Result<()> {
+    /// IDs of the subscriptions on the current session, keyed by type such
+    /// as `stream.online`. Empty until the first connect has subscribed.
+    #[must_use]
+    pub fn subscription_ids(&self) -> HashMap<String, String> {
+        self.subscriptions.ids()
+    }
+
+    /// The subscription budget from Twitch's last create or list response,
+    /// or `None` before the first. Deletes are not reflected until the next
+    /// such response.
+    #[must_use]
+    pub fn cost_status(&self) -> Option<SubscriptionCost> {
+        self.subscriptions.cost()
+    }
+
+    /// Deletes a subscription by ID, e.g. one from
+    /// [`EventSubClient::subscription_ids`], without touching the connection.
+    ///
+    /// Its type is not subscribed to again on later reconnects.
+    pub async fn unsubscribe(&self, id: &str) -> Result<(), Error> {
+        let response = self
+            .api_request(Method::DELETE)
+            .await?
+            .query(&[("id", id)])
+            .send()
+            .await?;
+        check_status(response).await?;
+
+        match self.subscriptions.remove(id) {
+            Some(sub_type) => info!("unsubscribed from {}", sub_type),
+            None => info!("unsubscribed from {}", id),
+        }
+        Ok(())
+    }
+
+    /// Every subscription Twitch holds for this client ID, across all
+    /// sessions, as reported by Helix. A subscription whose `status` is not
+    /// `enabled` delivers nothing, e.g. after its token lost a scope.
+    pub async fn list_subscriptions(&self) -> Result<Vec<SubscriptionInfo>, Error> {
+        let mut subscriptions = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let mut request = self.api_request(Method::GET).await?;
+            if let Some(after) = &cursor {
+                request = request.query(&[("after", after)]);
+            }
+
+            let response = check_status(request.send().await?).await?;
+            let page: SubscriptionPage = response.json().await?;
+            subscriptions.extend(page.data);
+            record_cost(&self.subscriptions, page.cost);
+
+            cursor = page.pagination.and_then(|pagination| pagination.cursor);
+            if cursor.is_none() {
+                return Ok(subscriptions);
+            }
+        }
+    }
+
+    async fn api_request(&self, method: Method) -> Result<RequestBuilder, Error> {
+        let token = self.token_manager.get_token().await?;
+        let api_token = token.strip_prefix("oauth:").unwrap_or(&token);
+
+        Ok(self
+            .client
+            .request(method, &self.api_url)
+            .timeout(self.request_timeout)
+            .header("Authorization", format!("Bearer {}", api_token))
+            .header("Client-Id", &self.client_id))
+    }
+
+    pub async fn shutdown(&self) -> Result<(), Error> {
         self.cancel_token.cancel();
-        if let Some(handle) = self.handle.take() {
+        let handle = self.handle().take();
+        if let Some(handle) = handle {
             handle.await?;
         }
 
         Ok(())
     }
+
+    fn handle(&self) -> MutexGuard<'_, Option<JoinHandle<()>>> {
+        self.handle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Turns a non-success Helix response into [`Error::Api`].
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    Err(Error::Api {
+        status: status.as_u16(),
+        message: response.text().await.unwrap_or_default(),
+    })
 }
 
 async fn run_lifecycle(params: EventSubLifecycleParams) -> Result<()> {
@@ -164,10 +438,21 @@ async fn run_lifecycle(params: EventSubLifecycleParams) -> Result<()> {
         broadcaster_id,
         bot_user_id,
         client_id,
+        topics,
+        required,
+        ws_url,
+        api_url,
+        request_timeout,
+        emit_unknown,
+        emit_connected,
+        keepalive_buffer,
+        dedup_window,
+        recorder,
+        subscriptions,
         cancel_token,
     } = params;
 
-    let url = Url::parse(EVENTSUB_WS_URL)?;
+    let url = Url::parse(&ws_url)?;
     info!("connecting to EventSub: {}", url);
     let (mut ws_stream, _) = connect_async(url.to_string())
         .await
@@ -175,25 +460,85 @@ async fn run_lifecycle(params: EventSubLifecycleParams) -> Result<()> {
 
     let session = receive_welcome(&mut ws_stream).await?;
     info!("EventSub session established: {}", session.id);
+    subscriptions.clear();
 
     let token = token_manager.get_token().await?;
     let api_token = token.strip_prefix("oauth:").unwrap_or(&token);
 
-    subscribe_to_rewards(&client, &client_id, api_token, &broadcaster_id, &session.id).await?;
-    subscribe_to_chat(
-        &client,
-        &client_id,
-        api_token,
-        &broadcaster_id,
-        &bot_user_id,
-        &session.id,
-    )
-    .await?;
-
-    let keepalive_timeout =
-        Duration::from_secs(session.keepalive_timeout_seconds + KEEPALIVE_TIMEOUT_BUFFER_SECS);
-
-    run_eventsub_loop(ws_stream, event_tx, cancel_token, keepalive_timeout).await
+    let subscriber = Subscriber {
+        client: &client,
+        api_url: &api_url,
+        request_timeout,
+        client_id: &client_id,
+        access_token: api_token,
+        session_id: &session.id,
+        subscriptions: &subscriptions,
+    };
+
+    let mut failed = Vec::new();
+    for (sub_type, condition) in subscription_plan(&broadcaster_id, &bot_user_id, &topics) {
+        if let Err(e) = create_subscription(&subscriber, sub_type, condition).await {
+            if required.contains(sub_type) {
+                return Err(e.context(format!("required subscription {sub_type} failed")));
+            }
+            warn!("skipping {}: {:?}", sub_type, e);
+            failed.push(sub_type);
+        }
+    }
+    if !failed.is_empty() {
+        if subscriptions.ids().is_empty() {
+            anyhow::bail!("every EventSub subscription failed");
+        }
+        warn!("EventSub running without {}", failed.join(", "));
+    }
+
+    if emit_connected {
+        let connected = TwitchEvent::Connected {
+            session_id: Some(session.id.clone()),
+        };
+        event_tx.send(connected).await?;
+    }
+
+    let mut keepalive_timeout =
+        Duration::from_secs(session.keepalive_timeout_seconds) + keepalive_buffer;
+    let mut seen = SeenMessages::new(dedup_window);
+
+    loop {
+        let reconnect_url = run_eventsub_loop(
+            &mut ws_stream,
+            &event_tx,
+            &cancel_token,
+            keepalive_timeout,
+            emit_unknown,
+            &mut seen,
+            recorder.as_ref(),
+        )
+        .await?;
+
+        let Some(reconnect_url) = reconnect_url else {
+            return Ok(());
+        };
+
+        // subscriptions move to the new session, so only the socket is swapped;
+        // an error here falls back to a cold reconnect that re-subscribes
+        let (new_stream, session) = connect_to_reconnect_url(&reconnect_url).await?;
+        info!("EventSub session moved: {}", session.id);
+
+        let _ = ws_stream.close(None).await;
+        ws_stream = new_stream;
+        keepalive_timeout =
+            Duration::from_secs(session.keepalive_timeout_seconds) + keepalive_buffer;
+    }
+}
+
+async fn connect_to_reconnect_url(url: &str) -> Result<(WsStream, Session)> {
+    info!("reconnecting to EventSub: {}", url);
+    let (mut ws_stream, _) = connect_async(url)
+        .await
+        .context("EventSub reconnect connection failed")?;
+
+    let session = receive_welcome(&mut ws_stream).await?;
+    Ok((ws_stream, session))
 }
 
 async fn receive_welcome(ws: &mut WsStream) -> Result<Session> {
@@ -242,98 +587,177 @@ async fn receive_welcome(ws: &mut WsStream) -> Result<Session> {
     }
 }
 
-async fn subscribe_to_rewards(
-    client: &Client,
-    client_id: &str,
-    access_token: &str,
+/// Everything a subscription request needs besides its type and condition.
+struct Subscriber<'a> {
+    client: &'a Client,
+    api_url: &'a str,
+    request_timeout: Duration,
+    client_id: &'a str,
+    access_token: &'a str,
+    session_id: &'a str,
+    subscriptions: &'a ActiveSubscriptions,
+}
+
+/// Every subscription a cold connect creates, as type and condition.
+fn subscription_plan(
     broadcaster_id: &str,
-    session_id: &str,
+    bot_user_id: &str,
+    topics: &[EventSubTopic],
+) -> Vec<(&'static str, serde_json::Value)> {
+    let broadcaster = serde_json::json!({ "broadcaster_user_id": broadcaster_id });
+    let chat = serde_json::json!({
+        "broadcaster_user_id": broadcaster_id,
+        "user_id": bot_user_id
+    });
+
+    let mut plan = vec![
+        (
+            "channel.channel_points_custom_reward_redemption.add",
+            broadcaster.clone(),
+        ),
+        ("channel.chat.message", chat.clone()),
+    ];
+    let raided = serde_json::json!({ "to_broadcaster_user_id": broadcaster_id });
+    let whispered = serde_json::json!({ "user_id": bot_user_id });
+
+    for topic in topics {
+        let (sub_types, condition): (&[&'static str], _) = match topic {
+            EventSubTopic::Subscriptions => (
+                &["channel.subscribe", "channel.subscription.message"],
+                &broadcaster,
+            ),
+            EventSubTopic::Moderation => (&["channel.ban"], &broadcaster),
+            EventSubTopic::StreamStatus => (&["stream.online", "stream.offline"], &broadcaster),
+            EventSubTopic::ChatSettings => (&["channel.chat_settings.update"], &chat),
+            EventSubTopic::Raids => (&["channel.raid"], &raided),
+            EventSubTopic::Whispers => (&["user.whisper.message"], &whispered),
+        };
+        plan.extend(
+            sub_types
+                .iter()
+                .map(|sub_type| (*sub_type, condition.clone())),
+        );
+    }
+
+    plan
+}
+
+async fn create_subscription(
+    subscriber: &Subscriber<'_>,
+    sub_type: &str,
+    condition: serde_json::Value,
 ) -> Result<()> {
+    if subscriber.subscriptions.is_removed(sub_type) {
+        debug!("skipping unsubscribed {}", sub_type);
+        return Ok(());
+    }
+
     let request = SubscriptionRequest {
-        sub_type: "channel.channel_points_custom_reward_redemption.add".to_string(),
+        sub_type: sub_type.to_string(),
         version: "1".to_string(),
-        condition: serde_json::json!({
-            "broadcaster_user_id": broadcaster_id
-        }),
+        condition,
         transport: Transport {
             method: "websocket".to_string(),
-            session_id: session_id.to_string(),
+            session_id: subscriber.session_id.to_string(),
         },
     };
 
-    let response = client
-        .post(EVENTSUB_API_URL)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("Client-Id", client_id)
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await?;
+    // a reconnect would only hit the same limit again, so wait it out here
+    let mut attempt = 1;
+    let response = loop {
+        let response = subscriber
+            .client
+            .post(subscriber.api_url)
+            .timeout(subscriber.request_timeout)
+            .header(
+                "Authorization",
+                format!("Bearer {}", subscriber.access_token),
+            )
+            .header("Client-Id", subscriber.client_id)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= SUBSCRIBE_ATTEMPTS {
+            break response;
+        }
+        let delay = rate_limit_delay(response.headers(), SystemTime::now());
+        warn!(
+            "rate limited subscribing to {}, retrying in {:?}",
+            sub_type, delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    };
 
     if response.status().is_success() {
-        info!("subscribed to channel.channel_points_custom_reward_redemption.add");
+        let created: CreatedSubscriptions = response
+            .json()
+            .await
+            .with_context(|| format!("Invalid response subscribing to {}", sub_type))?;
+        if let Some(subscription) = created.data.into_iter().next() {
+            subscriber.subscriptions.insert(sub_type, subscription.id);
+        }
+        record_cost(subscriber.subscriptions, created.cost);
+        info!("subscribed to {}", sub_type);
         Ok(())
     } else {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
         Err(anyhow::anyhow!(
-            "Failed to subscribe: {} - {}",
+            "Failed to subscribe to {}: {} - {}",
+            sub_type,
             status,
             body
         ))
     }
 }
 
-async fn subscribe_to_chat(
-    client: &Client,
-    client_id: &str,
-    access_token: &str,
-    broadcaster_id: &str,
-    bot_user_id: &str,
-    session_id: &str,
-) -> Result<()> {
-    let request = SubscriptionRequest {
-        sub_type: "channel.chat.message".to_string(),
-        version: "1".to_string(),
-        condition: serde_json::json!({
-            "broadcaster_user_id": broadcaster_id,
-            "user_id": bot_user_id
-        }),
-        transport: Transport {
-            method: "websocket".to_string(),
-            session_id: session_id.to_string(),
-        },
+/// How long to wait after a 429, from `Retry-After` (seconds) or Helix's
+/// `Ratelimit-Reset` (a Unix timestamp), capped so the session survives.
+fn rate_limit_delay(headers: &HeaderMap, now: SystemTime) -> Duration {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
     };
 
-    let response = client
-        .post(EVENTSUB_API_URL)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("Client-Id", client_id)
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await?;
+    let retry_after = header(RETRY_AFTER.as_str()).map(Duration::from_secs);
+    let reset = header("ratelimit-reset").map(|reset| {
+        (UNIX_EPOCH + Duration::from_secs(reset))
+            .duration_since(now)
+            .unwrap_or_default()
+    });
+
+    retry_after
+        .or(reset)
+        .unwrap_or(RATE_LIMIT_FALLBACK)
+        .min(MAX_RATE_LIMIT_WAIT)
+}
 
-    if response.status().is_success() {
-        info!("subscribed to channel.chat.message");
-        Ok(())
-    } else {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        Err(anyhow::anyhow!(
-            "Failed to subscribe to chat: {} - {}",
-            status,
-            body
-        ))
+fn record_cost(subscriptions: &ActiveSubscriptions, cost: SubscriptionCost) {
+    if cost.is_near_limit() {
+        warn!(
+            "EventSub subscription cost is {} of {}, near the limit",
+            cost.total_cost, cost.max_total_cost
+        );
     }
+    subscriptions.set_cost(cost);
 }
 
+/// Reads messages until the connection ends. Returns the URL to move the session
+/// to when Twitch asks for a reconnect.
 async fn run_eventsub_loop(
-    mut ws: WsStream,
-    event_tx: mpsc::Sender<TwitchEvent>,
-    cancel_token: CancellationToken,
+    ws: &mut WsStream,
+    event_tx: &mpsc::Sender<TwitchEvent>,
+    cancel_token: &CancellationToken,
     keepalive_timeout: Duration,
-) -> Result<()> {
+    emit_unknown: bool,
+    seen: &mut SeenMessages,
+    recorder: Option<&RawRecorder>,
+) -> Result<Option<String>> {
     loop {
         tokio::select! {
             biased;
@@ -341,20 +765,25 @@ async fn run_eventsub_loop(
             _ = cancel_token.cancelled() => {
                 info!("EventSub loop cancelled");
                 let _ = ws.close(None).await;
-                return Ok(());
+                return Ok(None);
             }
 
             result = tokio::time::timeout(keepalive_timeout, ws.next()) => {
                 match result {
                     Ok(Some(Ok(msg))) => {
-                        handle_eventsub_message(msg, &event_tx).await?;
+                        if let (Some(recorder), Message::Text(text)) = (recorder, &msg) {
+                            recorder.record_json(text);
+                        }
+                        if let Some(url) = handle_eventsub_message(msg, event_tx, emit_unknown, seen).await? {
+                            return Ok(Some(url));
+                        }
                     }
                     Ok(Some(Err(e))) => {
                         return Err(anyhow::anyhow!("WebSocket error: {}", e));
                     }
                     Ok(None) => {
                         info!("EventSub WebSocket closed");
-                        return Ok(());
+                        return Ok(None);
                     }
                     Err(_) => {
                         warn!("EventSub keepalive timeout, reconnecting...");
@@ -366,7 +795,12 @@ async fn run_eventsub_loop(
     }
 }
 
-async fn handle_eventsub_message(msg: Message, event_tx: &mpsc::Sender<TwitchEvent>) -> Result<()> {
+async fn handle_eventsub_message(
+    msg: Message,
+    event_tx: &mpsc::Sender<TwitchEvent>,
+    emit_unknown: bool,
+    seen: &mut SeenMessages,
+) -> Result<Option<String>> {
     let text = match msg {
         Message::Text(t) => t,
         Message::Close(_) => {
@@ -375,24 +809,44 @@ async fn handle_eventsub_message(msg: Message, event_tx: &mpsc::Sender<TwitchEve
         }
         Message::Ping(_data) => {
             debug!("EventSub PING received");
-            return Ok(());
+            return Ok(None);
         }
-        _ => return Ok(()),
+        _ => return Ok(None),
     };
 
-    let parsed: EventSubMessage =
-        serde_json::from_str(&text).context("Failed to parse EventSub message")?;
+    // one bad frame shouldn't cost the session; only close frames and a
+    // dropped receiver end the connection
+    let parsed: EventSubMessage = match serde_json::from_str(&text) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            warn!(
+                "skipping unparseable EventSub message ({} bytes): {}",
+                text.len(),
+                e
+            );
+            return Ok(None);
+        }
+    };
 
     match parsed.metadata.message_type.as_str() {
         "session_keepalive" => {
             debug!("EventSub keepalive");
         }
         "notification" => {
-            handle_notification(&parsed, event_tx).await?;
+            if !seen.insert(&parsed.metadata.message_id) {
+                debug!(
+                    "skipping duplicate notification {}",
+                    parsed.metadata.message_id
+                );
+                return Ok(None);
+            }
+            handle_notification(&parsed, event_tx, emit_unknown).await?;
         }
         "session_reconnect" => {
+            let payload: ReconnectPayload = serde_json::from_value(parsed.payload)
+                .context("Failed to parse reconnect payload")?;
             warn!("EventSub requested reconnect");
-            return Err(anyhow::anyhow!("reconnect requested"));
+            return Ok(Some(payload.session.reconnect_url));
         }
         "revocation" => {
             warn!("EventSub subscription revoked");
@@ -402,7 +856,7 @@ async fn handle_eventsub_message(msg: Message, event_tx: &mpsc::Sender<TwitchEve
         }
     }
 
-    Ok(())
+    Ok(None)
 }
 
 fn determine_role_from_badges(badges: &[ChatBadge]) -> TwitchRole {
@@ -422,10 +876,49 @@ fn determine_role_from_badges(badges: &[ChatBadge]) -> TwitchRole {
 async fn handle_notification(
     msg: &EventSubMessage,
     event_tx: &mpsc::Sender<TwitchEvent>,
+    emit_unknown: bool,
 ) -> Result<()> {
+    let event = match notification_event(msg, emit_unknown) {
+        Ok(Some(event)) => event,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            warn!(
+                "skipping malformed {} notification {}: {:#}",
+                msg.metadata
+                    .subscription_type
+                    .as_deref()
+                    .unwrap_or("unknown"),
+                msg.metadata.message_id,
+                e
+            );
+            return Ok(());
+        }
+    };
+
+    if event_tx.send(event).await.is_err() {
+        return Err(anyhow::anyhow!("event receiver dropped"));
+    }
+
+    Ok(())
+}
+
+/// Parses a raw EventSub WebSocket message. Only notifications of a supported
+/// subscription type produce an event.
+pub fn parse_eventsub_message(text: &str) -> Result<Option<TwitchEvent>> {
+    let parsed: EventSubMessage =
+        serde_json::from_str(text).context("Failed to parse EventSub message")?;
+
+    if parsed.metadata.message_type != "notification" {
+        return Ok(None);
+    }
+
+    notification_event(&parsed, false)
+}
+
+fn notification_event(msg: &EventSubMessage, emit_unknown: bool) -> Result<Option<TwitchEvent>> {
     let sub_type = msg.metadata.subscription_type.as_deref().unwrap_or("");
 
-    match sub_type {
+    let event = match sub_type {
         "channel.channel_points_custom_reward_redemption.add" => {
             let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
             let redemption: RewardRedemptionEvent = serde_json::from_value(payload.event)?;
@@ -442,15 +935,18 @@ async fn handle_notification(
                 user_input: redemption.user_input,
             };
 
-            if event_tx.send(event).await.is_err() {
-                return Err(anyhow::anyhow!("event receiver dropped"));
-            }
+            Some(event)
         }
         "channel.chat.message" => {
             let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
             let chat_msg: ChatMessageEvent = serde_json::from_value(payload.event)?;
 
             let role = determine_role_from_badges(&chat_msg.badges);
+            let is_first_message = chat_msg.is_first_message();
+            let color = chat_msg.color();
+            let badge_info = chat_msg.badge_info();
+            let (text, is_action) = split_action(&chat_msg.message.text);
+            let text = text.to_string();
 
             let event = TwitchEvent::ChatMessage {
                 user: TwitchUser {
@@ -462,23 +958,162 @@ async fn handle_notification(
                     broadcaster_id: Some(chat_msg.broadcaster_user_id),
                     channel_login: Some(chat_msg.broadcaster_user_login),
                 },
-                text: chat_msg.message.text,
+                text,
+                message_id: chat_msg.message_id,
+                fragments: Some(
+                    chat_msg
+                        .message
+                        .fragments
+                        .into_iter()
+                        .map(MessageFragment::from)
+                        .collect(),
+                ),
+                emotes: None,
+                is_first_message,
+                is_action,
+                color,
+                badge_info,
             };
 
-            if event_tx.send(event).await.is_err() {
-                return Err(anyhow::anyhow!("event receiver dropped"));
-            }
+            Some(event)
+        }
+        "channel.chat_settings.update" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let settings: ChatSettingsUpdateEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::ChatSettingsUpdate {
+                emote_mode: settings.emote_mode,
+                follower_mode: settings.follower_mode,
+                follower_mode_duration: settings.follower_mode_duration_minutes,
+                slow_mode: settings.slow_mode,
+                slow_mode_wait_time: settings.slow_mode_wait_time_seconds,
+                subscriber_mode: settings.subscriber_mode,
+                unique_chat_mode: settings.unique_chat_mode,
+            };
+
+            Some(event)
+        }
+        "channel.raid" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let raid: RaidEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::Raid {
+                from_broadcaster: TwitchUser {
+                    id: raid.from_broadcaster_user_id,
+                    display_name: raid.from_broadcaster_user_name,
+                    role: TwitchRole::empty(),
+                },
+                viewers: raid.viewers,
+            };
+
+            Some(event)
+        }
+        "user.whisper.message" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let whisper: WhisperMessageEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::Whisper {
+                user: TwitchUser {
+                    id: whisper.from_user_id,
+                    display_name: whisper.from_user_name,
+                    role: TwitchRole::empty(),
+                },
+                text: whisper.whisper.text,
+            };
+
+            Some(event)
+        }
+        "channel.subscribe" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let sub: SubscribeEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::Subscription {
+                user: TwitchUser {
+                    id: sub.user_id.unwrap_or_default(),
+                    display_name: sub
+                        .user_name
+                        .or(sub.user_login)
+                        .unwrap_or_else(|| "anonymous".to_string()),
+                    role: TwitchRole::SUBSCRIBER,
+                },
+                tier: sub.tier,
+                is_gift: sub.is_gift,
+            };
+
+            Some(event)
+        }
+        "channel.subscription.message" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let resub: SubscriptionMessageEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::ResubMessage {
+                user: TwitchUser {
+                    id: resub.user_id,
+                    display_name: resub.user_name,
+                    role: TwitchRole::SUBSCRIBER,
+                },
+                cumulative_months: resub.cumulative_months,
+                streak: resub.streak_months,
+                text: resub.message.text,
+            };
+
+            Some(event)
+        }
+        "channel.ban" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let ban: BanEvent = serde_json::from_value(payload.event)?;
+            let duration = ban.timeout_duration()?;
+
+            let event = TwitchEvent::Ban {
+                target: TwitchUser {
+                    id: ban.user_id,
+                    display_name: ban.user_name,
+                    role: TwitchRole::empty(),
+                },
+                moderator: TwitchUser {
+                    id: ban.moderator_user_id,
+                    display_name: ban.moderator_user_name,
+                    role: TwitchRole::MODERATOR,
+                },
+                reason: ban.reason,
+                duration,
+            };
+
+            Some(event)
+        }
+        "stream.online" => {
+            let payload: NotificationPayload = serde_json::from_value(msg.payload.clone())?;
+            let online: StreamOnlineEvent = serde_json::from_value(payload.event)?;
+
+            let event = TwitchEvent::StreamOnline {
+                started_at: online.started_at()?,
+                stream_type: online.stream_type,
+            };
+
+            Some(event)
+        }
+        "stream.offline" => Some(TwitchEvent::StreamOffline),
+        other if emit_unknown => {
+            let event = TwitchEvent::Unknown {
+                subscription_type: other.to_string(),
+                payload: msg.payload.clone(),
+            };
+
+            Some(event)
         }
         other => {
             debug!("Unhandled notification type: {}", other);
+            None
         }
-    }
+    };
 
-    Ok(())
+    Ok(event)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::UNIX_EPOCH;
+
     use super::*;
 
     fn make_role(roles: &[TwitchRole]) -> TwitchRole {
@@ -495,6 +1130,7 @@ mod tests {
             ids.iter()
                 .map(|&id| ChatBadge {
                     set_id: id.to_string(),
+                    info: String::new(),
                 })
                 .collect::<Vec<_>>()
         };
@@ -524,6 +1160,10 @@ mod tests {
             TwitchRole::SUBSCRIBER
         );
 
+        let moderator_only = determine_role_from_badges(&make_badges(&["moderator"]));
+        assert_eq!(moderator_only, TwitchRole::MODERATOR);
+        assert!(moderator_only.at_least(TwitchRole::VIP));
+
         assert_eq!(
             determine_role_from_badges(&make_badges(&["no_audio"])),
             TwitchRole::empty()
@@ -655,5 +1295,689 @@ mod tests {
         expected_role.add(TwitchRole::SUBSCRIBER);
 
         assert_eq!(actual_role, expected_role);
+
+        assert_eq!(event.color(), Some("#0000FF".to_string()));
+        assert_eq!(
+            event.badge_info(),
+            vec![("subscriber".to_string(), "12".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_chat_settings_update() {
+        let json = r#"{
+            "metadata": {
+                "message_id": "befa7b53-d79d-478f-86b9-120f112b044e",
+                "message_type": "notification",
+                "message_timestamp": "2022-11-16T10:11:12.464757833Z",
+                "subscription_type": "channel.chat_settings.update",
+                "subscription_version": "1"
+            },
+            "payload": {
+                "subscription": {
+                    "id": "f1c2a387-161a-49f9-a165-0f21d7a4e1c4",
+                    "type": "channel.chat_settings.update",
+                    "version": "1",
+                    "status": "enabled",
+                    "cost": 0,
+                    "condition": {
+                        "broadcaster_user_id": "1337",
+                        "user_id": "9001"
+                    },
+                    "transport": {
+                        "method": "websocket",
+                        "session_id": "AQoQexAWVYKSTIu4ec_2VAxyuhAB"
+                    },
+                    "created_at": "2023-04-11T10:11:12.123Z"
+                },
+                "event": {
+                    "broadcaster_user_id": "1337",
+                    "broadcaster_user_login": "cool_user",
+                    "broadcaster_user_name": "Cool_User",
+                    "emote_mode": true,
+                    "follower_mode": false,
+                    "follower_mode_duration_minutes": null,
+                    "slow_mode": true,
+                    "slow_mode_wait_time_seconds": 10,
+                    "subscriber_mode": false,
+                    "unique_chat_mode": false
+                }
+            }
+        }"#;
+
+        let msg: EventSubMessage = serde_json::from_str(json).expect("failed to parse message");
+        let (tx, mut rx) = mpsc::channel(1);
+        handle_notification(&msg, &tx, false)
+            .await
+            .expect("failed to handle notification");
+
+        match rx.try_recv().expect("expected an event") {
+            TwitchEvent::ChatSettingsUpdate {
+                emote_mode,
+                follower_mode,
+                follower_mode_duration,
+                slow_mode,
+                slow_mode_wait_time,
+                subscriber_mode,
+                unique_chat_mode,
+            } => {
+                assert!(emote_mode);
+                assert!(!follower_mode);
+                assert_eq!(follower_mode_duration, None);
+                assert!(slow_mode);
+                assert_eq!(slow_mode_wait_time, Some(10));
+                assert!(!subscriber_mode);
+                assert!(!unique_chat_mode);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_raid() {
+        let json = r#"{
+            "metadata": {
+                "message_id": "befa7b53-d79d-478f-86b9-120f112b044e",
+                "message_type": "notification",
+                "message_timestamp": "2022-11-16T10:11:12.464757833Z",
+                "subscription_type": "channel.raid",
+                "subscription_version": "1"
+            },
+            "payload": {
+                "subscription": {
+                    "id": "f1c2a387-161a-49f9-a165-0f21d7a4e1c4",
+                    "type": "channel.raid",
+                    "version": "1",
+                    "status": "enabled",
+                    "cost": 0,
+                    "condition": {
+                        "to_broadcaster_user_id": "1337"
+                    },
+                    "transport": {
+                        "method": "websocket",
+                        "session_id": "AQoQexAWVYKSTIu4ec_2VAxyuhAB"
+                    },
+                    "created_at": "2019-11-16T10:11:12.123Z"
+                },
+                "event": {
+                    "from_broadcaster_user_id": "1234",
+                    "from_broadcaster_user_login": "cool_user",
+                    "from_broadcaster_user_name": "Cool_User",
+                    "to_broadcaster_user_id": "1337",
+                    "to_broadcaster_user_login": "cooler_user",
+                    "to_broadcaster_user_name": "Cooler_User",
+                    "viewers": 9001
+                }
+            }
+        }"#;
+
+        let msg: EventSubMessage = serde_json::from_str(json).expect("failed to parse message");
+        let (tx, mut rx) = mpsc::channel(1);
+        handle_notification(&msg, &tx, false)
+            .await
+            .expect("failed to handle notification");
+
+        match rx.try_recv().expect("expected an event") {
+            TwitchEvent::Raid {
+                from_broadcaster,
+                viewers,
+            } => {
+                assert_eq!(from_broadcaster.id, "1234");
+                assert_eq!(from_broadcaster.display_name, "Cool_User");
+                assert_eq!(viewers, 9001);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    fn notification(sub_type: &str, event: &str) -> EventSubMessage {
+        let json = format!(
+            r#"{{
+                "metadata": {{
+                    "message_id": "befa7b53-d79d-478f-86b9-120f112b044e",
+                    "message_type": "notification",
+                    "message_timestamp": "2022-11-16T10:11:12.464757833Z",
+                    "subscription_type": "{sub_type}",
+                    "subscription_version": "1"
+                }},
+                "payload": {{
+                    "subscription": {{
+                        "id": "f1c2a387-161a-49f9-a165-0f21d7a4e1c4",
+                        "type": "{sub_type}",
+                        "version": "1",
+                        "status": "enabled",
+                        "cost": 0,
+                        "condition": {{
+                            "broadcaster_user_id": "1337"
+                        }},
+                        "transport": {{
+                            "method": "websocket",
+                            "session_id": "AQoQexAWVYKSTIu4ec_2VAxyuhAB"
+                        }},
+                        "created_at": "2019-11-16T10:11:12.123Z"
+                    }},
+                    "event": {event}
+                }}
+            }}"#
+        );
+        serde_json::from_str(&json).expect("failed to parse message")
+    }
+
+    async fn parse_notification(msg: &EventSubMessage) -> TwitchEvent {
+        let (tx, mut rx) = mpsc::channel(1);
+        handle_notification(msg, &tx, false)
+            .await
+            .expect("failed to handle notification");
+        rx.try_recv().expect("expected an event")
+    }
+
+    #[tokio::test]
+    async fn test_session_reconnect_yields_reconnect_url() {
+        let msg = Message::Text(
+            r#"{
+                "metadata": {
+                    "message_id": "84c1e79a-2a4b-4c13-ba0b-4312293e9308",
+                    "message_type": "session_reconnect",
+                    "message_timestamp": "2022-11-18T09:10:11.634234626Z"
+                },
+                "payload": {
+                    "session": {
+                        "id": "AQoQexAWVYKSTIu4ec_2VAxyuhAB",
+                        "status": "reconnecting",
+                        "keepalive_timeout_seconds": null,
+                        "reconnect_url": "wss://eventsub.wss.twitch.tv?reconnect=abc",
+                        "connected_at": "2022-11-16T10:11:12.634234626Z"
+                    }
+                }
+            }"#
+            .into(),
+        );
+        let (tx, _rx) = mpsc::channel(1);
+
+        let mut seen = SeenMessages::new(DEDUP_WINDOW);
+
+        let url = handle_eventsub_message(msg, &tx, false, &mut seen)
+            .await
+            .unwrap();
+        assert_eq!(
+            url.as_deref(),
+            Some("wss://eventsub.wss.twitch.tv?reconnect=abc")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_notification_is_emitted_once() {
+        let text = serde_json::to_string(&serde_json::json!({
+            "metadata": {
+                "message_id": "befa7b53-d79d-478f-86b9-120f112b044e",
+                "message_type": "notification",
+                "message_timestamp": "2022-11-16T10:11:12.464757833Z",
+                "subscription_type": "stream.offline",
+                "subscription_version": "1"
+            },
+            "payload": {
+                "event": {
+                    "broadcaster_user_id": "1337"
+                }
+            }
+        }))
+        .unwrap();
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut seen = SeenMessages::new(DEDUP_WINDOW);
+
+        for _ in 0..2 {
+            handle_eventsub_message(Message::Text(text.clone()), &tx, false, &mut seen)
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(rx.try_recv(), Ok(TwitchEvent::StreamOffline)));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_raids_are_opt_in() {
+        let plan = subscription_plan("1337", "42", &[]);
+        assert!(plan.iter().all(|(sub_type, _)| *sub_type != "channel.raid"));
+
+        let plan = subscription_plan("1337", "42", &[EventSubTopic::Raids]);
+        let (_, condition) = plan
+            .iter()
+            .find(|(sub_type, _)| *sub_type == "channel.raid")
+            .expect("raids not subscribed");
+        assert_eq!(condition["to_broadcaster_user_id"], "1337");
+    }
+
+    #[test]
+    fn test_chat_settings_are_opt_in() {
+        let types = |topics: &[EventSubTopic]| -> Vec<&str> {
+            subscription_plan("1337", "42", topics)
+                .into_iter()
+                .map(|(sub_type, _)| sub_type)
+                .collect()
+        };
+        assert!(!types(&[]).contains(&"channel.chat_settings.update"));
+
+        let plan = subscription_plan("1337", "42", &[EventSubTopic::ChatSettings]);
+        let (_, condition) = plan
+            .iter()
+            .find(|(sub_type, _)| *sub_type == "channel.chat_settings.update")
+            .expect("chat settings not subscribed");
+        assert_eq!(condition["user_id"], "42");
+    }
+
+    #[test]
+    fn test_rate_limit_delay() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let headers = |pairs: &[(&'static str, &str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, value.parse().unwrap());
+            }
+            headers
+        };
+
+        assert_eq!(
+            rate_limit_delay(&headers(&[("retry-after", "2")]), now),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("ratelimit-reset", "1003")]), now),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("ratelimit-reset", "999")]), now),
+            Duration::ZERO
+        );
+        assert_eq!(
+            rate_limit_delay(&headers(&[("retry-after", "60")]), now),
+            MAX_RATE_LIMIT_WAIT
+        );
+        assert_eq!(rate_limit_delay(&headers(&[]), now), RATE_LIMIT_FALLBACK);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_messages_are_skipped() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut seen = SeenMessages::new(DEDUP_WINDOW);
+
+        let garbage = Message::Text("{\"metadata\": ".repeat(10_000));
+        let url = handle_eventsub_message(garbage, &tx, false, &mut seen)
+            .await
+            .unwrap();
+        assert_eq!(url, None);
+
+        let msg = notification("channel.chat.message", r#"{"chatter_user_id": 42}"#);
+        handle_notification(&msg, &tx, false).await.unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_notification_is_dropped_by_default() {
+        let msg = notification("channel.hype_train.begin", r#"{"level": 2}"#);
+        let (tx, mut rx) = mpsc::channel(1);
+
+        handle_notification(&msg, &tx, false).await.unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_notification_is_emitted_when_enabled() {
+        let msg = notification("channel.hype_train.begin", r#"{"level": 2}"#);
+        let (tx, mut rx) = mpsc::channel(1);
+
+        handle_notification(&msg, &tx, true).await.unwrap();
+        match rx.try_recv().expect("expected an event") {
+            TwitchEvent::Unknown {
+                subscription_type,
+                payload,
+            } => {
+                assert_eq!(subscription_type, "channel.hype_train.begin");
+                assert_eq!(payload["event"]["level"], 2);
+                assert_eq!(payload["subscription"]["type"], "channel.hype_train.begin");
+            }
+            other => panic!("expected an unknown event, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_gifted_subscription() {
+        let msg = notification(
+            "channel.subscribe",
+            r#"{
+                "user_id": "1234",
+                "user_login": "cool_user",
+                "user_name": null,
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cooler_user",
+                "broadcaster_user_name": "Cooler_User",
+                "tier": "1000",
+                "is_gift": true
+            }"#,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::Subscription {
+                user,
+                tier,
+                is_gift,
+            } => {
+                assert_eq!(user.id, "1234");
+                assert_eq!(user.display_name, "cool_user");
+                assert_eq!(tier, "1000");
+                assert!(is_gift);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_resub_message() {
+        let msg = notification(
+            "channel.subscription.message",
+            r#"{
+                "user_id": "1234",
+                "user_login": "cool_user",
+                "user_name": "Cool_User",
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cooler_user",
+                "broadcaster_user_name": "Cooler_User",
+                "tier": "1000",
+                "message": {
+                    "text": "Love the stream! FevziGG",
+                    "emotes": [
+                        {
+                            "begin": 23,
+                            "end": 30,
+                            "id": "302976485"
+                        }
+                    ]
+                },
+                "cumulative_months": 15,
+                "streak_months": 1,
+                "duration_months": 6
+            }"#,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::ResubMessage {
+                user,
+                cumulative_months,
+                streak,
+                text,
+            } => {
+                assert_eq!(user.display_name, "Cool_User");
+                assert_eq!(cumulative_months, 15);
+                assert_eq!(streak, Some(1));
+                assert_eq!(text, "Love the stream! FevziGG");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_whisper() {
+        let msg = notification(
+            "user.whisper.message",
+            r#"{
+                "from_user_id": "423374343",
+                "from_user_login": "glowingfish",
+                "from_user_name": "glowingfish",
+                "to_user_id": "424596340",
+                "to_user_login": "quotrok",
+                "to_user_name": "quotrok",
+                "whisper_id": "some-whisper-id",
+                "whisper": {
+                    "text": "a secret"
+                }
+            }"#,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::Whisper { user, text } => {
+                assert_eq!(user.id, "423374343");
+                assert_eq!(user.display_name, "glowingfish");
+                assert_eq!(text, "a secret");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_whispers_are_opt_in() {
+        let plan = subscription_plan("1337", "42", &[]);
+        assert!(
+            plan.iter()
+                .all(|(sub_type, _)| *sub_type != "user.whisper.message")
+        );
+
+        let plan = subscription_plan("1337", "42", &[EventSubTopic::Whispers]);
+        let (_, condition) = plan
+            .iter()
+            .find(|(sub_type, _)| *sub_type == "user.whisper.message")
+            .expect("whispers not subscribed");
+        assert_eq!(condition["user_id"], "42");
+    }
+
+    fn ban_event(ends_at: &str, is_permanent: bool) -> String {
+        format!(
+            r#"{{
+                "user_id": "1234",
+                "user_login": "cool_user",
+                "user_name": "Cool_User",
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cooler_user",
+                "broadcaster_user_name": "Cooler_User",
+                "moderator_user_id": "1339",
+                "moderator_user_login": "mod_user",
+                "moderator_user_name": "Mod_User",
+                "reason": "Offensive language",
+                "banned_at": "2020-07-15T18:15:11.17106713Z",
+                "ends_at": {ends_at},
+                "is_permanent": {is_permanent}
+            }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_parse_timeout() {
+        let msg = notification(
+            "channel.ban",
+            &ban_event(r#""2020-07-15T18:16:11.17106713Z""#, false),
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::Ban {
+                target,
+                moderator,
+                reason,
+                duration,
+            } => {
+                assert_eq!(target.display_name, "Cool_User");
+                assert_eq!(moderator.display_name, "Mod_User");
+                assert_eq!(reason, "Offensive language");
+                assert_eq!(duration, Some(60));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_permanent_ban() {
+        let msg = notification("channel.ban", &ban_event("null", true));
+
+        match parse_notification(&msg).await {
+            TwitchEvent::Ban { duration, .. } => assert_eq!(duration, None),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream_online() {
+        let msg = notification(
+            "stream.online",
+            r#"{
+                "id": "9001",
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cool_user",
+                "broadcaster_user_name": "Cool_User",
+                "type": "live",
+                "started_at": "2020-10-11T10:11:12.123Z"
+            }"#,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::StreamOnline {
+                started_at,
+                stream_type,
+            } => {
+                let since_epoch = started_at.duration_since(UNIX_EPOCH).unwrap();
+                assert_eq!(since_epoch.as_millis(), 1_602_411_072_123);
+                assert_eq!(stream_type, "live");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_stream_offline() {
+        let msg = notification(
+            "stream.offline",
+            r#"{
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cool_user",
+                "broadcaster_user_name": "Cool_User"
+            }"#,
+        );
+
+        assert!(matches!(
+            parse_notification(&msg).await,
+            TwitchEvent::StreamOffline
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_parse_chat_message_fragments() {
+        let msg = notification(
+            "channel.chat.message",
+            r##"{
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cool_user",
+                "broadcaster_user_name": "Cool_User",
+                "chatter_user_id": "9001",
+                "chatter_user_login": "cooler_user",
+                "chatter_user_name": "Cooler_User",
+                "message_id": "539c3f26-077a-4c28-985a-064b38d61320",
+                "message": {
+                    "text": "hi @cool_user Kappa Cheer100",
+                    "fragments": [
+                        { "type": "text", "text": "hi ", "cheermote": null, "emote": null, "mention": null },
+                        {
+                            "type": "mention",
+                            "text": "@cool_user",
+                            "cheermote": null,
+                            "emote": null,
+                            "mention": { "user_id": "1337", "user_login": "cool_user", "user_name": "Cool_User" }
+                        },
+                        { "type": "text", "text": " ", "cheermote": null, "emote": null, "mention": null },
+                        {
+                            "type": "emote",
+                            "text": "Kappa",
+                            "cheermote": null,
+                            "emote": { "id": "25", "emote_set_id": "0", "owner_id": "0", "format": ["static"] },
+                            "mention": null
+                        },
+                        { "type": "text", "text": " ", "cheermote": null, "emote": null, "mention": null },
+                        {
+                            "type": "cheermote",
+                            "text": "Cheer100",
+                            "cheermote": { "prefix": "cheer", "bits": 100, "tier": 100 },
+                            "emote": null,
+                            "mention": null
+                        }
+                    ]
+                },
+                "color": "#0000FF",
+                "badges": [],
+                "message_type": "text",
+                "cheer": { "bits": 100 },
+                "reply": null,
+                "channel_points_custom_reward_id": null
+            }"##,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::ChatMessage {
+                text,
+                fragments,
+                is_first_message,
+                ..
+            } => {
+                assert_eq!(text, "hi @cool_user Kappa Cheer100");
+                assert!(!is_first_message);
+                let fragments = fragments.expect("EventSub messages carry fragments");
+                assert_eq!(fragments.len(), 6);
+                assert_eq!(fragments[0], MessageFragment::Text("hi ".to_string()));
+                assert_eq!(
+                    fragments[1],
+                    MessageFragment::Mention {
+                        user_id: "1337".to_string(),
+                        text: "@cool_user".to_string(),
+                    }
+                );
+                assert_eq!(
+                    fragments[3],
+                    MessageFragment::Emote {
+                        id: "25".to_string(),
+                        name: "Kappa".to_string(),
+                    }
+                );
+                assert_eq!(
+                    fragments[5],
+                    MessageFragment::Cheermote {
+                        text: "Cheer100".to_string(),
+                        bits: 100,
+                    }
+                );
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_chat_action_message() {
+        let msg = notification(
+            "channel.chat.message",
+            r#"{
+                "broadcaster_user_id": "1337",
+                "broadcaster_user_login": "cool_user",
+                "broadcaster_user_name": "Cool_User",
+                "chatter_user_id": "4145994",
+                "chatter_user_login": "viewer32",
+                "chatter_user_name": "viewer32",
+                "message_id": "cc106a89-1814-919d-454c-f4f2f970aae7",
+                "message": { "text": "\u0001ACTION waves\u0001", "fragments": [] },
+                "color": "",
+                "badges": [],
+                "message_type": "text"
+            }"#,
+        );
+
+        match parse_notification(&msg).await {
+            TwitchEvent::ChatMessage {
+                text,
+                is_action,
+                message_id,
+                ..
+            } => {
+                assert_eq!(text, "waves");
+                assert!(is_action);
+                assert_eq!(
+                    message_id.as_deref(),
+                    Some("cc106a89-1814-919d-454c-f4f2f970aae7")
+                );
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
     }
 }
@@ -0,0 +1,68 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Remembers message IDs seen within a sliding time window.
+///
+/// EventSub may deliver a notification more than once; Twitch recommends
+/// dropping repeats of a `message_id` seen in the last 10 minutes.
+pub(crate) struct SeenMessages {
+    window: Duration,
+    ids: HashSet<String>,
+    order: VecDeque<(Instant, String)>,
+}
+
+impl SeenMessages {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records `id`, returning `false` if it was already seen within the window.
+    pub fn insert(&mut self, id: &str) -> bool {
+        self.insert_at(id, Instant::now())
+    }
+
+    fn insert_at(&mut self, id: &str, now: Instant) -> bool {
+        while let Some((seen_at, _)) = self.order.front() {
+            if now.duration_since(*seen_at) < self.window {
+                break;
+            }
+            if let Some((_, expired)) = self.order.pop_front() {
+                self.ids.remove(&expired);
+            }
+        }
+
+        if !self.ids.insert(id.to_string()) {
+            return false;
+        }
+
+        self.order.push_back((now, id.to_string()));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat_within_window_is_rejected() {
+        let mut seen = SeenMessages::new(Duration::from_secs(600));
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("b"));
+    }
+
+    #[test]
+    fn test_repeat_after_window_is_accepted() {
+        let mut seen = SeenMessages::new(Duration::from_secs(600));
+        let start = Instant::now();
+
+        assert!(seen.insert_at("a", start));
+        assert!(!seen.insert_at("a", start + Duration::from_secs(599)));
+        assert!(seen.insert_at("a", start + Duration::from_secs(600)));
+    }
+}
@@ -1,4 +1,9 @@
 mod client;
+mod dedup;
 mod protocol;
+mod subscriptions;
+mod topic;
 
-pub use client::EventSubClient;
+pub use client::{EventSubClient, parse_eventsub_message};
+pub use subscriptions::{SubscriptionCost, SubscriptionInfo};
+pub use topic::EventSubTopic;
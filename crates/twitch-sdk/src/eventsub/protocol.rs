@@ -1,4 +1,11 @@
+use std::time::SystemTime;
+
+use anyhow::Context;
 use serde::Deserialize;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use super::subscriptions::{SubscriptionCost, SubscriptionInfo};
+use crate::model::MessageFragment;
 
 #[derive(Debug, Deserialize)]
 pub struct EventSubMessage {
@@ -8,6 +15,7 @@ pub struct EventSubMessage {
 
 #[derive(Debug, Deserialize)]
 pub struct MessageMetadata {
+    pub message_id: String,
     pub message_type: String,
     #[serde(default)]
     pub subscription_type: Option<String>,
@@ -24,6 +32,47 @@ pub struct Session {
     pub keepalive_timeout_seconds: u64,
 }
 
+/// Response to creating a subscription; `data` holds the one just created.
+#[derive(Debug, Deserialize)]
+pub struct CreatedSubscriptions {
+    pub data: Vec<CreatedSubscription>,
+    #[serde(flatten)]
+    pub cost: SubscriptionCost,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatedSubscription {
+    pub id: String,
+}
+
+/// One page of `GET eventsub/subscriptions`.
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionPage {
+    pub data: Vec<SubscriptionInfo>,
+    #[serde(flatten)]
+    pub cost: SubscriptionCost,
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pagination {
+    /// Absent on the last page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// Payload of `session_reconnect`, which only carries the URL to move to.
+#[derive(Debug, Deserialize)]
+pub struct ReconnectPayload {
+    pub session: ReconnectSession,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReconnectSession {
+    pub reconnect_url: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct NotificationPayload {
     pub event: serde_json::Value,
@@ -51,16 +100,192 @@ pub struct ChatMessageEvent {
     pub broadcaster_user_login: String,
     pub chatter_user_id: String,
     pub chatter_user_name: String,
+    #[serde(default)]
+    pub message_id: Option<String>,
     pub message: ChatMessage,
     pub badges: Vec<ChatBadge>,
+    #[serde(default)]
+    pub message_type: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl ChatMessageEvent {
+    /// Twitch sends a first-time chatter's introduction as a `user_intro` message.
+    pub fn is_first_message(&self) -> bool {
+        self.message_type.as_deref() == Some("user_intro")
+    }
+
+    /// The chosen color, if any; Twitch sends an empty string otherwise.
+    pub fn color(&self) -> Option<String> {
+        self.color.clone().filter(|color| !color.is_empty())
+    }
+
+    pub fn badge_info(&self) -> Vec<(String, String)> {
+        self.badges
+            .iter()
+            .filter(|badge| !badge.info.is_empty())
+            .map(|badge| (badge.set_id.clone(), badge.info.clone()))
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ChatMessage {
     pub text: String,
+    #[serde(default)]
+    pub fragments: Vec<MessageFragmentData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageFragmentData {
+    #[serde(rename = "type")]
+    pub fragment_type: String,
+    pub text: String,
+    pub cheermote: Option<CheermoteData>,
+    pub emote: Option<EmoteData>,
+    pub mention: Option<MentionData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheermoteData {
+    pub bits: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmoteData {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MentionData {
+    pub user_id: String,
+}
+
+impl From<MessageFragmentData> for MessageFragment {
+    fn from(fragment: MessageFragmentData) -> Self {
+        let MessageFragmentData {
+            fragment_type,
+            text,
+            cheermote,
+            emote,
+            mention,
+        } = fragment;
+
+        match (fragment_type.as_str(), cheermote, emote, mention) {
+            ("cheermote", Some(cheermote), _, _) => MessageFragment::Cheermote {
+                text,
+                bits: cheermote.bits,
+            },
+            ("emote", _, Some(emote), _) => MessageFragment::Emote {
+                id: emote.id,
+                name: text,
+            },
+            ("mention", _, _, Some(mention)) => MessageFragment::Mention {
+                user_id: mention.user_id,
+                text,
+            },
+            _ => MessageFragment::Text(text),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ChatBadge {
     pub set_id: String,
+    #[serde(default)]
+    pub info: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatSettingsUpdateEvent {
+    pub emote_mode: bool,
+    pub follower_mode: bool,
+    pub follower_mode_duration_minutes: Option<u32>,
+    pub slow_mode: bool,
+    pub slow_mode_wait_time_seconds: Option<u32>,
+    pub subscriber_mode: bool,
+    pub unique_chat_mode: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RaidEvent {
+    pub from_broadcaster_user_id: String,
+    pub from_broadcaster_user_name: String,
+    pub viewers: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhisperMessageEvent {
+    pub from_user_id: String,
+    pub from_user_name: String,
+    pub whisper: WhisperBody,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhisperBody {
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubscribeEvent {
+    pub user_id: Option<String>,
+    pub user_login: Option<String>,
+    pub user_name: Option<String>,
+    pub tier: String,
+    pub is_gift: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubscriptionMessageEvent {
+    pub user_id: String,
+    pub user_name: String,
+    pub message: ChatMessage,
+    pub cumulative_months: u32,
+    pub streak_months: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanEvent {
+    pub user_id: String,
+    pub user_name: String,
+    pub moderator_user_id: String,
+    pub moderator_user_name: String,
+    pub reason: String,
+    pub banned_at: String,
+    pub ends_at: Option<String>,
+    pub is_permanent: bool,
+}
+
+impl BanEvent {
+    /// Length of the timeout in seconds, `None` for a permanent ban.
+    pub fn timeout_duration(&self) -> anyhow::Result<Option<u32>> {
+        let ends_at = match &self.ends_at {
+            Some(ends_at) if !self.is_permanent => ends_at,
+            _ => return Ok(None),
+        };
+
+        let banned_at = parse_timestamp(&self.banned_at)?;
+        let ends_at = parse_timestamp(ends_at)?;
+        let seconds = (ends_at - banned_at).whole_seconds().max(0);
+
+        Ok(Some(u32::try_from(seconds).unwrap_or(u32::MAX)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamOnlineEvent {
+    #[serde(rename = "type")]
+    pub stream_type: String,
+    pub started_at: String,
+}
+
+impl StreamOnlineEvent {
+    pub fn started_at(&self) -> anyhow::Result<SystemTime> {
+        parse_timestamp(&self.started_at).map(SystemTime::from)
+    }
+}
+
+fn parse_timestamp(value: &str) -> anyhow::Result<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339).with_context(|| format!("invalid timestamp: {value}"))
 }
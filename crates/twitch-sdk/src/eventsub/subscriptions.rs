@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::Deserialize;
+
+/// A subscription as listed by
+/// [`EventSubClient::list_subscriptions`](super::EventSubClient::list_subscriptions).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[non_exhaustive]
+pub struct SubscriptionInfo {
+    pub id: String,
+    /// E.g. `channel.chat.message`.
+    #[serde(rename = "type")]
+    pub sub_type: String,
+    /// `enabled` when events are delivered; anything else, such as
+    /// `authorization_revoked` or `websocket_disconnected`, means they are not.
+    pub status: String,
+    /// Points counted against the client's `max_total_cost`.
+    pub cost: u32,
+}
+
+/// The client's subscription budget, as of the last create or list response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[non_exhaustive]
+pub struct SubscriptionCost {
+    /// Number of subscriptions, including disabled ones.
+    pub total: u32,
+    pub total_cost: u32,
+    pub max_total_cost: u32,
+}
+
+impl SubscriptionCost {
+    /// Share of the budget, in percent, past which the client warns.
+    pub const WARNING_PERCENT: u32 = 80;
+
+    #[must_use]
+    pub fn remaining(&self) -> u32 {
+        self.max_total_cost.saturating_sub(self.total_cost)
+    }
+
+    /// Always `false` when there is no budget to report, i.e. `max_total_cost` is 0.
+    #[must_use]
+    pub fn is_near_limit(&self) -> bool {
+        self.max_total_cost > 0
+            && u64::from(self.total_cost) * 100
+                >= u64::from(self.max_total_cost) * u64::from(Self::WARNING_PERCENT)
+    }
+}
+
+impl SubscriptionInfo {
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.status == "enabled"
+    }
+}
+
+/// Active subscription IDs keyed by subscription type, shared between an
+/// [`EventSubClient`](super::EventSubClient) and its connection task.
+///
+/// Types removed by ID stay removed: cold reconnects skip them instead of
+/// quietly subscribing again.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActiveSubscriptions {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    ids: HashMap<String, String>,
+    removed: HashSet<String>,
+    cost: Option<SubscriptionCost>,
+}
+
+impl ActiveSubscriptions {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Forgets every ID; subscriptions die with the session they were made on.
+    pub fn clear(&self) {
+        self.state().ids.clear();
+    }
+
+    pub fn is_removed(&self, sub_type: &str) -> bool {
+        self.state().removed.contains(sub_type)
+    }
+
+    pub fn insert(&self, sub_type: &str, id: String) {
+        self.state().ids.insert(sub_type.to_string(), id);
+    }
+
+    /// Drops `id` and marks its type removed, returning that type if `id` was known.
+    pub fn remove(&self, id: &str) -> Option<String> {
+        let mut state = self.state();
+        let sub_type = state
+            .ids
+            .iter()
+            .find(|(_, active)| *active == id)
+            .map(|(sub_type, _)| sub_type.clone())?;
+
+        state.ids.remove(&sub_type);
+        state.removed.insert(sub_type.clone());
+        Some(sub_type)
+    }
+
+    pub fn ids(&self) -> HashMap<String, String> {
+        self.state().ids.clone()
+    }
+
+    pub fn set_cost(&self, cost: SubscriptionCost) {
+        self.state().cost = Some(cost);
+    }
+
+    pub fn cost(&self) -> Option<SubscriptionCost> {
+        self.state().cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removed_type_survives_clear() {
+        let subscriptions = ActiveSubscriptions::default();
+        subscriptions.insert("channel.raid", "a".to_string());
+        subscriptions.insert("stream.online", "b".to_string());
+
+        assert_eq!(subscriptions.remove("a").as_deref(), Some("channel.raid"));
+        assert_eq!(subscriptions.remove("unknown"), None);
+        subscriptions.clear();
+
+        assert!(subscriptions.ids().is_empty());
+        assert!(subscriptions.is_removed("channel.raid"));
+        assert!(!subscriptions.is_removed("stream.online"));
+    }
+
+    #[test]
+    fn test_cost_near_limit() {
+        let cost = |total_cost| SubscriptionCost {
+            total: total_cost,
+            total_cost,
+            max_total_cost: 10,
+        };
+
+        assert!(!cost(7).is_near_limit());
+        assert!(cost(8).is_near_limit());
+        assert_eq!(cost(8).remaining(), 2);
+        assert_eq!(cost(12).remaining(), 0);
+    }
+
+    #[test]
+    fn test_zero_budget_is_never_near_limit() {
+        let cost = SubscriptionCost {
+            total: 0,
+            total_cost: 0,
+            max_total_cost: 0,
+        };
+
+        assert!(!cost.is_near_limit());
+    }
+}
@@ -0,0 +1,28 @@
+/// Optional EventSub topics, subscribed to on top of chat and rewards.
+///
+/// Each costs subscription budget and some need scopes a bot token usually
+/// lacks, so they are opt-in via
+/// [`EventSubClient::with_topic`](super::EventSubClient::with_topic).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventSubTopic {
+    /// `channel.subscribe` and `channel.subscription.message`; needs
+    /// `channel:read:subscriptions` from the broadcaster.
+    Subscriptions,
+    /// `channel.ban`; needs `channel:moderate` from a moderator of the channel.
+    Moderation,
+    /// `stream.online` and `stream.offline`; no scopes needed, but each costs
+    /// one point of the session's subscription budget.
+    StreamStatus,
+    /// `channel.chat_settings.update`, emitted as
+    /// [`TwitchEvent::ChatSettingsUpdate`](crate::TwitchEvent::ChatSettingsUpdate);
+    /// needs `user:read:chat` from the bot.
+    ChatSettings,
+    /// `channel.raid` for raids into the broadcaster's channel, emitted as
+    /// [`TwitchEvent::Raid`](crate::TwitchEvent::Raid); no scopes needed.
+    Raids,
+    /// `user.whisper.message` for whispers to the bot, emitted as
+    /// [`TwitchEvent::Whisper`](crate::TwitchEvent::Whisper); needs
+    /// `user:read:whispers` from the bot.
+    Whispers,
+}
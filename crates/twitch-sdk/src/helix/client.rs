@@ -0,0 +1,385 @@
+use std::sync::Arc;
+
+use reqwest::{Client, RequestBuilder, Response, redirect};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::errors::HelixError;
+use crate::auth::TokenManager;
+use crate::chat::helix_types::{CLIENT_TIMEOUT, CONNECTION_TIMEOUT, REDIRECT_LIMIT};
+use crate::model::{TwitchRole, TwitchUser};
+
+const HELIX_BASE_URL: &str = "https://api.twitch.tv/helix";
+const MAX_USERS_PER_REQUEST: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementColor {
+    /// The channel's accent color.
+    #[default]
+    Primary,
+    Blue,
+    Green,
+    Orange,
+    Purple,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataResponse<T> {
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserData {
+    id: String,
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// Thin client for Helix endpoints, authenticated with the [`TokenManager`]'s token.
+#[non_exhaustive]
+pub struct HelixClient {
+    client_id: String,
+    token_manager: Arc<TokenManager>,
+    client: Client,
+    base_url: String,
+}
+
+impl HelixClient {
+    pub fn new(client_id: &str, token_manager: Arc<TokenManager>) -> Result<Self, HelixError> {
+        let client = Client::builder()
+            .timeout(CLIENT_TIMEOUT)
+            .connect_timeout(CONNECTION_TIMEOUT)
+            .redirect(redirect::Policy::limited(REDIRECT_LIMIT))
+            .build()?;
+
+        Ok(Self {
+            client_id: client_id.to_string(),
+            token_manager,
+            client,
+            base_url: HELIX_BASE_URL.to_string(),
+        })
+    }
+
+    /// Points the client at another Helix-compatible server, e.g. a mock in tests.
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Posts a highlighted announcement. `moderator_id` must match the token's user.
+    pub async fn announce(
+        &self,
+        broadcaster_id: &str,
+        moderator_id: &str,
+        message: &str,
+        color: AnnouncementColor,
+    ) -> Result<(), HelixError> {
+        let request = self
+            .client
+            .post(self.url("chat/announcements"))
+            .query(&[
+                ("broadcaster_id", broadcaster_id),
+                ("moderator_id", moderator_id),
+            ])
+            .json(&json!({ "message": message, "color": color }));
+
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Sends a shoutout for `to_id` in `from_id`'s channel. `moderator_id` must match
+    /// the token's user.
+    pub async fn shoutout(
+        &self,
+        from_id: &str,
+        to_id: &str,
+        moderator_id: &str,
+    ) -> Result<(), HelixError> {
+        let request = self.client.post(self.url("chat/shoutouts")).query(&[
+            ("from_broadcaster_id", from_id),
+            ("to_broadcaster_id", to_id),
+            ("moderator_id", moderator_id),
+        ]);
+
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Deletes one chat message. `moderator_id` must match the token's user.
+    pub async fn delete_chat_message(
+        &self,
+        broadcaster_id: &str,
+        moderator_id: &str,
+        message_id: &str,
+    ) -> Result<(), HelixError> {
+        let request = self.client.delete(self.url("moderation/chat")).query(&[
+            ("broadcaster_id", broadcaster_id),
+            ("moderator_id", moderator_id),
+            ("message_id", message_id),
+        ]);
+
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Times a user out for `duration_secs` (Twitch allows 1 to 1 209 600).
+    /// `moderator_id` must match the token's user.
+    pub async fn timeout_user(
+        &self,
+        broadcaster_id: &str,
+        moderator_id: &str,
+        user_id: &str,
+        duration_secs: u32,
+        reason: &str,
+    ) -> Result<(), HelixError> {
+        let request = self
+            .client
+            .post(self.url("moderation/bans"))
+            .query(&[
+                ("broadcaster_id", broadcaster_id),
+                ("moderator_id", moderator_id),
+            ])
+            .json(&json!({
+                "data": { "user_id": user_id, "duration": duration_secs, "reason": reason }
+            }));
+
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Resolves logins to users, batching requests to stay within Helix's limit of
+    /// 100 logins per call. Unknown logins are left out of the result.
+    pub async fn get_users(&self, logins: &[&str]) -> Result<Vec<TwitchUser>, HelixError> {
+        let mut users = Vec::with_capacity(logins.len());
+
+        for batch in logins.chunks(MAX_USERS_PER_REQUEST) {
+            let query: Vec<_> = batch.iter().map(|login| ("login", *login)).collect();
+            let request = self.client.get(self.url("users")).query(&query);
+
+            let response: DataResponse<UserData> = self.send(request).await?.json().await?;
+            users.extend(response.data.into_iter().map(|user| TwitchUser {
+                id: user.id,
+                display_name: user.display_name,
+                role: TwitchRole::empty(),
+            }));
+        }
+
+        Ok(users)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, HelixError> {
+        let token = self.token_manager.get_token().await?;
+        let access_token = token.strip_prefix("oauth:").unwrap_or(&token);
+
+        let response = request
+            .bearer_auth(access_token)
+            .header("Client-Id", &self.client_id)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<ErrorBody>(&body)
+            .map(|error| error.message)
+            .unwrap_or(body);
+
+        Err(HelixError::Api {
+            status: status.as_u16(),
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    struct Recorded {
+        head: String,
+        body: String,
+    }
+
+    /// Serves one request with the given status line and JSON body, returning what was received.
+    async fn serve_once(listener: &TcpListener, status: &str, body: &str) -> Recorded {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+
+        let header_end = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+        let content_length = head
+            .lines()
+            .find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or(0);
+
+        while request.len() < header_end + content_length {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+
+        let response = format!(
+            "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+
+        Recorded {
+            head,
+            body: String::from_utf8_lossy(&request[header_end..]).to_string(),
+        }
+    }
+
+    async fn make_client() -> (HelixClient, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let token_manager = Arc::new(TokenManager::new(
+            "test_client_id".to_string(),
+            "test_secret".to_string(),
+            "test_refresh".to_string(),
+        ));
+        token_manager
+            .set_token_for_test("oauth:test_token".to_string())
+            .await;
+
+        let client = HelixClient::new("test_client_id", token_manager)
+            .unwrap()
+            .with_base_url(format!("http://{}", listener.local_addr().unwrap()));
+
+        (client, listener)
+    }
+
+    #[tokio::test]
+    async fn test_announce_sends_authorized_request() {
+        let (client, listener) = make_client().await;
+        let server = tokio::spawn(async move { serve_once(&listener, "204 No Content", "").await });
+
+        client
+            .announce("1337", "9001", "hello", AnnouncementColor::Purple)
+            .await
+            .unwrap();
+
+        let recorded = server.await.unwrap();
+        assert!(recorded.head.starts_with(
+            "POST /chat/announcements?broadcaster_id=1337&moderator_id=9001 HTTP/1.1"
+        ));
+        assert!(
+            recorded
+                .head
+                .contains("authorization: Bearer test_token\r\n")
+        );
+        assert!(recorded.head.contains("client-id: test_client_id\r\n"));
+
+        let body: serde_json::Value = serde_json::from_str(&recorded.body).unwrap();
+        assert_eq!(body["message"], "hello");
+        assert_eq!(body["color"], "purple");
+    }
+
+    #[tokio::test]
+    async fn test_non_success_status_is_structured_error() {
+        let (client, listener) = make_client().await;
+        let server = tokio::spawn(async move {
+            serve_once(
+                &listener,
+                "400 Bad Request",
+                r#"{"error":"Bad Request","status":400,"message":"The broadcaster may not give themselves a Shoutout."}"#,
+            )
+            .await
+        });
+
+        let result = client.shoutout("1337", "1337", "1337").await;
+        server.await.unwrap();
+
+        match result {
+            Err(HelixError::Api { status, message }) => {
+                assert_eq!(status, 400);
+                assert_eq!(
+                    message,
+                    "The broadcaster may not give themselves a Shoutout."
+                );
+            }
+            other => panic!("expected an API error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_moderation_requests() {
+        let (client, listener) = make_client().await;
+        let server = tokio::spawn(async move {
+            let delete = serve_once(&listener, "204 No Content", "").await;
+            let timeout = serve_once(&listener, "200 OK", r#"{"data":[]}"#).await;
+            (delete, timeout)
+        });
+
+        client
+            .delete_chat_message("1337", "9001", "abc-123")
+            .await
+            .unwrap();
+        client
+            .timeout_user("1337", "9001", "42", 60, "links")
+            .await
+            .unwrap();
+
+        let (delete, timeout) = server.await.unwrap();
+        assert!(delete.head.starts_with(
+            "DELETE /moderation/chat?broadcaster_id=1337&moderator_id=9001&message_id=abc-123 HTTP/1.1"
+        ));
+        assert!(
+            timeout.head.starts_with(
+                "POST /moderation/bans?broadcaster_id=1337&moderator_id=9001 HTTP/1.1"
+            )
+        );
+        let body: serde_json::Value = serde_json::from_str(&timeout.body).unwrap();
+        assert_eq!(body["data"]["user_id"], "42");
+        assert_eq!(body["data"]["duration"], 60);
+        assert_eq!(body["data"]["reason"], "links");
+    }
+
+    #[tokio::test]
+    async fn test_get_users_batches_logins() {
+        let (client, listener) = make_client().await;
+        let server = tokio::spawn(async move {
+            let user = r#"{"data":[{"id":"1","login":"user0","display_name":"User0"}]}"#;
+            let first = serve_once(&listener, "200 OK", user).await;
+            let second = serve_once(&listener, "200 OK", user).await;
+            (first, second)
+        });
+
+        let logins: Vec<String> = (0..101).map(|i| format!("user{i}")).collect();
+        let logins: Vec<&str> = logins.iter().map(String::as_str).collect();
+        let users = client.get_users(&logins).await.unwrap();
+
+        let (first, second) = server.await.unwrap();
+        let request_line = |head: &str| head.lines().next().unwrap().to_string();
+        assert_eq!(request_line(&first.head).matches("login=").count(), 100);
+        assert!(request_line(&second.head).starts_with("GET /users?login=user100 "));
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].display_name, "User0");
+    }
+}
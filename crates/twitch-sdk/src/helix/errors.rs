@@ -0,0 +1,13 @@
+use crate::auth::AuthError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HelixError {
+    #[error("Failed to get access token: {0}")]
+    FailedGetAccessToken(#[from] AuthError),
+
+    #[error("Helix request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Helix returned {status}: {message}")]
+    Api { status: u16, message: String },
+}
@@ -0,0 +1,5 @@
+mod client;
+mod errors;
+
+pub use client::{AnnouncementColor, HelixClient};
+pub use errors::HelixError;
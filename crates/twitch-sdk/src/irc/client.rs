@@ -1,23 +1,35 @@
-use std::sync::Arc;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, error::SendTimeoutError};
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 use url::Url;
 
-use super::parser::parse_irc_messages;
+use super::parser::{parse_irc_messages_iter, parse_irc_structure};
+use crate::Error;
 use crate::auth::TokenManager;
 use crate::model::TwitchEvent;
+use crate::recorder::RawRecorder;
 
 const TWITCH_WS_URL: &str = "wss://irc-ws.chat.twitch.tv:443";
 const CHANNEL_BUFFER_SIZE: usize = 100;
 const WS_CMD_BUFFER_SIZE: usize = 32;
 const RECONNECT_DELAY_SECS: u64 = 5;
+const EVENT_SEND_TIMEOUT: Duration = Duration::from_secs(1);
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// `NOTICE` texts Twitch answers a bad `PASS` or `NICK` with before closing.
+const LOGIN_FAILURES: [&str; 2] = ["Login authentication failed", "Improperly formatted auth"];
+const ANONYMOUS_NICK: &str = "justinfan12345";
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 type WsWriter = futures_util::stream::SplitSink<WsStream, Message>;
@@ -25,12 +37,28 @@ type WsReader = futures_util::stream::SplitStream<WsStream>;
 
 #[non_exhaustive]
 pub struct IrcClient {
-    token_manager: Arc<TokenManager>,
+    token_manager: Option<Arc<TokenManager>>,
     nick: String,
     channel: String,
     cancel_token: CancellationToken,
     custom_url: Option<String>,
-    handle: Option<JoinHandle<()>>,
+    recorder: Option<RawRecorder>,
+    event_send_timeout: Duration,
+    join_timeout: Duration,
+    emit_connected: bool,
+    dropped_events: Arc<AtomicU64>,
+    handle: Mutex<Option<JoinHandle<Result<(), Error>>>>,
+}
+
+struct IrcLifecycleParams {
+    events: EventSink,
+    token_manager: Option<Arc<TokenManager>>,
+    nick: String,
+    channel: String,
+    cancel_token: CancellationToken,
+    ws_url: String,
+    join_timeout: Duration,
+    recorder: Option<RawRecorder>,
 }
 
 impl Drop for IrcClient {
@@ -42,13 +70,29 @@ impl Drop for IrcClient {
 impl IrcClient {
     #[must_use]
     pub fn new(token_manager: Arc<TokenManager>, nick: String, channel: String) -> Self {
+        Self::build(Some(token_manager), nick, channel)
+    }
+
+    /// A read-only client that joins `channel` as `justinfan12345` without a
+    /// token. Twitch delivers chat to it but rejects anything it sends.
+    #[must_use]
+    pub fn anonymous(channel: String) -> Self {
+        Self::build(None, ANONYMOUS_NICK.to_string(), channel)
+    }
+
+    fn build(token_manager: Option<Arc<TokenManager>>, nick: String, channel: String) -> Self {
         Self {
             token_manager,
             nick,
             channel,
             cancel_token: CancellationToken::new(),
             custom_url: None,
-            handle: None,
+            recorder: None,
+            event_send_timeout: EVENT_SEND_TIMEOUT,
+            join_timeout: JOIN_TIMEOUT,
+            emit_connected: false,
+            dropped_events: Arc::new(AtomicU64::new(0)),
+            handle: Mutex::new(None),
         }
     }
 
@@ -65,13 +109,73 @@ impl IrcClient {
         self
     }
 
+    /// Appends every raw inbound line to the file at `path` before parsing,
+    /// producing a log that can be replayed later.
+    pub fn with_raw_recorder(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        self.recorder = Some(RawRecorder::open(path)?);
+        Ok(self)
+    }
+
+    /// Like [`IrcClient::with_raw_recorder`], but writes to `writer`.
+    #[must_use]
+    pub fn with_raw_writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.recorder = Some(RawRecorder::from_writer(writer));
+        self
+    }
+
+    /// How long the reader waits for room in a full event channel before
+    /// dropping the event. Defaults to one second.
+    #[must_use]
+    pub fn with_event_send_timeout(mut self, timeout: Duration) -> Self {
+        self.event_send_timeout = timeout;
+        self
+    }
+
+    /// How long a new connection waits for Twitch to confirm the `JOIN` before
+    /// it is dropped and retried. Defaults to ten seconds.
+    #[must_use]
+    pub fn with_join_timeout(mut self, timeout: Duration) -> Self {
+        self.join_timeout = timeout;
+        self
+    }
+
+    /// Emits [`TwitchEvent::Connected`] when Twitch confirms the `JOIN` of
+    /// each new connection, so consumers can tell the bot is (back) online.
+    #[must_use]
+    pub fn with_connection_events(mut self) -> Self {
+        self.emit_connected = true;
+        self
+    }
+
     #[must_use]
     pub fn cancel_token(&self) -> CancellationToken {
         self.cancel_token.clone()
     }
 
-    pub async fn connect(&mut self) -> Result<mpsc::Receiver<TwitchEvent>> {
+    /// Events dropped because the receiver stayed full, across reconnects.
+    #[must_use]
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// Starts the connection task and returns its event receiver. Fails with
+    /// [`Error::AlreadyConnected`] while a previous task is still running.
+    ///
+    /// Takes `&self`, so a client shared through an `Arc` can be started and
+    /// shut down without a lock around it.
+    pub async fn connect(&self) -> Result<mpsc::Receiver<TwitchEvent>, Error> {
+        let mut handle = self.handle();
+        if handle.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return Err(Error::AlreadyConnected);
+        }
+
         let (tx, rx) = mpsc::channel(CHANNEL_BUFFER_SIZE);
+        let events = EventSink {
+            tx,
+            timeout: self.event_send_timeout,
+            dropped: self.dropped_events.clone(),
+            emit_connected: self.emit_connected,
+        };
 
         let tm = self.token_manager.clone();
         let nick = self.nick.clone();
@@ -81,8 +185,10 @@ impl IrcClient {
             .custom_url
             .clone()
             .unwrap_or_else(|| TWITCH_WS_URL.to_string());
+        let join_timeout = self.join_timeout;
+        let recorder = self.recorder.clone();
 
-        self.handle = Some(tokio::spawn(async move {
+        *handle = Some(tokio::spawn(async move {
             info!("starting IRC client lifecycle...");
 
             loop {
@@ -94,48 +200,101 @@ impl IrcClient {
                         break;
                     }
 
-                    result = run_lifecycle(
-                        tx.clone(),
-                        tm.clone(),
-                        nick.clone(),
-                        channel.clone(),
-                        cancel.clone(),
-                        url.clone(),
-                    ) => {
+                    result = run_lifecycle(IrcLifecycleParams {
+                        events: events.clone(),
+                        token_manager: tm.clone(),
+                        nick: nick.clone(),
+                        channel: channel.clone(),
+                        cancel_token: cancel.clone(),
+                        ws_url: url.clone(),
+                        join_timeout,
+                        recorder: recorder.clone(),
+                    }) => {
                         if let Err(e) = result {
                             if cancel.is_cancelled() {
                                 info!("IRC client shutdown complete");
                                 break;
                             }
+                            if let Some(Error::LoginFailed(notice)) = e.downcast_ref::<Error>() {
+                                error!("authentication failed, check your token: {}. not reconnecting", notice);
+                                return Err(Error::LoginFailed(notice.clone()));
+                            }
                             error!("twitch connection lost: {:?}. reconnecting in {}s...", e, RECONNECT_DELAY_SECS);
                             tokio::time::sleep(tokio::time::Duration::from_secs(RECONNECT_DELAY_SECS)).await;
                         }
                     }
                 }
             }
+
+            Ok(())
         }));
 
         Ok(rx)
     }
 
-    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+    /// Stops the connection task and waits for it. Returns
+    /// [`Error::LoginFailed`] if the task already stopped because Twitch
+    /// rejected the token.
+    pub async fn shutdown(&self) -> Result<(), Error> {
         self.cancel_token.cancel();
-        if let Some(handle) = self.handle.take() {
-            handle.await?;
+        let handle = self.handle().take();
+        if let Some(handle) = handle {
+            handle.await??;
         }
+
         Ok(())
     }
+
+    fn handle(&self) -> MutexGuard<'_, Option<JoinHandle<Result<(), Error>>>> {
+        self.handle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
 
-async fn run_lifecycle(
-    event_tx: mpsc::Sender<TwitchEvent>,
-    token_manager: Arc<TokenManager>,
-    nick: String,
-    channel: String,
-    cancel_token: CancellationToken,
-    ws_url: String,
-) -> Result<()> {
-    let token = token_manager.get_token().await.context("auth failed")?;
+/// The reader's side of the event channel. A slow consumer costs events, not
+/// the connection: only a dropped receiver is an error.
+#[derive(Clone)]
+struct EventSink {
+    tx: mpsc::Sender<TwitchEvent>,
+    timeout: Duration,
+    dropped: Arc<AtomicU64>,
+    emit_connected: bool,
+}
+
+impl EventSink {
+    async fn send(&self, event: TwitchEvent) -> Result<()> {
+        match self.tx.send_timeout(event, self.timeout).await {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Timeout(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    "event channel full for {:?}, dropped event ({} so far)",
+                    self.timeout, dropped
+                );
+                Ok(())
+            }
+            Err(SendTimeoutError::Closed(_)) => Err(anyhow::anyhow!("event receiver dropped")),
+        }
+    }
+}
+
+async fn run_lifecycle(params: IrcLifecycleParams) -> Result<()> {
+    let IrcLifecycleParams {
+        events,
+        token_manager,
+        nick,
+        channel,
+        cancel_token,
+        ws_url,
+        join_timeout,
+        recorder,
+    } = params;
+
+    let token = match &token_manager {
+        Some(token_manager) => Some(token_manager.get_token().await.context("auth failed")?),
+        None => None,
+    };
 
     let ws_stream = connect_to_url(&ws_url).await?;
     let (write_sink, read_stream) = ws_stream.split();
@@ -144,9 +303,23 @@ async fn run_lifecycle(
     let (writer_error_tx, writer_error_rx) = tokio::sync::oneshot::channel::<()>();
 
     spawn_writer_actor(write_sink, cmd_rx, writer_error_tx);
-    perform_handshake(&cmd_tx, &token, &nick, &channel).await?;
-
-    run_reader_loop(read_stream, event_tx, cmd_tx, cancel_token, writer_error_rx).await?;
+    perform_handshake(&cmd_tx, token.as_deref(), &nick, &channel).await?;
+
+    let join = PendingJoin {
+        nick,
+        deadline: Instant::now() + join_timeout,
+        confirmed: false,
+    };
+    run_reader_loop(
+        read_stream,
+        events,
+        cmd_tx,
+        join,
+        cancel_token,
+        writer_error_rx,
+        recorder,
+    )
+    .await?;
 
     Ok(())
 }
@@ -179,11 +352,13 @@ fn spawn_writer_actor(
 
 async fn perform_handshake(
     cmd_tx: &mpsc::Sender<String>,
-    token: &str,
+    token: Option<&str>,
     nick: &str,
     channel: &str,
 ) -> Result<()> {
-    cmd_tx.send(format!("PASS {}", token)).await?;
+    if let Some(token) = token {
+        cmd_tx.send(format!("PASS {}", token)).await?;
+    }
     cmd_tx.send(format!("NICK {}", nick)).await?;
     cmd_tx
         .send("CAP REQ :twitch.tv/tags twitch.tv/commands".to_string())
@@ -195,10 +370,12 @@ async fn perform_handshake(
 
 async fn run_reader_loop(
     mut stream: WsReader,
-    event_tx: mpsc::Sender<TwitchEvent>,
+    events: EventSink,
     cmd_tx: mpsc::Sender<String>,
+    mut join: PendingJoin,
     cancel_token: CancellationToken,
     mut writer_error_rx: tokio::sync::oneshot::Receiver<()>,
+    recorder: Option<RawRecorder>,
 ) -> Result<()> {
     loop {
         tokio::select! {
@@ -209,6 +386,10 @@ async fn run_reader_loop(
                 return Ok(());
             }
 
+            _ = tokio::time::sleep_until(join.deadline), if !join.confirmed => {
+                return Err(anyhow::anyhow!("no join confirmation from twitch"));
+            }
+
             _ = &mut writer_error_rx => {
                 warn!("writer actor failed, restarting connection");
                 return Err(anyhow::anyhow!("writer actor died"));
@@ -224,7 +405,10 @@ async fn run_reader_loop(
 
                 match msg {
                     Message::Text(text) => {
-                        handle_text_message(&text, &event_tx, &cmd_tx).await?;
+                        if let Some(recorder) = &recorder {
+                            recorder.record_lines(&text);
+                        }
+                        handle_text_message(&text, &events, &cmd_tx, &mut join).await?;
                     }
                     Message::Close(_) => {
                         info!("twitch sent close frame");
@@ -240,8 +424,9 @@ async fn run_reader_loop(
 
 async fn handle_text_message(
     text: &str,
-    event_tx: &mpsc::Sender<TwitchEvent>,
+    events: &EventSink,
     cmd_tx: &mpsc::Sender<String>,
+    join: &mut PendingJoin,
 ) -> Result<()> {
     for pong in text
         .lines()
@@ -251,10 +436,55 @@ async fn handle_text_message(
         cmd_tx.send(pong).await.ok();
     }
 
-    let events = parse_irc_messages(text);
-    for event in events {
-        if event_tx.send(event).await.is_err() {
-            return Err(anyhow::anyhow!("event receiver dropped"));
+    if !join.confirmed {
+        check_join(text, join)?;
+        if join.confirmed && events.emit_connected {
+            events
+                .send(TwitchEvent::Connected { session_id: None })
+                .await?;
+        }
+    }
+
+    for event in parse_irc_messages_iter(text) {
+        events.send(event).await?;
+    }
+
+    Ok(())
+}
+
+/// The `JOIN` sent during the handshake, until Twitch answers it.
+struct PendingJoin {
+    nick: String,
+    deadline: Instant,
+    confirmed: bool,
+}
+
+/// Looks for Twitch's answer to the `JOIN`: the echo of our own `JOIN`,
+/// `ROOMSTATE` or the end of the names list confirm it. A `NOTICE` before
+/// that, e.g. a failed login or a suspended channel, rejects it.
+fn check_join(text: &str, join: &mut PendingJoin) -> Result<()> {
+    for msg in text.lines().filter_map(parse_irc_structure) {
+        let (command, params) = (msg.command, msg.params);
+
+        let own_join = command == "JOIN"
+            && msg
+                .prefix
+                .split_once('!')
+                .is_some_and(|(user, _)| user.eq_ignore_ascii_case(&join.nick));
+        if own_join || command == "ROOMSTATE" || command == "366" {
+            info!("join confirmed");
+            join.confirmed = true;
+            return Ok(());
+        }
+        if command == "NOTICE" {
+            let notice = params.split_once(" :").map_or(params, |(_, text)| text);
+            if LOGIN_FAILURES
+                .iter()
+                .any(|failure| notice.starts_with(failure))
+            {
+                return Err(Error::LoginFailed(notice.to_string()).into());
+            }
+            anyhow::bail!("join rejected: {}", notice);
         }
     }
 
@@ -2,4 +2,4 @@ mod client;
 mod parser;
 
 pub use client::IrcClient;
-pub use parser::parse_irc_messages;
+pub use parser::{parse_irc_messages, parse_irc_messages_iter};
@@ -1,20 +1,31 @@
-use crate::model::{TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser};
+use std::borrow::Cow;
+
+use crate::model::{
+    EmotePositions, TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser, split_action,
+};
 
 pub fn parse_irc_messages(raw: &str) -> Vec<TwitchEvent> {
+    parse_irc_messages_iter(raw).collect()
+}
+
+/// Lazily parses every line of a frame, without collecting the events first.
+pub fn parse_irc_messages_iter(raw: &str) -> impl Iterator<Item = TwitchEvent> + '_ {
     raw.split('\n')
         .map(|line| line.strip_suffix('\r').unwrap_or(line))
         .filter(|line| !line.is_empty())
         .filter_map(parse_line)
-        .collect()
 }
 
-struct IrcMessage<'a> {
-    tags: &'a str,
-    command: &'a str,
-    params: &'a str,
+/// One IRC line split into its parts; `tags` and `prefix` are empty when
+/// absent, and `prefix` is the source without its leading `:`.
+pub(crate) struct IrcMessage<'a> {
+    pub(crate) tags: &'a str,
+    pub(crate) prefix: &'a str,
+    pub(crate) command: &'a str,
+    pub(crate) params: &'a str,
 }
 
-fn parse_irc_structure(line: &str) -> Option<IrcMessage<'_>> {
+pub(crate) fn parse_irc_structure(line: &str) -> Option<IrcMessage<'_>> {
     let line = line.trim();
     if line.is_empty() {
         return None;
@@ -26,16 +37,17 @@ fn parse_irc_structure(line: &str) -> Option<IrcMessage<'_>> {
         ("", line)
     };
 
-    let rest = if let Some(stripped) = rest.strip_prefix(':') {
-        stripped.split_once(' ').map(|(_, r)| r)?
+    let (prefix, rest) = if let Some(stripped) = rest.strip_prefix(':') {
+        stripped.split_once(' ')?
     } else {
-        rest
+        ("", rest)
     };
 
     let (command, params) = rest.split_once(' ')?;
 
     Some(IrcMessage {
         tags,
+        prefix,
         command,
         params,
     })
@@ -46,35 +58,224 @@ fn parse_line(line: &str) -> Option<TwitchEvent> {
 
     match msg.command {
         "PRIVMSG" => parse_privmsg(msg.tags, msg.params),
+        "WHISPER" => parse_whisper(msg.tags, msg.params),
+        "ROOMSTATE" => parse_roomstate(msg.tags, msg.params),
+        "CLEARCHAT" => parse_clearchat(msg.tags, msg.params),
+        "CLEARMSG" => parse_clearmsg(msg.tags, msg.params),
         _ => None,
     }
 }
 
 fn parse_privmsg(tags: &str, params: &str) -> Option<TwitchEvent> {
-    let channel_and_text = params.split_once(" :")?;
+    let channel_and_text = split_trailing(params)?;
     let channel = channel_and_text.0.strip_prefix('#').map(str::to_string);
-    let text = channel_and_text.1.to_string();
+    let (text, is_action) = split_action(channel_and_text.1);
 
-    let meta = parse_tags(tags);
+    let tags = parse_tags(tags);
 
     Some(TwitchEvent::ChatMessage {
         user: TwitchUser {
-            id: meta.user_id.to_string(),
-            display_name: meta.display_name.to_string(),
-            role: meta.role,
+            id: tags.user_id.into_owned(),
+            display_name: tags.display_name.into_owned(),
+            role: tags.role,
         },
         target: TwitchChatTarget {
-            broadcaster_id: None,
+            broadcaster_id: tags.room_id.map(Cow::into_owned),
             channel_login: channel,
         },
-        text,
+        text: text.to_string(),
+        message_id: tags.message_id.map(Cow::into_owned),
+        fragments: None,
+        emotes: tags.emotes.as_deref().map(parse_emotes),
+        is_first_message: tags.is_first_message,
+        is_action,
+        color: tags.color.map(Cow::into_owned),
+        badge_info: tags
+            .badge_info
+            .as_deref()
+            .map(parse_badge_info)
+            .unwrap_or_default(),
+    })
+}
+
+/// Splits `<target> :<text>` into its parts.
+///
+/// An empty body (`#channel :`) is kept as empty text rather than dropping the
+/// line. Without the `:` everything after the target is taken as the text, so
+/// malformed input still comes through; a lone target has no text to emit.
+fn split_trailing(params: &str) -> Option<(&str, &str)> {
+    params.split_once(" :").or_else(|| params.split_once(' '))
+}
+
+/// Decodes `subscriber/12,predictions/blue` into `(set, info)` pairs.
+fn parse_badge_info(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|badge| badge.split_once('/'))
+        .map(|(set, info)| (set.to_string(), info.to_string()))
+        .collect()
+}
+
+/// Reverses IRCv3 tag escaping (`\s`, `\:`, `\\`, `\r`, `\n`).
+///
+/// Per the spec, an unknown escape drops the backslash and a trailing lone
+/// backslash is dropped entirely.
+fn unescape_tag_value(value: &str) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some(':') => unescaped.push(';'),
+            Some('r') => unescaped.push('\r'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+
+    Cow::Owned(unescaped)
+}
+
+/// Decodes `25:0-4,6-10/1902:12-16`, skipping malformed entries.
+fn parse_emotes(value: &str) -> EmotePositions {
+    let mut emotes = EmotePositions::new();
+
+    for emote in value.split('/') {
+        let Some((id, ranges)) = emote.split_once(':') else {
+            continue;
+        };
+
+        let ranges: Vec<_> = ranges
+            .split(',')
+            .filter_map(|range| {
+                let (start, end) = range.split_once('-')?;
+                Some((start.parse().ok()?, end.parse().ok()?))
+            })
+            .collect();
+
+        if !ranges.is_empty() {
+            emotes.entry(id.to_string()).or_default().extend(ranges);
+        }
+    }
+
+    emotes
+}
+
+fn parse_whisper(tags: &str, params: &str) -> Option<TwitchEvent> {
+    let (_recipient, text) = split_trailing(params)?;
+    let tags = parse_tags(tags);
+
+    Some(TwitchEvent::Whisper {
+        user: TwitchUser {
+            id: tags.user_id.into_owned(),
+            display_name: tags.display_name.into_owned(),
+            role: tags.role,
+        },
+        text: text.to_string(),
     })
 }
 
-struct UserMeta<'a> {
-    user_id: &'a str,
-    display_name: &'a str,
+fn parse_roomstate(tags: &str, params: &str) -> Option<TwitchEvent> {
+    let channel = params.trim().strip_prefix('#')?;
+    let flag = |val: &str| match val {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+
+    let mut slow = None;
+    let mut followers_only = None;
+    let mut subs_only = None;
+    let mut emote_only = None;
+    let mut unique_chat = None;
+
+    for pair in tags.split(';') {
+        let Some((key, val)) = pair.split_once('=') else {
+            continue;
+        };
+
+        match key {
+            "slow" => slow = val.parse().ok(),
+            "followers-only" => followers_only = val.parse().ok(),
+            "subs-only" => subs_only = flag(val),
+            "emote-only" => emote_only = flag(val),
+            "r9k" => unique_chat = flag(val),
+            _ => {}
+        }
+    }
+
+    Some(TwitchEvent::RoomState {
+        channel: channel.to_string(),
+        slow,
+        followers_only,
+        subs_only,
+        emote_only,
+        unique_chat,
+    })
+}
+
+fn parse_clearchat(tags: &str, params: &str) -> Option<TwitchEvent> {
+    let (channel, target_user) = match params.split_once(" :") {
+        Some((channel, user)) => (channel, Some(user.to_string())),
+        None => (params.trim(), None),
+    };
+    let duration = tags
+        .split(';')
+        .find_map(|pair| pair.strip_prefix("ban-duration="))
+        .and_then(|val| val.parse().ok());
+
+    Some(TwitchEvent::ClearChat {
+        channel: channel.strip_prefix('#')?.to_string(),
+        target_user,
+        duration,
+    })
+}
+
+fn parse_clearmsg(tags: &str, params: &str) -> Option<TwitchEvent> {
+    let (channel, text) = split_trailing(params)?;
+
+    let mut login = None;
+    let mut target_msg_id = None;
+    for pair in tags.split(';') {
+        match pair.split_once('=') {
+            Some(("login", val)) => login = Some(unescape_tag_value(val).into_owned()),
+            Some(("target-msg-id", val)) if !val.is_empty() => {
+                target_msg_id = Some(val.to_string())
+            }
+            _ => {}
+        }
+    }
+
+    Some(TwitchEvent::ClearMessage {
+        channel: channel.strip_prefix('#')?.to_string(),
+        login: login?,
+        target_msg_id: target_msg_id?,
+        text: text.to_string(),
+    })
+}
+
+/// The tags a message needs, read in a single pass over the tag string.
+#[derive(Default)]
+struct MessageTags<'a> {
+    user_id: Cow<'a, str>,
+    display_name: Cow<'a, str>,
     role: TwitchRole,
+    room_id: Option<Cow<'a, str>>,
+    message_id: Option<Cow<'a, str>>,
+    emotes: Option<Cow<'a, str>>,
+    is_first_message: bool,
+    color: Option<Cow<'a, str>>,
+    badge_info: Option<Cow<'a, str>>,
 }
 
 fn parse_badges(badges: &str) -> TwitchRole {
@@ -92,44 +293,46 @@ fn parse_badges(badges: &str) -> TwitchRole {
     role
 }
 
-fn parse_tags(tags: &str) -> UserMeta<'_> {
+fn parse_tags(tags: &str) -> MessageTags<'_> {
+    let mut parsed = MessageTags {
+        user_id: Cow::Borrowed("0"),
+        display_name: Cow::Borrowed("anon"),
+        ..MessageTags::default()
+    };
     if tags.is_empty() {
-        return UserMeta {
-            user_id: "0",
-            display_name: "anon",
-            role: TwitchRole::empty(),
-        };
+        return parsed;
     }
 
-    let mut user_id = "0";
-    let mut display_name: Option<&str> = None;
-    let mut login: Option<&str> = None;
-    let mut role = TwitchRole::empty();
+    let mut display_name = None;
+    let mut login = None;
 
     for pair in tags.split(';') {
         let Some((key, val)) = pair.split_once('=') else {
             continue;
         };
+        let val = unescape_tag_value(val);
 
         match key {
-            "user-id" => user_id = val,
+            "user-id" => parsed.user_id = val,
             "display-name" if !val.is_empty() => display_name = Some(val),
             "login" => login = Some(val),
-            "mod" if val == "1" => role.add(TwitchRole::MODERATOR),
-            "subscriber" if val == "1" => role.add(TwitchRole::SUBSCRIBER),
-            "badges" => {
-                let badge_role = parse_badges(val);
-                role.add(badge_role);
-            }
+            "mod" if val == "1" => parsed.role.add(TwitchRole::MODERATOR),
+            "subscriber" if val == "1" => parsed.role.add(TwitchRole::SUBSCRIBER),
+            "badges" => parsed.role.add(parse_badges(&val)),
+            "room-id" if !val.is_empty() => parsed.room_id = Some(val),
+            "id" if !val.is_empty() => parsed.message_id = Some(val),
+            "emotes" => parsed.emotes = Some(val),
+            "first-msg" => parsed.is_first_message = val == "1",
+            "color" if !val.is_empty() => parsed.color = Some(val),
+            "badge-info" => parsed.badge_info = Some(val),
             _ => {}
         }
     }
 
-    UserMeta {
-        user_id,
-        display_name: display_name.or(login).unwrap_or("anon"),
-        role,
+    if let Some(name) = display_name.or(login) {
+        parsed.display_name = name;
     }
+    parsed
 }
 
 #[cfg(test)]
@@ -319,6 +522,15 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    #[test]
+    fn test_structure_keeps_prefix() {
+        let msg = parse_irc_structure(":bot!bot@bot.tmi.twitch.tv JOIN #channel").unwrap();
+        assert_eq!(msg.tags, "");
+        assert_eq!(msg.prefix, "bot!bot@bot.tmi.twitch.tv");
+        assert_eq!(msg.command, "JOIN");
+        assert_eq!(msg.params, "#channel");
+    }
+
     #[test]
     fn test_malformed_no_command() {
         let events = parse_irc_messages("@tags-only-no-rest");
@@ -389,4 +601,346 @@ mod tests {
             _ => panic!("Expected ChatMessage"),
         }
     }
+
+    #[test]
+    fn test_parse_whisper() {
+        let raw = "@badges=;color=;display-name=Friend;emotes=;message-id=1;thread-id=123_456;turbo=0;user-id=123;user-type= :friend!friend@friend.tmi.twitch.tv WHISPER mybot :psst, hello";
+        match parse_one(raw) {
+            TwitchEvent::Whisper { user, text } => {
+                assert_eq!(user.id, "123");
+                assert_eq!(user.display_name, "Friend");
+                assert_eq!(text, "psst, hello");
+            }
+            other => panic!("Expected Whisper, got {:?}", other),
+        }
+    }
+
+    fn emotes_of(raw: &str) -> Option<EmotePositions> {
+        match parse_one(raw) {
+            TwitchEvent::ChatMessage { emotes, .. } => emotes,
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_single_emote() {
+        let raw = "@emotes=25:0-4;user-id=1;display-name=Test :t PRIVMSG #ch :Kappa hi";
+        let emotes = emotes_of(raw).expect("emotes tag was present");
+        assert_eq!(emotes.len(), 1);
+        assert_eq!(emotes["25"], vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_parse_multiple_emotes() {
+        let raw = "@emotes=25:0-4,6-10/1902:12-16;user-id=1 :t PRIVMSG #ch :Kappa Kappa Keepo";
+        let emotes = emotes_of(raw).expect("emotes tag was present");
+        assert_eq!(emotes.len(), 2);
+        assert_eq!(emotes["25"], vec![(0, 4), (6, 10)]);
+        assert_eq!(emotes["1902"], vec![(12, 16)]);
+    }
+
+    #[test]
+    fn test_parse_empty_emotes() {
+        let raw = "@emotes=;user-id=1 :t PRIVMSG #ch :no emotes here";
+        assert_eq!(emotes_of(raw), Some(EmotePositions::new()));
+
+        let raw = "@user-id=1 :t PRIVMSG #ch :no emotes tag";
+        assert_eq!(emotes_of(raw), None);
+    }
+
+    #[test]
+    fn test_parse_first_message_flag() {
+        let is_first = |raw: &str| match parse_one(raw) {
+            TwitchEvent::ChatMessage {
+                is_first_message, ..
+            } => is_first_message,
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        };
+
+        assert!(is_first(
+            "@first-msg=1;user-id=1 :t PRIVMSG #ch :hello everyone"
+        ));
+        assert!(!is_first("@first-msg=0;user-id=1 :t PRIVMSG #ch :hi again"));
+        assert!(!is_first("@user-id=1 :t PRIVMSG #ch :no tag"));
+    }
+
+    #[test]
+    fn test_parse_empty_message() {
+        let event = parse_one("@user-id=1;display-name=Viewer :t PRIVMSG #ch :");
+
+        match event {
+            TwitchEvent::ChatMessage { text, target, .. } => {
+                assert_eq!(text, "");
+                assert_eq!(target.channel_login.as_deref(), Some("ch"));
+            }
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_privmsg_without_text_is_dropped() {
+        assert!(parse_irc_messages("@user-id=1 :t PRIVMSG #ch").is_empty());
+    }
+
+    #[test]
+    fn test_parse_privmsg_without_leading_colon() {
+        let parse = |raw: &str| match parse_one(raw) {
+            TwitchEvent::ChatMessage { text, target, .. } => (target.channel_login, text),
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        };
+
+        assert_eq!(
+            parse("@user-id=1 :t PRIVMSG #ch hello"),
+            (Some("ch".to_string()), "hello".to_string())
+        );
+        assert_eq!(
+            parse("@user-id=1 :t PRIVMSG #ch hello there friend"),
+            (Some("ch".to_string()), "hello there friend".to_string())
+        );
+        assert_eq!(
+            parse("@user-id=1 :t PRIVMSG #ch hello :there"),
+            (Some("ch hello".to_string()), "there".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_action_message() {
+        let parse = |raw: &str| match parse_one(raw) {
+            TwitchEvent::ChatMessage {
+                text, is_action, ..
+            } => (text, is_action),
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        };
+
+        assert_eq!(
+            parse("@user-id=1 :t PRIVMSG #ch :\u{1}ACTION waves hello\u{1}"),
+            ("waves hello".to_string(), true)
+        );
+        assert_eq!(
+            parse("@user-id=1 :t PRIVMSG #ch :just talking"),
+            ("just talking".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_unescape_tag_value() {
+        assert_eq!(unescape_tag_value("plain"), "plain");
+        assert_eq!(unescape_tag_value(r"Cool\sUser"), "Cool User");
+        assert_eq!(unescape_tag_value(r"a\:b\\c\r\n"), "a;b\\c\r\n");
+        assert_eq!(unescape_tag_value(r"unknown\qescape"), "unknownqescape");
+        assert_eq!(unescape_tag_value("trailing\\"), "trailing");
+    }
+
+    #[test]
+    fn test_parse_escaped_display_name() {
+        let event = parse_one(r"@display-name=Cool\sUser\:1;user-id=1 :t PRIVMSG #ch :hi");
+
+        match event {
+            TwitchEvent::ChatMessage { user, .. } => {
+                assert_eq!(user.display_name, "Cool User;1");
+            }
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_color_and_badge_info() {
+        let raw = "@badge-info=subscriber/12;badges=subscriber/12;color=#1E90FF;user-id=1 :t PRIVMSG #ch :hi";
+        match parse_one(raw) {
+            TwitchEvent::ChatMessage {
+                color, badge_info, ..
+            } => {
+                assert_eq!(color, Some("#1E90FF".to_string()));
+                assert_eq!(
+                    badge_info,
+                    vec![("subscriber".to_string(), "12".to_string())]
+                );
+            }
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_message_id() {
+        let id = |raw: &str| match parse_one(raw) {
+            TwitchEvent::ChatMessage { message_id, .. } => message_id,
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        };
+
+        assert_eq!(
+            id("@id=b34ccfc7-4977-403a-8a94-33c6bac34fb8;user-id=1 :t PRIVMSG #ch :hi"),
+            Some("b34ccfc7-4977-403a-8a94-33c6bac34fb8".to_string())
+        );
+        assert_eq!(id("@user-id=1 :t PRIVMSG #ch :hi"), None);
+    }
+
+    #[test]
+    fn test_parse_absent_color_and_badge_info() {
+        for raw in [
+            "@badge-info=;color=;user-id=1 :t PRIVMSG #ch :hi",
+            "@user-id=1 :t PRIVMSG #ch :hi",
+        ] {
+            match parse_one(raw) {
+                TwitchEvent::ChatMessage {
+                    color, badge_info, ..
+                } => {
+                    assert_eq!(color, None);
+                    assert!(badge_info.is_empty());
+                }
+                other => panic!("Expected ChatMessage, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_full_roomstate() {
+        let raw = "@emote-only=0;followers-only=-1;r9k=0;room-id=12345678;slow=0;subs-only=0 \
+                   :tmi.twitch.tv ROOMSTATE #bar";
+
+        match parse_one(raw) {
+            TwitchEvent::RoomState {
+                channel,
+                slow,
+                followers_only,
+                subs_only,
+                emote_only,
+                unique_chat,
+            } => {
+                assert_eq!(channel, "bar");
+                assert_eq!(slow, Some(0));
+                assert_eq!(followers_only, Some(-1));
+                assert_eq!(subs_only, Some(false));
+                assert_eq!(emote_only, Some(false));
+                assert_eq!(unique_chat, Some(false));
+            }
+            other => panic!("Expected RoomState, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_partial_roomstate() {
+        let raw = "@emote-only=1;room-id=12345678 :tmi.twitch.tv ROOMSTATE #bar";
+
+        match parse_one(raw) {
+            TwitchEvent::RoomState {
+                slow,
+                followers_only,
+                subs_only,
+                emote_only,
+                unique_chat,
+                ..
+            } => {
+                assert_eq!(emote_only, Some(true));
+                assert_eq!(slow, None);
+                assert_eq!(followers_only, None);
+                assert_eq!(subs_only, None);
+                assert_eq!(unique_chat, None);
+            }
+            other => panic!("Expected RoomState, got {:?}", other),
+        }
+
+        let raw = "@followers-only=10;room-id=12345678;slow=30 :tmi.twitch.tv ROOMSTATE #bar";
+        assert!(matches!(
+            parse_one(raw),
+            TwitchEvent::RoomState {
+                slow: Some(30),
+                followers_only: Some(10),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_clearchat_timeout() {
+        let raw = "@ban-duration=350;room-id=12345678;target-user-id=87654321;tmi-sent-ts=1642715756806 \
+                   :tmi.twitch.tv CLEARCHAT #dallas :ronni";
+
+        match parse_one(raw) {
+            TwitchEvent::ClearChat {
+                channel,
+                target_user,
+                duration,
+            } => {
+                assert_eq!(channel, "dallas");
+                assert_eq!(target_user.as_deref(), Some("ronni"));
+                assert_eq!(duration, Some(350));
+            }
+            other => panic!("Expected ClearChat, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_clearchat_permanent_ban() {
+        let raw = "@room-id=12345678;target-user-id=87654321;tmi-sent-ts=1642715695392 \
+                   :tmi.twitch.tv CLEARCHAT #dallas :ronni";
+
+        match parse_one(raw) {
+            TwitchEvent::ClearChat {
+                target_user,
+                duration,
+                ..
+            } => {
+                assert_eq!(target_user.as_deref(), Some("ronni"));
+                assert_eq!(duration, None);
+            }
+            other => panic!("Expected ClearChat, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_clearchat_whole_chat() {
+        let raw = "@room-id=12345678;tmi-sent-ts=1642715695392 :tmi.twitch.tv CLEARCHAT #dallas";
+
+        match parse_one(raw) {
+            TwitchEvent::ClearChat {
+                channel,
+                target_user,
+                duration,
+            } => {
+                assert_eq!(channel, "dallas");
+                assert_eq!(target_user, None);
+                assert_eq!(duration, None);
+            }
+            other => panic!("Expected ClearChat, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_clearmsg() {
+        let raw = "@login=foo;room-id=;target-msg-id=94e6c7ff-bf98-4faa-af5d-7ad633a158a9;tmi-sent-ts=1642720582342 \
+                   :tmi.twitch.tv CLEARMSG #bar :what a great day";
+
+        match parse_one(raw) {
+            TwitchEvent::ClearMessage {
+                channel,
+                login,
+                target_msg_id,
+                text,
+            } => {
+                assert_eq!(channel, "bar");
+                assert_eq!(login, "foo");
+                assert_eq!(target_msg_id, "94e6c7ff-bf98-4faa-af5d-7ad633a158a9");
+                assert_eq!(text, "what a great day");
+            }
+            other => panic!("Expected ClearMessage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_clearmsg_without_target_is_dropped() {
+        let events = parse_irc_messages("@login=foo :tmi.twitch.tv CLEARMSG #bar :hi");
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_parse_room_id_as_broadcaster_id() {
+        let raw = "@room-id=1337;user-id=1 :t PRIVMSG #ch :hi";
+        match parse_one(raw) {
+            TwitchEvent::ChatMessage { target, .. } => {
+                assert_eq!(target.broadcaster_id.as_deref(), Some("1337"));
+                assert_eq!(target.channel_login.as_deref(), Some("ch"));
+            }
+            other => panic!("Expected ChatMessage, got {:?}", other),
+        }
+    }
 }
@@ -1,10 +1,24 @@
 pub mod auth;
 pub mod chat;
+mod error;
+#[cfg(feature = "eventsub")]
 pub mod eventsub;
+pub mod helix;
+#[cfg(feature = "irc")]
 pub mod irc;
 pub mod model;
+#[cfg(any(feature = "irc", feature = "eventsub"))]
+mod recorder;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
-pub use auth::TokenManager;
-pub use eventsub::EventSubClient;
+pub use auth::{AuthError, TokenManager};
+pub use error::Error;
+#[cfg(feature = "eventsub")]
+pub use eventsub::{EventSubClient, EventSubTopic, SubscriptionCost, SubscriptionInfo};
+pub use helix::HelixClient;
+#[cfg(feature = "irc")]
 pub use irc::IrcClient;
-pub use model::{TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser};
+pub use model::{
+    EmotePositions, MessageFragment, TwitchChatTarget, TwitchEvent, TwitchRole, TwitchUser,
+};
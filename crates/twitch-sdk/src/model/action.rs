@@ -0,0 +1,24 @@
+/// Unwraps a `/me` message, sent as `\x01ACTION text\x01`.
+pub(crate) fn split_action(text: &str) -> (&str, bool) {
+    match text
+        .strip_prefix("\u{1}ACTION ")
+        .map(|action| action.strip_suffix('\u{1}').unwrap_or(action))
+    {
+        Some(action) => (action, true),
+        None => (text, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_action_without_closing_byte() {
+        assert_eq!(split_action("\u{1}ACTION dances"), ("dances", true));
+        assert_eq!(
+            split_action("ACTION is a word"),
+            ("ACTION is a word", false)
+        );
+    }
+}
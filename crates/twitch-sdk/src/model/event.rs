@@ -1,12 +1,30 @@
-use super::{TwitchChatTarget, TwitchUser};
+use std::time::SystemTime;
+
+use super::{EmotePositions, MessageFragment, TwitchChatTarget, TwitchUser};
 
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum TwitchEvent {
+    #[non_exhaustive]
     ChatMessage {
         user: TwitchUser,
         target: TwitchChatTarget,
+        /// Plain text of the whole message.
         text: String,
+        /// Twitch's id for the message, needed to delete it.
+        message_id: Option<String>,
+        /// Structured pieces of `text`; only EventSub provides them.
+        fragments: Option<Vec<MessageFragment>>,
+        /// Emote positions within `text`; only IRC provides them.
+        emotes: Option<EmotePositions>,
+        /// The user's first message ever in this channel.
+        is_first_message: bool,
+        /// Sent with `/me`; the `\x01ACTION` wrapper is already stripped from `text`.
+        is_action: bool,
+        /// Username color as `#RRGGBB`; `None` when the user never picked one.
+        color: Option<String>,
+        /// Badge set to extra info, e.g. `("subscriber", "12")` for 12 months subscribed.
+        badge_info: Vec<(String, String)>,
     },
     RewardRedemption {
         user: TwitchUser,
@@ -15,4 +33,105 @@ pub enum TwitchEvent {
         cost: u32,
         user_input: Option<String>,
     },
+    /// Private message sent directly to the bot account.
+    Whisper {
+        user: TwitchUser,
+        text: String,
+    },
+    /// Another broadcaster raided this channel.
+    Raid {
+        from_broadcaster: TwitchUser,
+        viewers: u32,
+    },
+    /// New or gifted subscription. `tier` is Twitch's raw value: `1000`, `2000` or `3000`.
+    Subscription {
+        user: TwitchUser,
+        tier: String,
+        is_gift: bool,
+    },
+    /// Resubscription shared in chat with an optional message.
+    ResubMessage {
+        user: TwitchUser,
+        cumulative_months: u32,
+        /// Consecutive months, `None` when the user chose not to share it.
+        streak: Option<u32>,
+        text: String,
+    },
+    /// A user was banned or timed out.
+    Ban {
+        target: TwitchUser,
+        moderator: TwitchUser,
+        reason: String,
+        /// Timeout length in seconds; `None` means a permanent ban.
+        duration: Option<u32>,
+    },
+    /// The broadcaster went live. `stream_type` is Twitch's raw value, e.g. `live` or `rerun`.
+    StreamOnline {
+        started_at: SystemTime,
+        stream_type: String,
+    },
+    StreamOffline,
+    /// Full chat settings snapshot, sent whenever any setting changes.
+    ChatSettingsUpdate {
+        emote_mode: bool,
+        follower_mode: bool,
+        /// Minutes a user must follow before chatting; `None` when follower mode is off.
+        follower_mode_duration: Option<u32>,
+        slow_mode: bool,
+        /// Seconds between messages; `None` when slow mode is off.
+        slow_mode_wait_time: Option<u32>,
+        subscriber_mode: bool,
+        unique_chat_mode: bool,
+    },
+    /// Chat settings from an IRC `ROOMSTATE`. The one sent on joining carries
+    /// every setting; later ones only the setting that changed, leaving the
+    /// others `None`.
+    RoomState {
+        /// Channel login, without the `#`.
+        channel: String,
+        /// Seconds between messages; `0` when slow mode is off.
+        slow: Option<u32>,
+        /// Twitch's raw value: minutes a user must follow before chatting, `0`
+        /// for any follower, `-1` when followers-only mode is off.
+        followers_only: Option<i32>,
+        subs_only: Option<bool>,
+        emote_only: Option<bool>,
+        /// Unique chat mode, `r9k` in the tags.
+        unique_chat: Option<bool>,
+    },
+    /// IRC `CLEARCHAT`: a user's messages were purged by a timeout or ban, or
+    /// with no `target_user`, the whole chat was cleared.
+    ClearChat {
+        /// Channel login, without the `#`.
+        channel: String,
+        /// Login of the timed out or banned user.
+        target_user: Option<String>,
+        /// Timeout length in seconds; `None` means a permanent ban or a chat clear.
+        duration: Option<u32>,
+    },
+    /// IRC `CLEARMSG`: a single message was deleted.
+    ClearMessage {
+        /// Channel login, without the `#`.
+        channel: String,
+        /// Login of the message's author.
+        login: String,
+        /// The deleted message's id, as in [`TwitchEvent::ChatMessage`]'s `message_id`.
+        target_msg_id: String,
+        /// Text of the deleted message.
+        text: String,
+    },
+    /// The client connected: an EventSub session is welcomed and subscribed, or
+    /// Twitch confirmed the IRC `JOIN`. Sent again after every reconnect that
+    /// opens a new connection. `session_id` is the EventSub session, `None` for
+    /// IRC. Only emitted when enabled with `with_connection_events` on the client.
+    Connected {
+        session_id: Option<String>,
+    },
+    /// A notification without a typed event, carrying Twitch's raw `payload`. Only
+    /// emitted when enabled with
+    /// [`EventSubClient::with_unknown_notifications`](crate::EventSubClient::with_unknown_notifications).
+    Unknown {
+        subscription_type: String,
+        payload: serde_json::Value,
+    },
 }
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+/// A piece of a chat message as split by Twitch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MessageFragment {
+    Text(String),
+    Emote {
+        id: String,
+        name: String,
+    },
+    /// Bits cheer such as `Cheer100`.
+    Cheermote {
+        text: String,
+        bits: u32,
+    },
+    Mention {
+        user_id: String,
+        text: String,
+    },
+}
+
+/// Emote id to the inclusive `(start, end)` character ranges where it appears,
+/// as sent in the IRC `emotes` tag.
+pub type EmotePositions = HashMap<String, Vec<(usize, usize)>>;
@@ -1,9 +1,15 @@
+#[cfg(any(feature = "irc", feature = "eventsub"))]
+mod action;
 mod event;
+mod fragment;
 mod role;
 mod target;
 mod user;
 
+#[cfg(any(feature = "irc", feature = "eventsub"))]
+pub(crate) use action::split_action;
 pub use event::TwitchEvent;
+pub use fragment::{EmotePositions, MessageFragment};
 pub use role::TwitchRole;
 pub use target::TwitchChatTarget;
 pub use user::TwitchUser;
@@ -1,3 +1,9 @@
+/// The badges Twitch reported for a user, one bit each.
+///
+/// The bits are kept exactly as reported, so a moderator without a subscriber
+/// badge does not [`contains`](TwitchRole::contains) `SUBSCRIBER`. Use
+/// [`at_least`](TwitchRole::at_least) for hierarchy checks; the bot's `Role`
+/// follows the same order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[non_exhaustive]
 pub struct TwitchRole(u8);
@@ -22,6 +28,27 @@ impl TwitchRole {
         self.0 |= other.0;
     }
 
+    pub fn remove(&mut self, other: TwitchRole) {
+        self.0 &= !other.0;
+    }
+
+    #[must_use]
+    pub fn contains(&self, other: TwitchRole) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether the highest badge ranks at or above `required` in the hierarchy
+    /// broadcaster > moderator > VIP > subscriber.
+    #[must_use]
+    pub fn at_least(&self, required: TwitchRole) -> bool {
+        self.highest().0 >= required.highest().0
+    }
+
     #[must_use]
     pub fn highest(&self) -> TwitchRole {
         const PRIORITY: [u8; 4] = [
@@ -38,3 +65,47 @@ impl TwitchRole {
         TwitchRole(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_then_contains() {
+        let mut role = TwitchRole::empty();
+        assert!(role.is_empty());
+
+        role.add(TwitchRole::MODERATOR);
+        role.add(TwitchRole::SUBSCRIBER);
+
+        assert!(!role.is_empty());
+        assert!(role.contains(TwitchRole::MODERATOR));
+        assert!(role.contains(TwitchRole::SUBSCRIBER));
+        assert!(!role.contains(TwitchRole::VIP));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut role = TwitchRole::empty();
+        role.add(TwitchRole::VIP);
+        role.add(TwitchRole::SUBSCRIBER);
+
+        role.remove(TwitchRole::VIP);
+        assert!(!role.contains(TwitchRole::VIP));
+        assert!(role.contains(TwitchRole::SUBSCRIBER));
+
+        role.remove(TwitchRole::SUBSCRIBER);
+        assert!(role.is_empty());
+    }
+
+    #[test]
+    fn test_at_least_follows_hierarchy() {
+        assert!(TwitchRole::MODERATOR.at_least(TwitchRole::VIP));
+        assert!(!TwitchRole::MODERATOR.contains(TwitchRole::VIP));
+        assert!(TwitchRole::BROADCASTER.at_least(TwitchRole::MODERATOR));
+        assert!(TwitchRole::VIP.at_least(TwitchRole::VIP));
+        assert!(!TwitchRole::SUBSCRIBER.at_least(TwitchRole::VIP));
+        assert!(!TwitchRole::empty().at_least(TwitchRole::SUBSCRIBER));
+        assert!(TwitchRole::empty().at_least(TwitchRole::empty()));
+    }
+}
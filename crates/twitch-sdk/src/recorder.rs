@@ -0,0 +1,103 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tracing::warn;
+
+/// Writes raw inbound frames, one message per line, for later replay.
+#[derive(Clone)]
+pub(crate) struct RawRecorder {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl RawRecorder {
+    /// Appends to the file at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::from_writer(file))
+    }
+
+    pub fn from_writer(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    #[cfg(feature = "irc")]
+    /// Records each non-empty line of an IRC frame separately.
+    pub fn record_lines(&self, frame: &str) {
+        for line in frame.lines().filter(|line| !line.trim().is_empty()) {
+            self.write_line(line);
+        }
+    }
+
+    #[cfg(feature = "eventsub")]
+    /// Records a JSON frame on a single line. JSON strings can't hold raw line
+    /// breaks, so flattening them only touches insignificant whitespace.
+    pub fn record_json(&self, frame: &str) {
+        self.write_line(&frame.replace(['\r', '\n'], " "));
+    }
+
+    fn write_line(&self, line: &str) {
+        let Ok(mut writer) = self.writer.lock() else {
+            return;
+        };
+
+        if let Err(error) = writeln!(writer, "{line}").and_then(|()| writer.flush()) {
+            warn!("failed to record raw message: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[cfg(feature = "irc")]
+    #[test]
+    fn test_irc_frame_is_split_into_lines() {
+        let buffer = SharedBuffer::default();
+        let recorder = RawRecorder::from_writer(buffer.clone());
+
+        recorder.record_lines("PING :tmi.twitch.tv\r\n:u PRIVMSG #ch :hi\r\n");
+
+        assert_eq!(
+            buffer.contents(),
+            "PING :tmi.twitch.tv\n:u PRIVMSG #ch :hi\n"
+        );
+    }
+
+    #[cfg(feature = "eventsub")]
+    #[test]
+    fn test_json_frame_is_kept_on_one_line() {
+        let buffer = SharedBuffer::default();
+        let recorder = RawRecorder::from_writer(buffer.clone());
+
+        recorder.record_json("{\n  \"text\": \"a\\nb\"\n}");
+
+        let contents = buffer.contents();
+        assert_eq!(contents.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(value["text"], "a\nb");
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// A local stand-in for Twitch's EventSub WebSocket and Helix subscriptions
+/// endpoint. Point a client at it with `with_ws_url(server.ws_url())` and
+/// `with_api_url(server.api_url())`.
+///
+/// Every WebSocket connection is greeted with a `session_welcome` whose
+/// session ID is `session-<n>`, counting from 1. Created subscriptions get
+/// the ID `<type>-<session id>`. List requests are answered with a fixed
+/// two-page listing. Deleting the ID `missing` fails with `404`, and creating
+/// a type passed to [`MockEventSubServer::reject_subscriptions`] with `403`.
+pub struct MockEventSubServer {
+    ws_addr: SocketAddr,
+    api_addr: SocketAddr,
+    rejected: Arc<Mutex<HashSet<String>>>,
+    connections_rx: mpsc::Receiver<MockConnection>,
+    subscriptions_rx: mpsc::Receiver<Value>,
+    deletions_rx: mpsc::Receiver<String>,
+}
+
+/// A request to the Helix subscriptions endpoint.
+enum ApiRequest {
+    /// The JSON body of a create request, rejected or not.
+    Subscribe(Value),
+    /// The `id` of a delete request.
+    Unsubscribe(String),
+    /// A list request, answered with a fixed two-page listing.
+    List,
+}
+
+/// One accepted WebSocket connection, already greeted with `session_welcome`.
+pub struct MockConnection {
+    pub session_id: String,
+    outgoing_tx: mpsc::Sender<String>,
+}
+
+impl MockConnection {
+    /// Sends `msg` to the client as a text frame.
+    pub async fn send(&self, msg: Value) {
+        self.outgoing_tx.send(msg.to_string()).await.unwrap();
+    }
+}
+
+impl MockEventSubServer {
+    /// Starts listening; welcomes announce `keepalive_timeout_seconds`.
+    pub async fn start(keepalive_timeout_seconds: u64) -> Self {
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_addr = ws_listener.local_addr().unwrap();
+        let api_addr = api_listener.local_addr().unwrap();
+
+        let (connections_tx, connections_rx) = mpsc::channel(8);
+        let (subscriptions_tx, subscriptions_rx) = mpsc::channel(32);
+        let (deletions_tx, deletions_rx) = mpsc::channel(8);
+
+        tokio::spawn(async move {
+            let mut count = 0;
+            while let Ok((stream, _)) = ws_listener.accept().await {
+                count += 1;
+                let session_id = format!("session-{count}");
+                let (outgoing_tx, outgoing_rx) = mpsc::channel(32);
+
+                let welcome = welcome(&session_id, keepalive_timeout_seconds);
+                tokio::spawn(serve_connection(stream, welcome, outgoing_rx));
+
+                let connection = MockConnection {
+                    session_id,
+                    outgoing_tx,
+                };
+                if connections_tx.send(connection).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let rejected = Arc::new(Mutex::new(HashSet::new()));
+        let rejected_types = rejected.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = api_listener.accept().await {
+                let subscriptions_tx = subscriptions_tx.clone();
+                let deletions_tx = deletions_tx.clone();
+                let rejected = rejected_types.clone();
+                tokio::spawn(async move {
+                    match serve_api_request(stream, &rejected).await {
+                        ApiRequest::Subscribe(body) => {
+                            let _ = subscriptions_tx.send(body).await;
+                        }
+                        ApiRequest::Unsubscribe(id) => {
+                            let _ = deletions_tx.send(id).await;
+                        }
+                        ApiRequest::List => {}
+                    }
+                });
+            }
+        });
+
+        Self {
+            ws_addr,
+            api_addr,
+            rejected,
+            connections_rx,
+            subscriptions_rx,
+            deletions_rx,
+        }
+    }
+
+    /// Answers later create requests for these types with `403 Forbidden`,
+    /// like Twitch does when the token lacks a scope.
+    pub fn reject_subscriptions(&self, sub_types: &[&str]) {
+        let mut rejected = self.rejected.lock().unwrap();
+        rejected.extend(sub_types.iter().map(|sub_type| sub_type.to_string()));
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.ws_addr)
+    }
+
+    pub fn api_url(&self) -> String {
+        format!("http://{}/eventsub/subscriptions", self.api_addr)
+    }
+
+    /// Waits up to 5 seconds for the client to open a connection.
+    pub async fn next_connection(&mut self) -> MockConnection {
+        tokio::time::timeout(Duration::from_secs(5), self.connections_rx.recv())
+            .await
+            .expect("Timeout waiting for a connection")
+            .expect("Server stopped")
+    }
+
+    /// Waits for `count` create requests and returns their JSON bodies.
+    pub async fn expect_subscriptions(&mut self, count: usize) -> Vec<Value> {
+        let mut subscriptions = Vec::with_capacity(count);
+        for _ in 0..count {
+            let subscription =
+                tokio::time::timeout(Duration::from_secs(2), self.subscriptions_rx.recv())
+                    .await
+                    .expect("Timeout waiting for a subscription request")
+                    .expect("Server stopped");
+            subscriptions.push(subscription);
+        }
+        subscriptions
+    }
+
+    /// Waits for a delete request and returns the deleted ID.
+    pub async fn expect_deletion(&mut self) -> String {
+        tokio::time::timeout(Duration::from_secs(2), self.deletions_rx.recv())
+            .await
+            .expect("Timeout waiting for a delete request")
+            .expect("Server stopped")
+    }
+
+    pub fn assert_no_more_subscriptions(&mut self) {
+        if let Ok(subscription) = self.subscriptions_rx.try_recv() {
+            panic!("Unexpected subscription request: {subscription}");
+        }
+    }
+
+    pub fn assert_no_more_connections(&mut self) {
+        if let Ok(connection) = self.connections_rx.try_recv() {
+            panic!("Unexpected connection: {}", connection.session_id);
+        }
+    }
+}
+
+async fn serve_connection(
+    stream: TcpStream,
+    welcome: Value,
+    mut outgoing_rx: mpsc::Receiver<String>,
+) {
+    let ws_stream = accept_async(stream).await.unwrap();
+    let (mut write, mut read) = ws_stream.split();
+
+    if write
+        .send(Message::Text(welcome.to_string()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            Some(msg) = outgoing_rx.recv() => {
+                if write.send(Message::Text(msg)).await.is_err() {
+                    break;
+                }
+            }
+            msg = read.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Reads one request and answers it like Helix: a created subscription gets
+/// the ID `<type>-<session id>`, a deleted one `204 No Content`.
+async fn serve_api_request(mut stream: TcpStream, rejected: &Mutex<HashSet<String>>) -> ApiRequest {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    let header_end = loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().unwrap())
+        })
+        .unwrap_or(0);
+
+    while request.len() < header_end + content_length {
+        let n = stream.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    if let Some(target) = head.strip_prefix("GET ") {
+        let path = target.split_whitespace().next().unwrap();
+        let body = if path.contains("after=page-2") {
+            json!({
+                "data": [subscription_info("b", "channel.raid", "authorization_revoked")],
+                "total": 2,
+                "total_cost": 1,
+                "max_total_cost": 10,
+                "pagination": {}
+            })
+        } else {
+            json!({
+                "data": [subscription_info("a", "channel.chat.message", "enabled")],
+                "total": 2,
+                "total_cost": 1,
+                "max_total_cost": 10,
+                "pagination": { "cursor": "page-2" }
+            })
+        }
+        .to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        return ApiRequest::List;
+    }
+
+    if let Some(target) = head.strip_prefix("DELETE ") {
+        let id = target
+            .split_whitespace()
+            .next()
+            .and_then(|path| path.split_once("id="))
+            .map(|(_, id)| id.to_string())
+            .expect("delete request without an id");
+        let response = if id == "missing" {
+            "HTTP/1.1 404 Not Found\r\ncontent-length: 9\r\nconnection: close\r\n\r\nnot found"
+        } else {
+            "HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n"
+        };
+        stream.write_all(response.as_bytes()).await.unwrap();
+        return ApiRequest::Unsubscribe(id);
+    }
+
+    let subscription: Value = serde_json::from_slice(&request[header_end..]).unwrap();
+    let sub_type = subscription["type"].as_str().unwrap();
+    if rejected.lock().unwrap().contains(sub_type) {
+        let response = "HTTP/1.1 403 Forbidden\r\ncontent-length: 13\r\nconnection: close\r\n\r\nmissing scope";
+        stream.write_all(response.as_bytes()).await.unwrap();
+        return ApiRequest::Subscribe(subscription);
+    }
+
+    let id = format!(
+        "{}-{}",
+        sub_type,
+        subscription["transport"]["session_id"].as_str().unwrap()
+    );
+    let body = json!({
+        "data": [{ "id": id, "status": "enabled", "type": subscription["type"] }],
+        "total": 1,
+        "total_cost": 0,
+        "max_total_cost": 10
+    })
+    .to_string();
+    let response = format!(
+        "HTTP/1.1 202 Accepted\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+
+    ApiRequest::Subscribe(subscription)
+}
+
+fn subscription_info(id: &str, sub_type: &str, status: &str) -> Value {
+    json!({
+        "id": id,
+        "status": status,
+        "type": sub_type,
+        "version": "1",
+        "condition": { "broadcaster_user_id": "1337" },
+        "created_at": "2023-07-19T14:56:51.634234626Z",
+        "transport": { "method": "websocket", "session_id": "session-1" },
+        "cost": if status == "enabled" { 1 } else { 0 }
+    })
+}
+
+fn welcome(session_id: &str, keepalive_timeout_seconds: u64) -> Value {
+    json!({
+        "metadata": {
+            "message_id": format!("welcome-{session_id}"),
+            "message_type": "session_welcome",
+            "message_timestamp": "2023-07-19T14:56:51.634234626Z"
+        },
+        "payload": {
+            "session": {
+                "id": session_id,
+                "status": "connected",
+                "connected_at": "2023-07-19T14:56:51.616329898Z",
+                "keepalive_timeout_seconds": keepalive_timeout_seconds,
+                "reconnect_url": null
+            }
+        }
+    })
+}
+
+/// A `session_reconnect` message pointing the client at `reconnect_url`.
+pub fn session_reconnect(session_id: &str, reconnect_url: &str) -> Value {
+    json!({
+        "metadata": {
+            "message_id": format!("reconnect-{session_id}"),
+            "message_type": "session_reconnect",
+            "message_timestamp": "2023-07-19T14:57:51.634234626Z"
+        },
+        "payload": {
+            "session": {
+                "id": session_id,
+                "status": "reconnecting",
+                "connected_at": "2023-07-19T14:56:51.616329898Z",
+                "keepalive_timeout_seconds": null,
+                "reconnect_url": reconnect_url
+            }
+        }
+    })
+}
+
+/// A `notification` message carrying `event` for a `sub_type` subscription.
+pub fn notification(message_id: &str, sub_type: &str, event: Value) -> Value {
+    json!({
+        "metadata": {
+            "message_id": message_id,
+            "message_type": "notification",
+            "message_timestamp": "2023-07-19T14:58:51.634234626Z",
+            "subscription_type": sub_type,
+            "subscription_version": "1"
+        },
+        "payload": {
+            "event": event
+        }
+    })
+}
@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// A local stand-in for Twitch's IRC WebSocket. Point a client at it with
+/// `with_url(server.url())`. It accepts a single connection and, unless
+/// started with [`MockIrcServer::start_ignoring_joins`], confirms the client's
+/// `JOIN` by echoing it back like Twitch does.
+pub struct MockIrcServer {
+    addr: SocketAddr,
+    outgoing_tx: mpsc::Sender<String>,
+    incoming_rx: mpsc::Receiver<String>,
+}
+
+impl MockIrcServer {
+    pub async fn start() -> Self {
+        Self::spawn(true).await
+    }
+
+    /// Never confirms the client's `JOIN`; tests send the reply themselves.
+    pub async fn start_ignoring_joins() -> Self {
+        Self::spawn(false).await
+    }
+
+    async fn spawn(confirm_joins: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<String>(32);
+        let (incoming_tx, incoming_rx) = mpsc::channel::<String>(32);
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ws_stream = accept_async(stream).await.unwrap();
+            let (mut write, mut read) = ws_stream.split();
+            let mut nick = String::new();
+
+            loop {
+                tokio::select! {
+                    Some(msg) = outgoing_rx.recv() => {
+                        if write.send(Message::Text(msg)).await.is_err() {
+                            break;
+                        }
+                    }
+                    msg = read.next() => {
+                        match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                if let Some(name) = text.strip_prefix("NICK ") {
+                                    nick = name.to_string();
+                                }
+                                if let Some(channel) = text.strip_prefix("JOIN ").filter(|_| confirm_joins) {
+                                    let echo = format!(":{nick}!{nick}@{nick}.tmi.twitch.tv JOIN {channel}");
+                                    if write.send(Message::Text(echo)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                let _ = incoming_tx.send(text).await;
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            addr,
+            outgoing_tx,
+            incoming_rx,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Sends `msg`, one or more IRC lines, to the client as a text frame.
+    pub async fn send(&self, msg: &str) {
+        self.outgoing_tx.send(msg.to_string()).await.unwrap();
+    }
+
+    /// The next line the client sent, or `None` after 2 seconds of silence.
+    pub async fn recv(&mut self) -> Option<String> {
+        tokio::time::timeout(Duration::from_secs(2), self.incoming_rx.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Like [`MockIrcServer::recv`], but panics unless a line arrives and
+    /// contains `pattern`.
+    pub async fn expect_contains(&mut self, pattern: &str) -> String {
+        let msg = self.recv().await.expect("Expected a message but got none");
+        assert!(
+            msg.contains(pattern),
+            "Expected message containing '{}', got: {}",
+            pattern,
+            msg
+        );
+        msg
+    }
+
+    /// Panics unless the client closes its connection within 2 seconds.
+    /// Lines it sends before closing are skipped.
+    pub async fn expect_disconnect(&mut self) {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while self.incoming_rx.recv().await.is_some() {}
+        })
+        .await
+        .expect("Timeout waiting for the client to disconnect");
+    }
+}
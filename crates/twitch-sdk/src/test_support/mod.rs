@@ -0,0 +1,15 @@
+//! Mock Twitch servers for testing code built on the SDK's clients, enabled
+//! by the `test-support` feature.
+//!
+//! They bind to an ephemeral local port and are meant for tests: helpers that
+//! wait for the client panic once their timeout runs out.
+
+#[cfg(feature = "eventsub")]
+mod eventsub;
+#[cfg(feature = "irc")]
+mod irc;
+
+#[cfg(feature = "eventsub")]
+pub use eventsub::{MockConnection, MockEventSubServer, notification, session_reconnect};
+#[cfg(feature = "irc")]
+pub use irc::MockIrcServer;
@@ -0,0 +1,389 @@
+//! Integration tests for EventSubClient against the `test-support` mock
+//! EventSub server.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use twitch_sdk::test_support::{MockEventSubServer, notification, session_reconnect};
+use twitch_sdk::{Error, EventSubClient, EventSubTopic, TokenManager, TwitchEvent};
+
+/// Subscriptions the client creates on every cold connect.
+const ALWAYS_ON_SUBSCRIPTIONS: usize = 2;
+
+fn stream_offline(message_id: &str) -> Value {
+    notification(
+        message_id,
+        "stream.offline",
+        json!({
+            "broadcaster_user_id": "1337",
+            "broadcaster_user_login": "cool_user",
+            "broadcaster_user_name": "Cool_User"
+        }),
+    )
+}
+
+async fn test_token_manager() -> Arc<TokenManager> {
+    let tm = Arc::new(TokenManager::new(
+        "test_client_id".to_string(),
+        "test_secret".to_string(),
+        "test_refresh".to_string(),
+    ));
+    tm.set_token_for_test("oauth:test_token_12345".to_string())
+        .await;
+    tm
+}
+
+async fn test_client(server: &MockEventSubServer, cancel: &CancellationToken) -> EventSubClient {
+    EventSubClient::new(
+        test_token_manager().await,
+        "test_client_id".to_string(),
+        "1337".to_string(),
+        "42".to_string(),
+    )
+    .with_ws_url(server.ws_url())
+    .with_api_url(server.api_url())
+    .with_reconnect_delay(Duration::from_millis(10))
+    .with_cancel_token(cancel.clone())
+}
+
+/// The mock reports a request before the client has read the response, so
+/// the IDs show up a little later.
+async fn expect_subscription_ids(client: &EventSubClient, count: usize) -> HashMap<String, String> {
+    tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            let ids = client.subscription_ids();
+            if ids.len() == count {
+                return ids;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Timeout waiting for subscription IDs")
+}
+
+async fn expect_event(rx: &mut mpsc::Receiver<TwitchEvent>) -> TwitchEvent {
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("Channel closed unexpectedly")
+}
+
+// ============================================================================
+// Integration Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_eventsub_client_subscribes_with_welcome_session() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let mut rx = client.connect().await.unwrap();
+    let connection = server.next_connection().await;
+
+    let subscriptions = server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+    for subscription in &subscriptions {
+        assert_eq!(subscription["transport"]["method"], "websocket");
+        assert_eq!(
+            subscription["transport"]["session_id"],
+            connection.session_id
+        );
+    }
+
+    connection.send(stream_offline("offline-1")).await;
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_emits_connected_per_new_session() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await.with_connection_events();
+
+    let mut rx = client.connect().await.unwrap();
+    let first = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    match expect_event(&mut rx).await {
+        TwitchEvent::Connected { session_id } => {
+            assert_eq!(session_id.as_deref(), Some(first.session_id.as_str()))
+        }
+        other => panic!("Expected Connected, got {other:?}"),
+    }
+
+    // a moved session is the same connection to consumers
+    first
+        .send(session_reconnect(&first.session_id, &server.ws_url()))
+        .await;
+    let second = server.next_connection().await;
+    second.send(stream_offline("offline-1")).await;
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_follows_reconnect_url_without_resubscribing() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let mut rx = client.connect().await.unwrap();
+    let first = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    first
+        .send(session_reconnect(&first.session_id, &server.ws_url()))
+        .await;
+    let second = server.next_connection().await;
+
+    second.send(stream_offline("offline-1")).await;
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+    server.assert_no_more_subscriptions();
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_falls_back_to_cold_reconnect() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let _rx = client.connect().await.unwrap();
+    let first = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    let unreachable = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable_url = format!("ws://{}", unreachable.local_addr().unwrap());
+    drop(unreachable);
+
+    first
+        .send(session_reconnect(&first.session_id, &unreachable_url))
+        .await;
+
+    let second = server.next_connection().await;
+    let subscriptions = server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+    assert!(
+        subscriptions
+            .iter()
+            .all(|s| s["transport"]["session_id"] == second.session_id)
+    );
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_reconnects_on_keepalive_timeout() {
+    let mut server = MockEventSubServer::start(1).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel)
+        .await
+        .with_keepalive_buffer(Duration::ZERO);
+
+    let mut rx = client.connect().await.unwrap();
+    let _first = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    // the first connection stays silent past its keepalive timeout
+    let second = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    second.send(stream_offline("offline-1")).await;
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_drops_redelivered_notification() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let mut rx = client.connect().await.unwrap();
+    let connection = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    connection.send(stream_offline("offline-1")).await;
+    connection.send(stream_offline("offline-1")).await;
+    connection.send(stream_offline("offline-2")).await;
+
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(rx.try_recv().is_err());
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_unsubscribes_by_id_and_stays_unsubscribed() {
+    let mut server = MockEventSubServer::start(1).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel)
+        .await
+        .with_keepalive_buffer(Duration::ZERO)
+        .with_topic(EventSubTopic::Raids);
+
+    let _rx = client.connect().await.unwrap();
+    let first = server.next_connection().await;
+    server
+        .expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS + 1)
+        .await;
+
+    let ids = expect_subscription_ids(&client, ALWAYS_ON_SUBSCRIPTIONS + 1).await;
+    let raid_id = ids["channel.raid"].clone();
+    assert_eq!(raid_id, format!("channel.raid-{}", first.session_id));
+
+    client.unsubscribe(&raid_id).await.unwrap();
+    assert_eq!(server.expect_deletion().await, raid_id);
+    assert!(!client.subscription_ids().contains_key("channel.raid"));
+
+    // the first connection stays silent past its keepalive timeout
+    let second = server.next_connection().await;
+    let subscriptions = server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+    assert!(subscriptions.iter().all(|s| s["type"] != "channel.raid"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.assert_no_more_subscriptions();
+
+    let ids = expect_subscription_ids(&client, ALWAYS_ON_SUBSCRIPTIONS).await;
+    assert!(ids.values().all(|id| id.ends_with(&second.session_id)));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_keeps_session_when_optional_subscription_fails() {
+    let mut server = MockEventSubServer::start(10).await;
+    server.reject_subscriptions(&["channel.channel_points_custom_reward_redemption.add"]);
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let mut rx = client.connect().await.unwrap();
+    let connection = server.next_connection().await;
+    server.expect_subscriptions(ALWAYS_ON_SUBSCRIPTIONS).await;
+
+    let ids = expect_subscription_ids(&client, ALWAYS_ON_SUBSCRIPTIONS - 1).await;
+    assert!(ids.contains_key("channel.chat.message"));
+
+    connection.send(stream_offline("offline-1")).await;
+    assert!(matches!(
+        expect_event(&mut rx).await,
+        TwitchEvent::StreamOffline
+    ));
+    server.assert_no_more_connections();
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_reconnects_when_required_subscription_fails() {
+    let mut server = MockEventSubServer::start(10).await;
+    server.reject_subscriptions(&["channel.chat.message"]);
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel)
+        .await
+        .with_required_subscription("channel.chat.message");
+
+    let _rx = client.connect().await.unwrap();
+    server.next_connection().await;
+    // rewards come first, then the required chat subscription fails
+    server.expect_subscriptions(2).await;
+
+    let second = server.next_connection().await;
+    assert_eq!(second.session_id, "session-2");
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_rejects_second_connect() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let _rx = client.connect().await.unwrap();
+    server.next_connection().await;
+
+    assert!(matches!(
+        client.connect().await,
+        Err(Error::AlreadyConnected)
+    ));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.assert_no_more_connections();
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_eventsub_client_unsubscribe_reports_api_errors() {
+    let mut server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+
+    let err = client.unsubscribe("missing").await.unwrap_err();
+
+    assert!(
+        matches!(&err, Error::Api { status: 404, message } if message == "not found"),
+        "unexpected error: {err:?}"
+    );
+    assert_eq!(server.expect_deletion().await, "missing");
+}
+
+#[tokio::test]
+async fn test_eventsub_client_lists_subscriptions_across_pages() {
+    let server = MockEventSubServer::start(10).await;
+    let cancel = CancellationToken::new();
+    let client = test_client(&server, &cancel).await;
+    assert_eq!(client.cost_status(), None);
+
+    let subscriptions = client.list_subscriptions().await.unwrap();
+
+    let summary: Vec<_> = subscriptions
+        .iter()
+        .map(|s| (s.sub_type.as_str(), s.is_enabled(), s.cost))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("channel.chat.message", true, 1),
+            ("channel.raid", false, 0)
+        ]
+    );
+
+    let cost = client.cost_status().unwrap();
+    assert_eq!(
+        (cost.total, cost.total_cost, cost.max_total_cost),
+        (2, 1, 10)
+    );
+    assert_eq!(cost.remaining(), 9);
+}
@@ -3,90 +3,13 @@
 //! These tests demonstrate how to test WebSocket clients by running a local
 //! mock server that simulates the Twitch IRC protocol.
 
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures_util::{SinkExt, StreamExt};
-use tokio::net::TcpListener;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
 use tokio_util::sync::CancellationToken;
 
-use twitch_sdk::{IrcClient, TokenManager, TwitchEvent};
-
-struct MockIrcServer {
-    addr: SocketAddr,
-    outgoing_tx: mpsc::Sender<String>,
-    incoming_rx: mpsc::Receiver<String>,
-}
-
-impl MockIrcServer {
-    async fn start() -> Self {
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let addr = listener.local_addr().unwrap();
-
-        let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<String>(32);
-        let (incoming_tx, incoming_rx) = mpsc::channel::<String>(32);
-
-        tokio::spawn(async move {
-            let (stream, _) = listener.accept().await.unwrap();
-            let ws_stream = accept_async(stream).await.unwrap();
-            let (mut write, mut read) = ws_stream.split();
-
-            loop {
-                tokio::select! {
-                    Some(msg) = outgoing_rx.recv() => {
-                        if write.send(Message::Text(msg)).await.is_err() {
-                            break;
-                        }
-                    }
-                    msg = read.next() => {
-                        match msg {
-                            Some(Ok(Message::Text(text))) => {
-                                let _ = incoming_tx.send(text).await;
-                            }
-                            Some(Ok(Message::Close(_))) | None => break,
-                            _ => {}
-                        }
-                    }
-                }
-            }
-        });
-
-        Self {
-            addr,
-            outgoing_tx,
-            incoming_rx,
-        }
-    }
-
-    fn url(&self) -> String {
-        format!("ws://{}", self.addr)
-    }
-
-    async fn send(&self, msg: &str) {
-        self.outgoing_tx.send(msg.to_string()).await.unwrap();
-    }
-
-    async fn recv(&mut self) -> Option<String> {
-        tokio::time::timeout(Duration::from_secs(2), self.incoming_rx.recv())
-            .await
-            .ok()
-            .flatten()
-    }
-
-    async fn expect_contains(&mut self, pattern: &str) -> String {
-        let msg = self.recv().await.expect("Expected a message but got none");
-        assert!(
-            msg.contains(pattern),
-            "Expected message containing '{}', got: {}",
-            pattern,
-            msg
-        );
-        msg
-    }
-}
+use twitch_sdk::test_support::MockIrcServer;
+use twitch_sdk::{Error, IrcClient, TokenManager, TwitchEvent};
 
 async fn test_token_manager() -> Arc<TokenManager> {
     let tm = Arc::new(TokenManager::new(
@@ -110,7 +33,7 @@ async fn test_irc_client_sends_handshake_on_connect() {
     let token_manager = test_token_manager().await;
     let cancel = CancellationToken::new();
 
-    let mut client = IrcClient::new(
+    let client = IrcClient::new(
         token_manager,
         "test_nick".to_string(),
         "test_channel".to_string(),
@@ -130,13 +53,33 @@ async fn test_irc_client_sends_handshake_on_connect() {
     cancel.cancel();
 }
 
+#[tokio::test]
+async fn test_anonymous_irc_client_skips_pass() {
+    let mut server = MockIrcServer::start().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::anonymous("test_channel".to_string())
+        .with_url(server.url())
+        .with_cancel_token(cancel.clone());
+
+    let _rx = client.connect().await.unwrap();
+
+    server.expect_contains("NICK justinfan12345").await;
+    server
+        .expect_contains("CAP REQ :twitch.tv/tags twitch.tv/commands")
+        .await;
+    server.expect_contains("JOIN #test_channel").await;
+
+    cancel.cancel();
+}
+
 #[tokio::test]
 async fn test_irc_client_responds_to_ping() {
     let mut server = MockIrcServer::start().await;
     let token_manager = test_token_manager().await;
     let cancel = CancellationToken::new();
 
-    let mut client = IrcClient::new(
+    let client = IrcClient::new(
         token_manager,
         "test_nick".to_string(),
         "test_channel".to_string(),
@@ -163,7 +106,7 @@ async fn test_irc_client_receives_chat_message() {
     let token_manager = test_token_manager().await;
     let cancel = CancellationToken::new();
 
-    let mut client = IrcClient::new(
+    let client = IrcClient::new(
         token_manager,
         "test_nick".to_string(),
         "test_channel".to_string(),
@@ -196,13 +139,178 @@ async fn test_irc_client_receives_chat_message() {
     cancel.cancel();
 }
 
+#[tokio::test]
+async fn test_irc_client_emits_connected_on_own_join() {
+    let server = MockIrcServer::start_ignoring_joins().await;
+    let token_manager = test_token_manager().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::new(
+        token_manager,
+        "test_nick".to_string(),
+        "test_channel".to_string(),
+    )
+    .with_url(server.url())
+    .with_connection_events()
+    .with_cancel_token(cancel.clone());
+
+    let mut rx = client.connect().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // another user's JOIN is not a confirmation
+    server
+        .send(":someone!someone@someone.tmi.twitch.tv JOIN #test_channel")
+        .await;
+    server
+        .send(":test_nick!test_nick@test_nick.tmi.twitch.tv JOIN #test_channel")
+        .await;
+
+    let event = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("Channel closed unexpectedly");
+    assert!(
+        matches!(event, TwitchEvent::Connected { session_id: None }),
+        "Expected Connected, got {event:?}"
+    );
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(rx.try_recv().is_err());
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_accepts_roomstate_as_join_confirmation() {
+    let server = MockIrcServer::start_ignoring_joins().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::anonymous("test_channel".to_string())
+        .with_url(server.url())
+        .with_join_timeout(Duration::from_millis(100))
+        .with_cancel_token(cancel.clone());
+
+    let mut rx = client.connect().await.unwrap();
+
+    server
+        .send("@emote-only=0;room-id=1337 :tmi.twitch.tv ROOMSTATE #test_channel")
+        .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    server
+        .send("@user-id=12345 :testuser!testuser@testuser.tmi.twitch.tv PRIVMSG #test_channel :still here")
+        .await;
+
+    let mut next_event = async || {
+        tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("Timeout waiting for event")
+            .expect("Channel closed unexpectedly")
+    };
+    assert!(matches!(
+        next_event().await,
+        TwitchEvent::RoomState {
+            emote_only: Some(false),
+            ..
+        }
+    ));
+    assert!(matches!(
+        next_event().await,
+        TwitchEvent::ChatMessage { text, .. } if text == "still here"
+    ));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_drops_connection_without_join_confirmation() {
+    let mut server = MockIrcServer::start_ignoring_joins().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::anonymous("test_channel".to_string())
+        .with_url(server.url())
+        .with_join_timeout(Duration::from_millis(100))
+        .with_cancel_token(cancel.clone());
+
+    let _rx = client.connect().await.unwrap();
+
+    server.expect_contains("NICK").await;
+    server.expect_contains("CAP REQ").await;
+    server.expect_contains("JOIN #test_channel").await;
+    server.expect_disconnect().await;
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_drops_connection_on_join_notice() {
+    let mut server = MockIrcServer::start_ignoring_joins().await;
+    let token_manager = test_token_manager().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::new(
+        token_manager,
+        "test_nick".to_string(),
+        "test_channel".to_string(),
+    )
+    .with_url(server.url())
+    .with_cancel_token(cancel.clone());
+
+    let _rx = client.connect().await.unwrap();
+
+    server.expect_contains("PASS").await;
+    server.expect_contains("NICK").await;
+    server.expect_contains("CAP REQ").await;
+    server.expect_contains("JOIN #test_channel").await;
+    server
+        .send(
+            "@msg-id=msg_channel_suspended :tmi.twitch.tv NOTICE #test_channel \
+             :This channel does not exist or has been suspended.",
+        )
+        .await;
+    server.expect_disconnect().await;
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_stops_on_login_failure() {
+    let mut server = MockIrcServer::start_ignoring_joins().await;
+    let token_manager = test_token_manager().await;
+
+    let client = IrcClient::new(
+        token_manager,
+        "test_nick".to_string(),
+        "test_channel".to_string(),
+    )
+    .with_url(server.url());
+
+    let mut rx = client.connect().await.unwrap();
+
+    server.expect_contains("PASS").await;
+    server
+        .send(":tmi.twitch.tv NOTICE * :Login authentication failed")
+        .await;
+
+    // the task ends instead of waiting to reconnect, closing the channel
+    let closed = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("Timeout waiting for the event channel to close");
+    assert!(closed.is_none());
+
+    let err = client.shutdown().await.unwrap_err();
+    assert!(
+        matches!(&err, Error::LoginFailed(notice) if notice == "Login authentication failed"),
+        "unexpected error: {err:?}"
+    );
+}
+
 #[tokio::test]
 async fn test_irc_client_handles_multiple_messages() {
     let server = MockIrcServer::start().await;
     let token_manager = test_token_manager().await;
     let cancel = CancellationToken::new();
 
-    let mut client = IrcClient::new(
+    let client = IrcClient::new(
         token_manager,
         "test_nick".to_string(),
         "test_channel".to_string(),
@@ -258,7 +366,7 @@ async fn test_irc_client_cancellation() {
     let token_manager = test_token_manager().await;
     let cancel = CancellationToken::new();
 
-    let mut client = IrcClient::new(
+    let client = IrcClient::new(
         token_manager,
         "test_nick".to_string(),
         "test_channel".to_string(),
@@ -275,3 +383,108 @@ async fn test_irc_client_cancellation() {
     let result = rx.try_recv();
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_irc_client_records_raw_lines() {
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let server = MockIrcServer::start_ignoring_joins().await;
+    let token_manager = test_token_manager().await;
+    let cancel = CancellationToken::new();
+    let buffer = SharedBuffer::default();
+
+    let client = IrcClient::new(
+        token_manager,
+        "test_nick".to_string(),
+        "test_channel".to_string(),
+    )
+    .with_url(server.url())
+    .with_raw_writer(buffer.clone())
+    .with_cancel_token(cancel.clone());
+
+    let mut rx = client.connect().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let line = "@user-id=1;display-name=User1 :u1 PRIVMSG #ch :Recorded";
+    server.send(line).await;
+
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let recorded = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert_eq!(recorded, format!("{line}\n"));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_drops_events_instead_of_reconnecting_when_full() {
+    let mut server = MockIrcServer::start().await;
+    let token_manager = test_token_manager().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::new(
+        token_manager,
+        "test_nick".to_string(),
+        "test_channel".to_string(),
+    )
+    .with_url(server.url())
+    .with_event_send_timeout(Duration::from_millis(10))
+    .with_cancel_token(cancel.clone());
+
+    let mut rx = client.connect().await.unwrap();
+    for _ in 0..4 {
+        server.recv().await;
+    }
+
+    // nothing reads `rx`, so everything past its buffer of 100 is dropped
+    let burst = (0..105)
+        .map(|i| format!("@user-id={i};display-name=User{i} :u PRIVMSG #ch :message {i}"))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    server.send(&burst).await;
+
+    server.send("PING :tmi.twitch.tv").await;
+    server.expect_contains("PONG :tmi.twitch.tv").await;
+    assert_eq!(client.dropped_events(), 5);
+
+    match rx.recv().await {
+        Some(TwitchEvent::ChatMessage { text, .. }) => assert_eq!(text, "message 0"),
+        other => panic!("Expected ChatMessage, got {:?}", other),
+    }
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn test_irc_client_rejects_second_connect() {
+    let server = MockIrcServer::start().await;
+    let cancel = CancellationToken::new();
+
+    let client = IrcClient::anonymous("test_channel".to_string())
+        .with_url(server.url())
+        .with_cancel_token(cancel.clone());
+
+    let _rx = client.connect().await.unwrap();
+
+    assert!(matches!(
+        client.connect().await,
+        Err(Error::AlreadyConnected)
+    ));
+
+    cancel.cancel();
+}